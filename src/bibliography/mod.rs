@@ -54,6 +54,10 @@ pub enum Bibliography {
     /// [DOI](https://doi.org/10.1007/s00190-006-0023-2)
     Fuk06,
 
+    /// W.A. Heiskanen and H. Moritz, 1967: *Physical Geodesy*.
+    /// W.H. Freeman and Company, San Francisco, 364 pp.
+    Hei67,
+
     /// IOGP, 2019: *Coordinate Conversions and Transformations including Formulas. Revised - September 2019*
     /// IOGP Geomatics Guidance Note Number 7, part 2. IOGP publication no. 373-7-2, 162 pp.
     /// [pdf](https://www.iogp.org/wp-content/uploads/2019/09/373-07-02.pdf)
@@ -96,6 +100,11 @@ pub enum Bibliography {
     /// [pdf](https://gfzpublic.gfz-potsdam.de/rest/items/item_8827_5/component/file_130038/content).
     Kru12,
 
+    /// H. Moritz, 2000: *Geodetic Reference System 1980*.
+    /// Journal of Geodesy 74, pp. 128–133.
+    /// [DOI](https://doi.org/10.1007/s001900050278)
+    Mor00,
+
     /// A. C. Ruffhead (2016):  The SMITSWAM method of datum transformations
     /// consisting of Standard Molodensky in two stages with applied misclosures,
     /// Survey Review, 48:350, pp. 376-384,
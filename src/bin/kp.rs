@@ -1,6 +1,7 @@
 use clap::Parser;
 use geodesy::prelude::*;
-use log::{info, trace}; // debug, error, warn: not used
+use geodesy::{geohash, maidenhead, mgrs, pluscode};
+use log::{info, trace, warn}; // debug, error: not used
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
@@ -33,11 +34,23 @@ struct Cli {
     #[clap(short = 'd', long)]
     decimals: Option<usize>,
 
+    /// Input format: 'plain', 'mgrs', 'geohash', 'maidenhead' or 'pluscode'.
+    /// Geocodes are converted to the center of their cell, as geographical
+    /// coordinates in radians, i.e. the internal representation
+    #[clap(long, value_parser = parse_format, default_value = "plain")]
+    input_format: Format,
+
+    /// Output format: 'plain', or one of the geocode formats, optionally
+    /// with a precision, e.g. 'mgrs:3'. Geocodes are computed from
+    /// geographical coordinates in radians, i.e. the internal representation
+    #[clap(long, value_parser = parse_format, default_value = "plain")]
+    output_format: Format,
+
     /// Activate debug mode
     #[clap(long)]
     debug: bool,
 
-    /// Report fwd-inv roundtrip deviation
+    /// Report fwd-inv roundtrip deviation. Requires plain output format
     #[clap(short, long)]
     roundtrip: bool,
 
@@ -56,6 +69,69 @@ struct Cli {
     args: Vec<String>,
 }
 
+/// The textual representations of coordinates, supported for input and output
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    /// Whitespace separated numbers, possibly in sexagesimal notation
+    Plain,
+    /// MGRS/USNG grid references, with the given number of digits per axis
+    Mgrs(usize),
+    /// Geohashes, with the given number of characters
+    Geohash(usize),
+    /// Maidenhead locators, with the given number of character pairs
+    Maidenhead(usize),
+    /// Open Location Codes (Plus Codes), with the given number of digits
+    PlusCode(usize),
+}
+
+impl Format {
+    // Decode a grid reference or geocode to the center of its cell
+    fn decode(self, text: &str) -> Result<Coor4D, Error> {
+        match self {
+            Format::Plain => Err(Error::Unsupported("Plain text is not a geocode".into())),
+            Format::Mgrs(_) => mgrs::decode(text),
+            Format::Geohash(_) => geohash::decode(text),
+            Format::Maidenhead(_) => maidenhead::decode(text),
+            Format::PlusCode(_) => pluscode::decode(text),
+        }
+    }
+
+    fn encode(self, coord: Coor4D) -> Result<String, Error> {
+        match self {
+            Format::Plain => Err(Error::Unsupported("Plain text is not a geocode".into())),
+            Format::Mgrs(precision) => mgrs::encode(coord, precision),
+            Format::Geohash(precision) => geohash::encode(coord, precision),
+            Format::Maidenhead(precision) => maidenhead::encode(coord, precision),
+            Format::PlusCode(precision) => pluscode::encode(coord, precision),
+        }
+    }
+}
+
+fn parse_format(format: &str) -> Result<Format, String> {
+    let (name, precision) = match format.split_once(':') {
+        Some((name, precision)) => (name, Some(precision)),
+        None => (format, None),
+    };
+    let precision = match precision {
+        Some(p) => Some(p.parse::<usize>().map_err(|e| e.to_string())?),
+        None => None,
+    };
+    let format = match name.to_lowercase().as_str() {
+        "plain" => return Ok(Format::Plain),
+        "mgrs" | "usng" => Format::Mgrs(precision.unwrap_or(mgrs::MAX_PRECISION)),
+        "geohash" => Format::Geohash(precision.unwrap_or(9)),
+        "maidenhead" => Format::Maidenhead(precision.unwrap_or(3)),
+        "pluscode" | "olc" => Format::PlusCode(precision.unwrap_or(10)),
+        _ => return Err(format!("Unknown format: '{format}'")),
+    };
+
+    // Check the precision by encoding a dummy coordinate
+    format
+        .encode(Coor4D::raw(0., 0., 0., 0.))
+        .map_err(|e| e.to_string())?;
+    Ok(format)
+}
+
 fn main() -> Result<(), anyhow::Error> {
     let mut options = Cli::parse();
     env_logger::Builder::new()
@@ -72,6 +148,15 @@ fn main() -> Result<(), anyhow::Error> {
         eprintln!("options: {options:#?}");
     }
 
+    // Roundtrip deviations are differences, not coordinates, so they are
+    // always given as plain numbers
+    if options.roundtrip && options.output_format != Format::Plain {
+        return Err(Error::General(
+            "The output format must be 'plain' when reporting roundtrip deviations",
+        )
+        .into());
+    }
+
     // A dash, '-', given as file name indicates stdin
     if options.args.is_empty() {
         options.args.push("-".to_string());
@@ -120,15 +205,23 @@ fn main() -> Result<(), anyhow::Error> {
             }
 
             // Convert the text representation to a Coor4D
-            args.extend(&(["0", "0", "0", "NaN", "0"][args.len()..]));
-            let mut b: Vec<f64> = vec![];
-            for e in args {
-                b.push(angular::parse_sexagesimal(e));
-            }
-            b[2] = options.height.unwrap_or(b[2]);
-            b[3] = options.time.unwrap_or(b[3]);
+            let mut coord = match options.input_format {
+                Format::Plain => {
+                    args.extend(&(["0", "0", "0", "NaN", "0"][args.len()..]));
+                    let mut b: Vec<f64> = vec![];
+                    for e in args {
+                        b.push(angular::parse_sexagesimal(e));
+                    }
+                    Coor4D([b[0], b[1], b[2], b[3]])
+                }
+                format => format.decode(&args.join(" ")).unwrap_or_else(|e| {
+                    warn!("{e}");
+                    Coor4D::nan()
+                }),
+            };
+            coord[2] = options.height.unwrap_or(coord[2]);
+            coord[3] = options.time.unwrap_or(coord[3]);
 
-            let coord = Coor4D([b[0], b[1], b[2], b[3]]);
             number_of_operands_read += 1;
             operands.push(coord);
 
@@ -207,6 +300,18 @@ fn transform(
         .unwrap_or(if operands[0][0] > 1000. { 5 } else { 10 });
 
     // Finally output the transformed coordinates
+    if options.output_format != Format::Plain {
+        for coord in operands {
+            match options.output_format.encode(*coord) {
+                Ok(code) => println!("{code}"),
+                Err(e) => {
+                    warn!("{e}");
+                    println!("NaN");
+                }
+            }
+        }
+        return Ok(n);
+    }
     for coord in operands {
         println!(
             "{1:.0$} {2:.0$} {3:.0$} {4:.0$} ",
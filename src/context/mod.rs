@@ -9,6 +9,9 @@ pub mod plain;
 #[cfg(feature = "with_plain")]
 pub use plain::Plain;
 
+#[cfg(feature = "with_plain")]
+pub mod parallel;
+
 // ----- T H E   C O N T E X T   T R A I T ---------------------------------------------
 
 /// Modes of communication between the *Rust Geodesy* internals and the external
@@ -1,29 +1,337 @@
-use crate::context_authoring::*;
-pub use std::path::PathBuf;
-
-// ----- T H E   P A R A L L E L   C O N T E X T   P R O V I D E R ---------------------
-
-/// Stub for a planned parallel-execution context
-
-pub struct CoordinateSubSet<'a> {
-    operands: &'a mut dyn CoordinateSet,
-    begin: usize,
-    end: usize
-    // .. and a mock Coordinate metadata struct, to be mangled by the pipeline operator
-}
-// let end = operands.len();
-// let mut cs = CoordinateSubSet { operands, begin: 0, end };
-
-impl<'a> CoordinateSet for CoordinateSubSet<'a> {
-    fn len(&self) -> usize {
-        self.operands.len()
-    }
-
-    fn get(&self, index: usize) -> Coord {
-        self.operands.get(index + self.begin)
-    }
-
-    fn set(&mut self, index: usize, value: &Coord) {
-        self.operands.set(index + self.begin, value);
-    }
-}
+use crate::authoring::*;
+use std::{num::NonZeroUsize, sync::Arc};
+
+// ----- T H E   P A R A L L E L   C O N T E X T   P R O V I D E R ---------------------
+
+/// A context provider for transforming large coordinate sets using all
+/// available cores.
+///
+/// Operator instantiation, and the resolution of grids and resources, is
+/// delegated to an embedded [Plain] context, so `Parallel` finds exactly the
+/// same operators, macros and grids as `Plain` does.
+///
+/// In `apply(...)`, the operands are split into disjoint
+/// [CoordinateSubSet] windows, which are handed to a pool of scoped worker
+/// threads, each running `Op::apply(...)` on its own window. The return
+/// value is the combined number of successes over all windows.
+#[derive(Debug)]
+pub struct Parallel {
+    plain: Plain,
+    threads: usize,
+}
+
+// Number of coordinate tuples each worker thread handles per round. Limits the
+// memory needed for the per-window buffers, when transforming huge data sets
+const WINDOW_SIZE: usize = 64 * 1024;
+
+// Below this number of tuples, the thread management overhead is not worth it
+const MINIMUM_PARALLEL_SIZE: usize = 1024;
+
+impl Parallel {
+    /// Override the number of worker threads. Defaults to the number of
+    /// cores available. A value of 0 is interpreted as 1.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// The number of worker threads used by `apply(...)`
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    fn with_plain(plain: Plain) -> Parallel {
+        let threads = std::thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1);
+        Parallel { plain, threads }
+    }
+}
+
+impl Default for Parallel {
+    fn default() -> Parallel {
+        Parallel::with_plain(Plain::default())
+    }
+}
+
+impl Context for Parallel {
+    fn new() -> Parallel {
+        Parallel::with_plain(Plain::new())
+    }
+
+    /// Instantiate an operator. Recognizes PROJ syntax, just like [Plain]
+    fn op(&mut self, definition: &str) -> Result<OpHandle, Error> {
+        self.plain.op(definition)
+    }
+
+    fn apply(
+        &self,
+        op: OpHandle,
+        direction: Direction,
+        operands: &mut dyn CoordinateSet,
+    ) -> Result<usize, Error> {
+        let op = self.plain.operator(op)?;
+        let n = operands.len();
+
+        // Small jobs are handled directly, with no thread overhead
+        if self.threads < 2 || n < MINIMUM_PARALLEL_SIZE {
+            return Ok(op.apply(self, operands, direction));
+        }
+
+        let window_size = WINDOW_SIZE.min((n + self.threads - 1) / self.threads);
+        let round_size = window_size * self.threads;
+        let forward = direction == Fwd;
+        let mut successes = 0_usize;
+
+        // Handle the operands in rounds, so we never need buffer space
+        // for more than `round_size` coordinate tuples at a time
+        for round_begin in (0..n).step_by(round_size) {
+            let round_end = (round_begin + round_size).min(n);
+
+            let mut windows: Vec<CoordinateSubSet> = (round_begin..round_end)
+                .step_by(window_size)
+                .map(|begin| {
+                    CoordinateSubSet::new(operands, begin, (begin + window_size).min(round_end))
+                })
+                .collect();
+
+            successes += std::thread::scope(|scope| {
+                let workers: Vec<_> = windows
+                    .iter_mut()
+                    .map(|window| {
+                        let direction = if forward { Fwd } else { Inv };
+                        scope.spawn(move || op.apply(self, window, direction))
+                    })
+                    .collect();
+                // A panic in a worker is propagated, so we never write back
+                // a window that was not fully processed
+                workers
+                    .into_iter()
+                    .map(|worker| {
+                        worker
+                            .join()
+                            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                    })
+                    .sum::<usize>()
+            });
+
+            for window in &windows {
+                window.write_back(operands);
+            }
+        }
+
+        Ok(successes)
+    }
+
+    fn globals(&self) -> BTreeMap<String, String> {
+        self.plain.globals()
+    }
+
+    fn steps(&self, op: OpHandle) -> Result<&Vec<String>, Error> {
+        self.plain.steps(op)
+    }
+
+    fn params(&self, op: OpHandle, index: usize) -> Result<ParsedParameters, Error> {
+        self.plain.params(op, index)
+    }
+
+    fn register_op(&mut self, name: &str, constructor: OpConstructor) {
+        self.plain.register_op(name, constructor);
+    }
+
+    fn register_resource(&mut self, name: &str, definition: &str) {
+        self.plain.register_resource(name, definition);
+    }
+
+    fn get_op(&self, name: &str) -> Result<OpConstructor, Error> {
+        self.plain.get_op(name)
+    }
+
+    fn get_resource(&self, name: &str) -> Result<String, Error> {
+        self.plain.get_resource(name)
+    }
+
+    fn get_blob(&self, name: &str) -> Result<Vec<u8>, Error> {
+        self.plain.get_blob(name)
+    }
+
+    fn get_grid(&self, name: &str) -> Result<Arc<dyn Grid>, Error> {
+        self.plain.get_grid(name)
+    }
+}
+
+// ----- C O O R D I N A T E   S U B S E T S -------------------------------------------
+
+/// A window of consecutive elements, `begin..end`, of a larger [CoordinateSet].
+///
+/// Since we cannot know whether a user supplied `CoordinateSet` is safe to
+/// access from more than one thread, the window holds its own copy of the
+/// elements. The copy is made on instantiation, and handed back to the parent
+/// set by `write_back(...)`, after the window has been transformed.
+#[derive(Debug, Default, Clone)]
+pub struct CoordinateSubSet {
+    begin: usize,
+    operands: Vec<Coor4D>,
+}
+
+impl CoordinateSubSet {
+    /// Copy the elements `begin..end` of `operands` into a new window
+    pub fn new(operands: &dyn CoordinateSet, begin: usize, end: usize) -> CoordinateSubSet {
+        let end = end.min(operands.len());
+        let operands = (begin..end).map(|i| operands.get_coord(i)).collect();
+        CoordinateSubSet { begin, operands }
+    }
+
+    /// Index of the first element of the window, in the parent set
+    pub fn begin(&self) -> usize {
+        self.begin
+    }
+
+    /// Index of the element immediately after the window, in the parent set
+    pub fn end(&self) -> usize {
+        self.begin + self.operands.len()
+    }
+
+    /// Hand the (presumably transformed) elements back to the parent set
+    pub fn write_back(&self, operands: &mut dyn CoordinateSet) {
+        for (i, coord) in self.operands.iter().enumerate() {
+            operands.set_coord(self.begin + i, coord);
+        }
+    }
+}
+
+impl CoordinateSet for CoordinateSubSet {
+    fn len(&self) -> usize {
+        self.operands.len()
+    }
+
+    fn get_coord(&self, index: usize) -> Coor4D {
+        self.operands[index]
+    }
+
+    fn set_coord(&mut self, index: usize, value: &Coor4D) {
+        self.operands[index] = *value;
+    }
+}
+
+// ----- T E S T S ------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A set of geographical coordinates large enough to actually go parallel
+    fn a_lot_of_coordinates(n: usize) -> Vec<Coor4D> {
+        (0..n)
+            .map(|i| {
+                let f = i as f64 / n as f64;
+                Coor4D::geo(54. + 4. * f, 8. + 7. * f, 0., 0.)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn subsets() -> Result<(), Error> {
+        let mut operands = Vec::from(some_basic_coor4dinates());
+        let mut window = CoordinateSubSet::new(&operands, 1, 5);
+        assert_eq!(window.begin(), 1);
+        assert_eq!(window.end(), 2);
+        assert_eq!(window.len(), 1);
+        assert_eq!(window.get_coord(0)[0], 59.);
+
+        window.set_coord(0, &Coor4D::raw(1., 2., 3., 4.));
+        assert_eq!(operands[1][0], 59.);
+        window.write_back(&mut operands);
+        assert_eq!(operands[0][0], 55.);
+        assert_eq!(operands[1][0], 1.);
+        Ok(())
+    }
+
+    #[test]
+    fn identical_to_plain() -> Result<(), Error> {
+        let mut plain = Plain::new();
+        let mut parallel = Parallel::new();
+        parallel.set_threads(4);
+        assert_eq!(parallel.threads(), 4);
+
+        let definition = "utm zone=32 | helmert x=-87 y=-96 z=-120";
+        let op_plain = plain.op(definition)?;
+        let op_parallel = parallel.op(definition)?;
+        assert_eq!(parallel.steps(op_parallel)?.len(), 2);
+
+        // Odd size, to make sure the last window is a partial one
+        let n = 3 * MINIMUM_PARALLEL_SIZE + 17;
+        let mut expected = a_lot_of_coordinates(n);
+        let mut data = expected.clone();
+
+        assert_eq!(plain.apply(op_plain, Fwd, &mut expected)?, n);
+        assert_eq!(parallel.apply(op_parallel, Fwd, &mut data)?, n);
+        assert_eq!(data, expected);
+
+        assert_eq!(parallel.apply(op_parallel, Inv, &mut data)?, n);
+        plain.apply(op_plain, Inv, &mut expected)?;
+        assert_eq!(data, expected);
+        Ok(())
+    }
+
+    // An operator which panics when it meets a coordinate with the first element 42
+    fn panicking_fwd(_op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+        for i in 0..operands.len() {
+            assert!(operands.get_coord(i)[0] != 42., "Panic!");
+            operands.set_coord(i, &Coor4D::origin());
+        }
+        operands.len()
+    }
+
+    fn panicking(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+        Op::plain(parameters, InnerOp(panicking_fwd), None, &[], ctx)
+    }
+
+    #[test]
+    fn panicking_worker() -> Result<(), Error> {
+        let mut ctx = Parallel::new();
+        ctx.set_threads(4);
+        ctx.register_op("panicking", OpConstructor(panicking));
+        let op = ctx.op("panicking")?;
+
+        let n = 2 * MINIMUM_PARALLEL_SIZE;
+        let mut data = vec![Coor4D::raw(1., 2., 3., 4.); n];
+        data[n - 1][0] = 42.;
+
+        // The panic is propagated to the caller...
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            ctx.apply(op, Fwd, &mut data)
+        }));
+        assert!(result.is_err());
+
+        // ...and no partially processed results are written back
+        assert!(data.iter().all(|c| c[0] != 0.));
+        Ok(())
+    }
+
+    #[test]
+    fn grids_and_resources() -> Result<(), Error> {
+        let mut ctx = Parallel::new();
+
+        // Resources are resolved the same way as in Plain
+        assert!(ctx.get_resource("stupid:way")?.ends_with("addone"));
+        let op = ctx.op("stupid:way")?;
+        let mut data = some_basic_coor2dinates();
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 2);
+        assert_eq!(data[0][0], 56.);
+
+        // ...and so are grids. Copenhagen is inside, London outside the grid
+        let op = ctx.op("gridshift grids=test.datum")?;
+        let n = 2 * MINIMUM_PARALLEL_SIZE;
+        let cph = Coor4D::geo(55., 12., 0., 0.);
+        let ldn = Coor4D::geo(51.505, -0.09, 0., 0.);
+        let mut data: Vec<Coor4D> = (0..n).map(|i| if i % 2 == 0 { cph } else { ldn }).collect();
+
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, n / 2);
+        let res = data[0].to_geo();
+        assert!((res[0] - 55.015278).abs() < 1e-6);
+        assert!((res[1] - 12.003333).abs() < 1e-6);
+        assert!(data[1][0].is_nan());
+        assert_eq!(data[0], data[n - 2]);
+        Ok(())
+    }
+}
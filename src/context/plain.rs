@@ -1,5 +1,6 @@
 #[cfg(feature = "with_plain")]
 use crate::authoring::*;
+use crate::grid::geotiff::GeoTiffGrid;
 use crate::grid::ntv2::Ntv2Grid;
 use once_cell::sync::Lazy;
 use std::{
@@ -53,19 +54,35 @@ impl GridCollection {
                 continue;
             };
 
-            if ext == "gsb" {
-                self.0
-                    .insert(name.to_string(), Arc::new(Ntv2Grid::new(&grid)?));
-            } else {
-                self.0
-                    .insert(name.to_string(), Arc::new(BaseGrid::gravsoft(&grid)?));
-            }
-            if let Some(grid) = self.0.get(name) {
-                return Ok(grid.clone());
-            }
+            let grid = Self::parse(ext, &grid)?;
+            self.0.insert(name.to_string(), grid.clone());
+            return Ok(grid);
         }
         Err(Error::NotFound(name.to_string(), ": Grid".to_string()))
     }
+
+    // Select the grid parser from the file extension, or - for unknown
+    // extensions - from the magic bytes of the formats identifiable by one.
+    // GTX and BYN have no magic bytes, so they must be named by extension
+    fn parse(ext: &str, buf: &[u8]) -> Result<Arc<dyn Grid>, Error> {
+        const TIFF_MAGIC: [&[u8]; 4] = [b"II*\0", b"MM\0*", b"II+\0", b"MM\0+"];
+        let is_isg = || {
+            buf[..buf.len().min(4096)]
+                .windows(13)
+                .any(|w| w == b"begin_of_head")
+        };
+        Ok(match ext {
+            "gsb" => Arc::new(Ntv2Grid::new(buf)?),
+            "tif" | "tiff" => Arc::new(GeoTiffGrid::new(buf)?),
+            "gtx" => Arc::new(BaseGrid::gtx(buf)?),
+            "byn" => Arc::new(BaseGrid::byn(buf)?),
+            "isg" => Arc::new(BaseGrid::isg(buf)?),
+            _ if buf.starts_with(b"NUM_OREC") => Arc::new(Ntv2Grid::new(buf)?),
+            _ if TIFF_MAGIC.iter().any(|m| buf.starts_with(m)) => Arc::new(GeoTiffGrid::new(buf)?),
+            _ if is_isg() => Arc::new(BaseGrid::isg(buf)?),
+            _ => Arc::new(BaseGrid::gravsoft(buf)?),
+        })
+    }
 }
 
 const BAD_ID_MESSAGE: Error = Error::General("Plain: Unknown operator id");
@@ -79,6 +96,12 @@ impl Plain {
     pub fn clear_grids() {
         GRIDS.lock().unwrap().0.clear();
     }
+
+    // Helper for the Parallel context, which delegates the operator
+    // bookkeeping to an embedded Plain
+    pub(crate) fn operator(&self, op: OpHandle) -> Result<&Op, Error> {
+        self.operators.get(&op).ok_or(BAD_ID_MESSAGE)
+    }
 }
 
 impl Default for Plain {
@@ -134,7 +157,7 @@ impl Context for Plain {
         direction: Direction,
         operands: &mut dyn CoordinateSet,
     ) -> Result<usize, Error> {
-        let op = self.operators.get(&op).ok_or(BAD_ID_MESSAGE)?;
+        let op = self.operator(op)?;
         Ok(op.apply(self, operands, direction))
     }
 
@@ -366,6 +389,8 @@ mod tests {
         let _op1 = ctx.op("gridshift grids=5458.gsb, 5458_with_subgrid.gsb")?;
         let _op2 = ctx.op("gridshift grids=5458.gsb, 5458_with_subgrid.gsb")?;
         let _op3 = ctx.op("gridshift grids=test.geoid")?;
+        let _op4 = ctx.op("deformation dt=1 grids=test_deformation.tif")?;
+        let _op5 = ctx.op("gridshift grids=test.gtx, test.byn, test.isg")?;
         assert!(ctx.op("gridshift grids=non.existing").is_err());
         Ok(())
     }
@@ -0,0 +1,546 @@
+//! Encoding and decoding of geocodes: Short text strings identifying cells of
+//! a global, hierarchical, latitude/longitude grid.
+//!
+//! Three geocode systems are supported:
+//!
+//! - [`geohash`], the Z-order base 32 geocode by Gustavo Niemeyer (2008)
+//! - [`maidenhead`], the locator system used by radio amateurs
+//! - [`pluscode`], the Open Location Code (Plus Code) system by Google (2014)
+//!
+//! Each of the systems provide the same set of functions: `encode`, computing
+//! the geocode for a position at a given precision, `decode`, returning the
+//! center of the cell identified by a geocode, `bounds`, returning the south
+//! western and north eastern corners of the cell, and `precision`, returning
+//! the precision of a geocode.
+//!
+//! Positions are given and returned as longitude and latitude in radians,
+//! i.e. the internal Geodesy convention. Precisions are given in the natural
+//! unit of each system, as documented by the individual `encode` functions.
+//!
+//! ```
+//! use geodesy::prelude::*;
+//! use geodesy::{geohash, maidenhead, pluscode};
+//!
+//! let zurich = Coor4D::geo(47.365562, 8.524989, 0., 0.);
+//! assert_eq!(geohash::encode(zurich, 9)?, "u0qj3yxsq");
+//! assert_eq!(maidenhead::encode(zurich, 3)?, "JN47gi");
+//! assert_eq!(pluscode::encode(zurich, 10)?, "8FVC9G8F+6X");
+//!
+//! let [sw, ne] = pluscode::bounds("8FVC9G8F+6X")?;
+//! assert!(sw[1] < zurich[1] && zurich[1] < ne[1]);
+//! # Ok::<(), geodesy::Error>(())
+//! ```
+use crate::prelude::*;
+
+// Check and normalize a position, returning (longitude, latitude) in degrees
+fn position(coord: Coor4D, system: &str) -> Result<(f64, f64), Error> {
+    let lon = angular::normalize_symmetric(coord[0]).to_degrees();
+    let lat = coord[1].to_degrees();
+    if !lon.is_finite() || !lat.is_finite() || lat.abs() > 90. {
+        return Err(Error::Invalid(format!(
+            "Cannot encode ({lon}, {lat}) as {system}"
+        )));
+    }
+    Ok((lon, lat))
+}
+
+fn check_precision(precision: usize, range: std::ops::RangeInclusive<usize>) -> Result<(), Error> {
+    if !range.contains(&precision) {
+        return Err(Error::Invalid(format!(
+            "Precision must be in the range {}..={}, got {precision}",
+            range.start(),
+            range.end()
+        )));
+    }
+    Ok(())
+}
+
+// The center of the cell given by its south western and north eastern corners
+fn center(bounds: [Coor4D; 2]) -> Coor4D {
+    let [sw, ne] = bounds;
+    Coor4D::raw((sw[0] + ne[0]) / 2., (sw[1] + ne[1]) / 2., 0., 0.)
+}
+
+// The cell corners, from (lon, lat) in degrees, to the internal representation
+fn corners(west: f64, south: f64, east: f64, north: f64) -> [Coor4D; 2] {
+    [
+        Coor4D::raw(west.to_radians(), south.to_radians(), 0., 0.),
+        Coor4D::raw(east.to_radians(), north.to_radians(), 0., 0.),
+    ]
+}
+
+// ----- G E O H A S H -----------------------------------------------------------------
+
+/// Geohash: Z-order (Morton) encoding of the bisection of the latitude and
+/// longitude ranges, written in a base 32 alphabet. Each character halves
+/// the cell 5 times, alternately along the longitude and the latitude axis,
+/// starting with the longitude.
+pub mod geohash {
+    use super::*;
+
+    const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+    /// The highest supported precision. 12 characters corresponds to cells
+    /// of size 37 mm by 19 mm
+    pub const MAX_PRECISION: usize = 12;
+
+    /// Encode the position `coord` (longitude and latitude in radians) as a
+    /// geohash of `precision` (1..=12) characters
+    pub fn encode(coord: impl Into<Coor4D>, precision: usize) -> Result<String, Error> {
+        let (lon, lat) = position(coord.into(), "geohash")?;
+        check_precision(precision, 1..=MAX_PRECISION)?;
+
+        let mut lon_range = [-180., 180.];
+        let mut lat_range = [-90., 90.];
+        let mut code = String::with_capacity(precision);
+        let mut even = true;
+        for _ in 0..precision {
+            let mut index = 0;
+            for _ in 0..5 {
+                let (range, value) = if even {
+                    (&mut lon_range, lon)
+                } else {
+                    (&mut lat_range, lat)
+                };
+                let mid = (range[0] + range[1]) / 2.;
+                index <<= 1;
+                if value >= mid {
+                    index |= 1;
+                    range[0] = mid;
+                } else {
+                    range[1] = mid;
+                }
+                even = !even;
+            }
+            code.push(ALPHABET[index] as char);
+        }
+        Ok(code)
+    }
+
+    /// The south western and north eastern corners of the cell identified by `code`
+    pub fn bounds(code: &str) -> Result<[Coor4D; 2], Error> {
+        let code = code.trim();
+        if code.is_empty() || code.len() > MAX_PRECISION {
+            return Err(Error::Invalid(format!("Malformed geohash: '{code}'")));
+        }
+
+        let mut lon_range = [-180., 180.];
+        let mut lat_range = [-90., 90.];
+        let mut even = true;
+        for c in code.bytes() {
+            let Some(index) = ALPHABET.iter().position(|&a| a == c.to_ascii_lowercase()) else {
+                return Err(Error::Invalid(format!("Malformed geohash: '{code}'")));
+            };
+            for bit in (0..5).rev() {
+                let range = if even { &mut lon_range } else { &mut lat_range };
+                let mid = (range[0] + range[1]) / 2.;
+                if index & (1 << bit) != 0 {
+                    range[0] = mid;
+                } else {
+                    range[1] = mid;
+                }
+                even = !even;
+            }
+        }
+        Ok(corners(
+            lon_range[0],
+            lat_range[0],
+            lon_range[1],
+            lat_range[1],
+        ))
+    }
+
+    /// The center of the cell identified by `code`
+    pub fn decode(code: &str) -> Result<Coor4D, Error> {
+        Ok(center(bounds(code)?))
+    }
+
+    /// The precision, i.e. the number of characters, of `code`
+    pub fn precision(code: &str) -> Result<usize, Error> {
+        bounds(code)?;
+        Ok(code.trim().len())
+    }
+}
+
+// ----- M A I D E N H E A D -----------------------------------------------------------
+
+/// Maidenhead locators: Pairs of alternately letters and digits, each pair
+/// giving the longitude and latitude of a cell within the cell given by the
+/// preceding pairs. The first pair (the "field") divides the world into
+/// 18 by 18 cells, using the letters A-R. The second pair (the "square")
+/// divides each field into 10 by 10 cells, using digits. The third pair
+/// (the "subsquare") divides each square into 24 by 24 cells, using the
+/// letters a-x. Further pairs alternate between digits and letters a-x.
+pub mod maidenhead {
+    use super::*;
+
+    /// The highest supported precision, i.e. number of pairs
+    pub const MAX_PRECISION: usize = 6;
+
+    // The number of subdivisions along each axis for each pair
+    fn base(pair: usize) -> usize {
+        match pair {
+            0 => 18,
+            p if p % 2 == 1 => 10,
+            _ => 24,
+        }
+    }
+
+    /// Encode the position `coord` (longitude and latitude in radians) as a
+    /// Maidenhead locator of `precision` (1..=6) pairs of characters
+    pub fn encode(coord: impl Into<Coor4D>, precision: usize) -> Result<String, Error> {
+        let (lon, lat) = position(coord.into(), "Maidenhead locator")?;
+        check_precision(precision, 1..=MAX_PRECISION)?;
+
+        let mut lon = lon + 180.;
+        let mut lat = lat + 90.;
+        let mut width = 360.;
+        let mut height = 180.;
+        let mut code = String::with_capacity(2 * precision);
+        for pair in 0..precision {
+            let base = base(pair);
+            width /= base as f64;
+            height /= base as f64;
+            let x = ((lon / width).floor() as usize).min(base - 1);
+            let y = ((lat / height).floor() as usize).min(base - 1);
+            lon -= x as f64 * width;
+            lat -= y as f64 * height;
+            for i in [x, y] {
+                let c = match pair {
+                    0 => b'A' + i as u8,
+                    p if p % 2 == 1 => b'0' + i as u8,
+                    _ => b'a' + i as u8,
+                };
+                code.push(c as char);
+            }
+        }
+        Ok(code)
+    }
+
+    /// The south western and north eastern corners of the cell identified by `code`
+    pub fn bounds(code: &str) -> Result<[Coor4D; 2], Error> {
+        let invalid = || Error::Invalid(format!("Malformed Maidenhead locator: '{code}'"));
+        let text = code.trim().as_bytes();
+        if text.is_empty() || text.len() % 2 == 1 || text.len() > 2 * MAX_PRECISION {
+            return Err(invalid());
+        }
+
+        let mut west = -180.;
+        let mut south = -90.;
+        let mut width = 360.;
+        let mut height = 180.;
+        for (pair, chars) in text.chunks(2).enumerate() {
+            let base = base(pair);
+            width /= base as f64;
+            height /= base as f64;
+            let mut index = [0; 2];
+            for (i, &c) in chars.iter().enumerate() {
+                let value = if pair % 2 == 1 {
+                    c.wrapping_sub(b'0')
+                } else {
+                    c.to_ascii_uppercase().wrapping_sub(b'A')
+                } as usize;
+                if value >= base {
+                    return Err(invalid());
+                }
+                index[i] = value;
+            }
+            west += index[0] as f64 * width;
+            south += index[1] as f64 * height;
+        }
+        Ok(corners(west, south, west + width, south + height))
+    }
+
+    /// The center of the cell identified by `code`
+    pub fn decode(code: &str) -> Result<Coor4D, Error> {
+        Ok(center(bounds(code)?))
+    }
+
+    /// The precision, i.e. the number of character pairs, of `code`
+    pub fn precision(code: &str) -> Result<usize, Error> {
+        bounds(code)?;
+        Ok(code.trim().len() / 2)
+    }
+}
+
+// ----- P L U S   C O D E -------------------------------------------------------------
+
+/// Open Location Code (Plus Code): Up to 10 digits of base 20 pairs, giving
+/// latitude and longitude of cells of size 20°, 1°, 0.05°, 0.0025° and
+/// 0.000125°, followed by up to 5 digits, each subdividing the cell into
+/// a grid of 5 rows by 4 columns. A `+` separator is inserted after the
+/// eighth digit. Codes shorter than 8 digits are padded with zeros.
+///
+/// Only full codes are supported, since recovering a position from a
+/// short code requires a reference location.
+pub mod pluscode {
+    use super::*;
+
+    const ALPHABET: &[u8; 20] = b"23456789CFGHJMPQRVWX";
+    const SEPARATOR: char = '+';
+    const PADDING: char = '0';
+    const SEPARATOR_POSITION: usize = 8;
+    const PAIR_LENGTH: usize = 10;
+
+    /// The highest supported precision, i.e. number of digits
+    pub const MAX_PRECISION: usize = 15;
+
+    // The grid is built from integer units, giving exact cell boundaries.
+    // The number of final grid cells per degree of latitude and longitude
+    const LAT_UNITS: i64 = 8000 * 3125;
+    const LON_UNITS: i64 = 8000 * 1024;
+
+    /// Encode the position `coord` (longitude and latitude in radians) as a
+    /// plus code of `precision` digits. Valid precisions are 2, 4, 6, 8, and
+    /// 10 through 15. The standard precision of 10 digits corresponds to cells
+    /// of size approximately 14 m by 14 m
+    pub fn encode(coord: impl Into<Coor4D>, precision: usize) -> Result<String, Error> {
+        let (lon, lat) = position(coord.into(), "plus code")?;
+        check_precision(precision, 2..=MAX_PRECISION)?;
+        if precision < PAIR_LENGTH && precision % 2 == 1 {
+            return Err(Error::Invalid(format!(
+                "Plus code precision must be even when below {PAIR_LENGTH}, got {precision}"
+            )));
+        }
+
+        // Rounding before truncation avoids floating point noise moving
+        // points on a cell boundary into the neighbouring cell. Points at
+        // the north pole go to the northernmost cell
+        let lat = ((lat + 90.) * LAT_UNITS as f64 * 1e6).round() / 1e6;
+        let lon = ((lon + 180.) * LON_UNITS as f64 * 1e6).round() / 1e6;
+        let mut lat = (lat.floor() as i64).min(180 * LAT_UNITS - 1);
+        let mut lon = (lon.floor() as i64).rem_euclid(360 * LON_UNITS);
+
+        // Build the code from the finest digit and up
+        let mut digits = Vec::with_capacity(MAX_PRECISION);
+        for _ in 0..MAX_PRECISION - PAIR_LENGTH {
+            digits.push(ALPHABET[(lat % 5 * 4 + lon % 4) as usize]);
+            lat /= 5;
+            lon /= 4;
+        }
+        for _ in 0..PAIR_LENGTH / 2 {
+            digits.push(ALPHABET[(lon % 20) as usize]);
+            digits.push(ALPHABET[(lat % 20) as usize]);
+            lat /= 20;
+            lon /= 20;
+        }
+        digits.reverse();
+        digits.truncate(precision);
+
+        let mut code: String = digits.iter().map(|&c| c as char).collect();
+        while code.len() < SEPARATOR_POSITION {
+            code.push(PADDING);
+        }
+        code.insert(SEPARATOR_POSITION, SEPARATOR);
+        Ok(code)
+    }
+
+    // Validate a code, returning its digits, with separator and padding removed
+    fn digits(code: &str) -> Result<Vec<u8>, Error> {
+        let invalid = || Error::Invalid(format!("Malformed plus code: '{code}'"));
+        let code = code.trim().to_ascii_uppercase();
+
+        // Exactly one separator, in the standard position (short codes are not supported)
+        if code.find(SEPARATOR) != Some(SEPARATOR_POSITION) || code.matches(SEPARATOR).count() != 1
+        {
+            return Err(invalid());
+        }
+        let (head, tail) = code.split_at(SEPARATOR_POSITION);
+        let tail = &tail[1..];
+
+        // Padding must be an even number of zeros, terminating the head, and
+        // padded codes cannot have digits after the separator
+        let digits = head.trim_end_matches(PADDING);
+        let padding = head.len() - digits.len();
+        if digits.contains(PADDING) || (padding > 0 && !tail.is_empty()) || padding % 2 == 1 {
+            return Err(invalid());
+        }
+        if digits.len() < 2 || tail.len() == 1 || tail.len() > MAX_PRECISION - SEPARATOR_POSITION {
+            return Err(invalid());
+        }
+
+        let mut values = Vec::with_capacity(MAX_PRECISION);
+        for c in digits.bytes().chain(tail.bytes()) {
+            let Some(value) = ALPHABET.iter().position(|&a| a == c) else {
+                return Err(invalid());
+            };
+            values.push(value as u8);
+        }
+
+        // The first pair must be within the latitude and longitude ranges
+        if values[0] >= 9 || values[1] >= 18 {
+            return Err(invalid());
+        }
+        Ok(values)
+    }
+
+    /// The south western and north eastern corners of the cell identified by `code`
+    pub fn bounds(code: &str) -> Result<[Coor4D; 2], Error> {
+        let digits = digits(code)?;
+
+        let mut lat = 0_i64;
+        let mut lon = 0_i64;
+        let mut lat_size = 180 * LAT_UNITS;
+        let mut lon_size = 360 * LON_UNITS;
+        for (i, &digit) in digits.iter().enumerate() {
+            let digit = digit as i64;
+            if i < PAIR_LENGTH {
+                if i % 2 == 0 {
+                    lat_size /= if i == 0 { 9 } else { 20 };
+                    lon_size /= if i == 0 { 18 } else { 20 };
+                    lat += digit * lat_size;
+                } else {
+                    lon += digit * lon_size;
+                }
+            } else {
+                lat_size /= 5;
+                lon_size /= 4;
+                lat += digit / 4 * lat_size;
+                lon += digit % 4 * lon_size;
+            }
+        }
+
+        let to_lat = |units: i64| units as f64 / LAT_UNITS as f64 - 90.;
+        let to_lon = |units: i64| units as f64 / LON_UNITS as f64 - 180.;
+        Ok(corners(
+            to_lon(lon),
+            to_lat(lat),
+            to_lon(lon + lon_size),
+            to_lat(lat + lat_size),
+        ))
+    }
+
+    /// The center of the cell identified by `code`
+    pub fn decode(code: &str) -> Result<Coor4D, Error> {
+        Ok(center(bounds(code)?))
+    }
+
+    /// The precision, i.e. the number of digits (excluding padding), of `code`
+    pub fn precision(code: &str) -> Result<usize, Error> {
+        Ok(digits(code)?.len())
+    }
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn geohash() -> Result<(), Error> {
+        // The example from the Wikipedia article on geohash
+        let jutland = Coor2D::geo(57.64911, 10.40744);
+        assert_eq!(geohash::encode(jutland, 11)?, "u4pruydqqvj");
+        assert_eq!(geohash::encode(jutland, 1)?, "u");
+        assert_eq!(geohash::precision("u4pruydqqvj")?, 11);
+
+        // Decoding returns the center of the cell, and is case insensitive
+        let center = geohash::decode("U4PRUYDQQVJ")?;
+        assert_float_eq!(center[0].to_degrees(), 10.40744, abs <= 1e-6);
+        assert_float_eq!(center[1].to_degrees(), 57.64911, abs <= 1e-6);
+
+        // The first character halves the longitude range 3 times, and the latitude 2 times
+        let [sw, ne] = geohash::bounds("u")?;
+        assert_float_eq!(sw.to_degrees().0, [0., 45., 0., 0.], abs_all <= 1e-12);
+        assert_float_eq!(ne.to_degrees().0, [45., 90., 0., 0.], abs_all <= 1e-12);
+
+        for bad in ["", "u4a", "u4pruydqqvjuu"] {
+            assert!(geohash::decode(bad).is_err());
+        }
+        assert!(geohash::encode(jutland, 0).is_err());
+        assert!(geohash::encode(jutland, 13).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn maidenhead() -> Result<(), Error> {
+        // The ARRL headquarters, station W1AW, Newington, Connecticut
+        let w1aw = Coor4D::geo(41.714775, -72.727260, 0., 0.);
+        assert_eq!(maidenhead::encode(w1aw, 1)?, "FN");
+        assert_eq!(maidenhead::encode(w1aw, 3)?, "FN31pr");
+        assert_eq!(maidenhead::encode(w1aw, 4)?, "FN31pr21");
+        assert_eq!(maidenhead::precision("FN31pr21")?, 4);
+
+        let [sw, ne] = maidenhead::bounds("fn31PR")?;
+        assert_float_eq!(sw[0].to_degrees(), -72.75, abs <= 1e-12);
+        assert_float_eq!(ne[0].to_degrees(), -72.75 + 5. / 60., abs <= 1e-12);
+        assert_float_eq!(sw[1].to_degrees(), 41.7083333333, abs <= 1e-9);
+        assert_float_eq!(ne[1].to_degrees(), 41.75, abs <= 1e-9);
+
+        // The poles and the antimeridian belong to the outermost cells
+        assert_eq!(
+            maidenhead::encode(Coor4D::geo(90., 180., 0., 0.), 2)?,
+            "AR09"
+        );
+        assert_eq!(
+            maidenhead::encode(Coor4D::geo(-90., -180., 0., 0.), 2)?,
+            "AA00"
+        );
+
+        for coord in [w1aw, Coor4D::geo(-33.86, 151.21, 0., 0.)] {
+            let code = maidenhead::encode(coord, 6)?;
+            assert_eq!(maidenhead::encode(maidenhead::decode(&code)?, 6)?, code);
+        }
+
+        for bad in ["", "F", "FS", "FN3", "FNA1", "FN31py"] {
+            assert!(maidenhead::decode(bad).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pluscode() -> Result<(), Error> {
+        // The Google office in Zürich
+        let zurich = Coor4D::geo(47.365562, 8.524989, 0., 0.);
+        assert_eq!(pluscode::encode(zurich, 10)?, "8FVC9G8F+6X");
+        assert_eq!(pluscode::encode(zurich, 11)?, "8FVC9G8F+6XH");
+        assert_eq!(pluscode::encode(zurich, 4)?, "8FVC0000+");
+        assert_eq!(pluscode::encode(zurich, 8)?, "8FVC9G8F+");
+        assert_eq!(pluscode::precision("8FVC0000+")?, 4);
+        assert_eq!(pluscode::precision("8fvc9g8f+6xq")?, 11);
+
+        // A 4 digit code identifies a 1 by 1 degree cell
+        let [sw, ne] = pluscode::bounds("8FVC0000+")?;
+        assert_float_eq!(sw.to_degrees().0, [8., 47., 0., 0.], abs_all <= 1e-12);
+        assert_float_eq!(ne.to_degrees().0, [9., 48., 0., 0.], abs_all <= 1e-12);
+
+        let center = pluscode::decode("8FVC9G8F+6X")?;
+        assert_float_eq!(center[0].to_degrees(), 8.524989, abs <= 1.25e-4 / 2.);
+        assert_float_eq!(center[1].to_degrees(), 47.365562, abs <= 1.25e-4 / 2.);
+
+        // Points at the north pole go to the northernmost cell, and the antimeridian to the westernmost
+        let code = pluscode::encode(Coor4D::geo(90., 180., 0., 0.), 10)?;
+        assert_eq!(code, "C2X2X2X2+X2");
+        assert!(pluscode::bounds(&code)?[1][1].to_degrees() == 90.);
+
+        for coord in [zurich, Coor4D::geo(-41.2865, 174.7762, 0., 0.)] {
+            for precision in [2, 4, 6, 8, 10, 11, 12, 13, 14, 15] {
+                let code = pluscode::encode(coord, precision)?;
+                assert_eq!(pluscode::encode(pluscode::decode(&code)?, precision)?, code);
+            }
+        }
+
+        for bad in [
+            "",
+            "8FVC9G8F",
+            "8FVC9G8F6X",
+            "8FVC+9G8F",
+            "9G8F+6X",
+            "8FVC0000+6X",
+            "8FV00000+",
+            "8F00VC00+",
+            "8FVC9G8F+6",
+            "8FVC9G8F+6XQQQQQQ",
+            "8FVC9G8F++",
+            "WFVC9G8F+6X",
+            "8FVC9G8F+6A",
+        ] {
+            assert!(pluscode::decode(bad).is_err(), "{bad}");
+        }
+        assert!(pluscode::encode(zurich, 9).is_err());
+        assert!(pluscode::encode(zurich, 16).is_err());
+        Ok(())
+    }
+}
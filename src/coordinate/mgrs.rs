@@ -0,0 +1,581 @@
+//! Conversion between geographical coordinates and MGRS/USNG grid references.
+//!
+//! The Military Grid Reference System (MGRS), and its civilian twin, the
+//! US National Grid (USNG), identify positions by a UTM (or UPS) zone,
+//! a latitude band, a 100 km grid square, and a numerical location within
+//! the grid square. The grid references are computed using the `utm` and
+//! `ups` operators on the WGS84 ellipsoid.
+//!
+//! The two formats differ only in presentation: MGRS references are written
+//! without spaces (`33UUB4721373141`), while USNG references separate the
+//! components by spaces (`33U UB 47213 73141`). Both forms are accepted by
+//! [`decode`].
+//!
+//! Following convention, references are *truncated* (not rounded) to the
+//! given precision, i.e. they identify the grid cell containing the position.
+//! Hence, [`decode`] returns the center of the cell.
+//!
+//! ```
+//! use geodesy::prelude::*;
+//! use geodesy::mgrs;
+//!
+//! let copenhagen = Coor4D::geo(55.68, 12.57, 0., 0.);
+//! let reference = mgrs::encode(copenhagen, 3)?;
+//! assert_eq!(reference, "33UUB472731");
+//! assert_eq!(mgrs::encode_usng(copenhagen, 3)?, "33U UB 472 731");
+//!
+//! let center = mgrs::decode(&reference)?;
+//! assert!((center[1].to_degrees() - 55.68).abs() < 0.001);
+//! # Ok::<(), geodesy::Error>(())
+//! ```
+use crate::prelude::*;
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+// Latitude bands of 8 degrees, from 80°S to 84°N (band X covers 12 degrees)
+const BANDS: &[u8; 20] = b"CDEFGHJKLMNPQRSTUVWX";
+
+// The 100 km column letters repeat for every 3 zones
+const COLUMNS: [&[u8; 8]; 3] = [b"ABCDEFGH", b"JKLMNPQR", b"STUVWXYZ"];
+
+// The 100 km row letters repeat every 2000 km, and are offset by 5 letters in even zones
+const ROWS: &[u8; 20] = b"ABCDEFGHJKLMNPQRSTUV";
+
+/// The highest supported precision: 5 digits per axis, i.e. 1 m resolution
+pub const MAX_PRECISION: usize = 5;
+
+// The utm and ups operators in use, instantiated on first use, by definition
+static PROJECTIONS: Lazy<Mutex<Projections>> =
+    Lazy::new(|| Mutex::new(Projections(Minimal::new(), BTreeMap::new())));
+
+struct Projections(Minimal, BTreeMap<String, OpHandle>);
+
+/// The components of a grid reference
+#[derive(Debug, Clone, PartialEq)]
+struct GridReference {
+    // UTM zone number, or 0 for UPS
+    zone: usize,
+    band: u8,
+    column: u8,
+    row: u8,
+    // Location within the 100 km square, in whole units of the precision
+    easting: u32,
+    northing: u32,
+    precision: usize,
+}
+
+// ----- E N C O D E -------------------------------------------------------------------
+
+/// Encode the position `coord` (longitude and latitude in radians, i.e. the
+/// internal Geodesy convention) as an MGRS grid reference, with `precision`
+/// digits for each of the easting and northing, i.e. from 0 (100 km) to 5 (1 m)
+pub fn encode(coord: impl Into<Coor4D>, precision: usize) -> Result<String, Error> {
+    let r = grid_reference(&coord.into(), precision)?;
+    let zone = if r.zone == 0 {
+        String::new()
+    } else {
+        r.zone.to_string()
+    };
+    let (band, column, row) = (r.band as char, r.column as char, r.row as char);
+    let (e, n) = digits(&r);
+    Ok(format!("{zone}{band}{column}{row}{e}{n}"))
+}
+
+/// Encode the position `coord` as a USNG grid reference, i.e. an MGRS grid
+/// reference with space separated components
+pub fn encode_usng(coord: impl Into<Coor4D>, precision: usize) -> Result<String, Error> {
+    let r = grid_reference(&coord.into(), precision)?;
+    let zone = if r.zone == 0 {
+        String::new()
+    } else {
+        r.zone.to_string()
+    };
+    let (band, column, row) = (r.band as char, r.column as char, r.row as char);
+    let (e, n) = digits(&r);
+    if precision == 0 {
+        return Ok(format!("{zone}{band} {column}{row}"));
+    }
+    Ok(format!("{zone}{band} {column}{row} {e} {n}"))
+}
+
+fn digits(r: &GridReference) -> (String, String) {
+    if r.precision == 0 {
+        return (String::new(), String::new());
+    }
+    let width = r.precision;
+    (
+        format!("{:0width$}", r.easting),
+        format!("{:0width$}", r.northing),
+    )
+}
+
+fn grid_reference(coord: &Coor4D, precision: usize) -> Result<GridReference, Error> {
+    if precision > MAX_PRECISION {
+        return Err(Error::Invalid(format!(
+            "MGRS precision must be in the range 0..=5, got {precision}"
+        )));
+    }
+    let lon = angular::normalize_symmetric(coord[0]).to_degrees();
+    let lat = coord[1].to_degrees();
+    if !lon.is_finite() || !lat.is_finite() || lat.abs() > 90. {
+        return Err(Error::Invalid(format!(
+            "Cannot encode ({lon}, {lat}) as MGRS"
+        )));
+    }
+
+    // Outside of the polar areas, MGRS is based on UTM
+    if (-80. ..84.).contains(&lat) {
+        return utm_grid_reference(coord, lon, lat, precision);
+    }
+    ups_grid_reference(coord, lat, precision)
+}
+
+fn utm_grid_reference(
+    coord: &Coor4D,
+    lon: f64,
+    lat: f64,
+    precision: usize,
+) -> Result<GridReference, Error> {
+    let mut zone = (((lon + 180.) / 6.).floor() as usize).min(59) + 1;
+
+    // The Norwegian exceptions: Zone 32V is widened to cover all of
+    // south-western Norway, and zones 31X-37X are adjusted around Svalbard
+    if (56. ..64.).contains(&lat) && (3. ..12.).contains(&lon) {
+        zone = 32;
+    }
+    if lat >= 72. && (0. ..42.).contains(&lon) {
+        zone = match lon {
+            lon if lon < 9. => 31,
+            lon if lon < 21. => 33,
+            lon if lon < 33. => 35,
+            _ => 37,
+        };
+    }
+
+    let band = BANDS[(((lat + 80.) / 8.).floor() as usize).min(19)];
+    let south = if lat < 0. { " south" } else { "" };
+    let projected = project(&format!("utm zone={zone}{south} ellps=WGS84"), coord, Fwd)?;
+    let (e, n) = (snap(projected[0]), snap(projected[1]));
+
+    let column_index = (e / 100_000.).floor() as usize;
+    if !(1..=8).contains(&column_index) {
+        return Err(Error::Invalid(format!(
+            "Easting {e} outside of the MGRS range for zone {zone}"
+        )));
+    }
+    let column = COLUMNS[(zone - 1) % 3][column_index - 1];
+    let offset = if zone % 2 == 0 { 5 } else { 0 };
+    let row = ROWS[((n / 100_000.).floor() as usize + offset) % 20];
+
+    let (easting, northing) = truncate(e, n, precision);
+    Ok(GridReference {
+        zone,
+        band,
+        column,
+        row,
+        easting,
+        northing,
+        precision,
+    })
+}
+
+// The UPS grid squares: Band letter, first column letter, false easting and northing
+#[rustfmt::skip]
+const UPS_GRID: [(u8, u8, f64, f64); 4] = [
+    (b'A', b'J',   800_000.,   800_000.),
+    (b'B', b'A', 2_000_000.,   800_000.),
+    (b'Y', b'J',   800_000., 1_300_000.),
+    (b'Z', b'A', 2_000_000., 1_300_000.),
+];
+
+fn ups_grid_reference(coord: &Coor4D, lat: f64, precision: usize) -> Result<GridReference, Error> {
+    let north = lat > 0.;
+    let definition = if north {
+        "ups ellps=WGS84"
+    } else {
+        "ups south ellps=WGS84"
+    };
+    let projected = project(definition, coord, Fwd)?;
+    let (e, n) = (snap(projected[0]), snap(projected[1]));
+
+    let east = e >= 2_000_000.;
+    let (band, first_column, false_easting, false_northing) =
+        UPS_GRID[2 * usize::from(north) + usize::from(east)];
+
+    // Rows skip the letters I and O
+    let mut row = b'A' + ((n - false_northing) / 100_000.).floor() as u8;
+    if row > b'H' {
+        row += 1;
+    }
+    if row > b'N' {
+        row += 1;
+    }
+
+    // Columns skip the letters D, E, I, M, N, O, V, W
+    let mut column = first_column + ((e - false_easting) / 100_000.).floor() as u8;
+    if east {
+        if column > b'C' {
+            column += 2;
+        }
+        if column > b'H' {
+            column += 1;
+        }
+        if column > b'L' {
+            column += 3;
+        }
+    } else {
+        if column > b'L' {
+            column += 3;
+        }
+        if column > b'U' {
+            column += 2;
+        }
+    }
+
+    let (easting, northing) = truncate(e, n, precision);
+    Ok(GridReference {
+        zone: 0,
+        band,
+        column,
+        row,
+        easting,
+        northing,
+        precision,
+    })
+}
+
+// Round off to the micrometer, to avoid e.g. the pole being truncated into the
+// neighbouring grid square, due to floating point noise
+fn snap(x: f64) -> f64 {
+    (x * 1e6).round() / 1e6
+}
+
+// The location within the 100 km square, truncated to the precision
+fn truncate(e: f64, n: f64, precision: usize) -> (u32, u32) {
+    let unit = 10f64.powi((MAX_PRECISION - precision) as i32);
+    let e = ((e % 100_000.) / unit).floor() as u32;
+    let n = ((n % 100_000.) / unit).floor() as u32;
+    (e, n)
+}
+
+// ----- D E C O D E -------------------------------------------------------------------
+
+/// Decode an MGRS or USNG grid reference, returning the center of the grid
+/// cell as longitude and latitude in radians (the internal Geodesy convention)
+pub fn decode(reference: &str) -> Result<Coor4D, Error> {
+    let r = parse(reference)?;
+    let unit = 10f64.powi((MAX_PRECISION - r.precision) as i32);
+    let e = r.easting as f64 * unit + unit / 2.;
+    let n = r.northing as f64 * unit + unit / 2.;
+    if r.zone == 0 {
+        return decode_ups(&r, e, n);
+    }
+    decode_utm(&r, e, n)
+}
+
+/// The precision (number of digits per axis) of an MGRS or USNG grid reference
+pub fn precision(reference: &str) -> Result<usize, Error> {
+    Ok(parse(reference)?.precision)
+}
+
+fn parse(reference: &str) -> Result<GridReference, Error> {
+    let invalid = || Error::Invalid(format!("Malformed MGRS reference: '{reference}'"));
+    let text: Vec<u8> = reference
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    // The zone number: none for UPS, otherwise one or two digits
+    let zone_digits = text.iter().take_while(|c| c.is_ascii_digit()).count();
+    if zone_digits > 2 || text.len() < zone_digits + 3 {
+        return Err(invalid());
+    }
+    let zone = if zone_digits == 0 {
+        0
+    } else {
+        std::str::from_utf8(&text[..zone_digits])?
+            .parse::<usize>()
+            .map_err(|_| invalid())?
+    };
+    if zone > 60 || (zone_digits > 0 && zone == 0) {
+        return Err(invalid());
+    }
+
+    let band = text[zone_digits];
+    let column = text[zone_digits + 1];
+    let row = text[zone_digits + 2];
+
+    // Check the letters
+    if zone == 0 {
+        if !b"ABYZ".contains(&band) {
+            return Err(invalid());
+        }
+    } else if !BANDS.contains(&band)
+        || !COLUMNS[(zone - 1) % 3].contains(&column)
+        || !ROWS.contains(&row)
+    {
+        return Err(invalid());
+    }
+
+    // The numerical location: An even number of digits, at most 10
+    let location = &text[zone_digits + 3..];
+    if location.len() % 2 == 1
+        || location.len() > 2 * MAX_PRECISION
+        || !location.iter().all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let precision = location.len() / 2;
+    let (easting, northing) = if precision == 0 {
+        (0, 0)
+    } else {
+        let (e, n) = location.split_at(precision);
+        (
+            std::str::from_utf8(e)?.parse().map_err(|_| invalid())?,
+            std::str::from_utf8(n)?.parse().map_err(|_| invalid())?,
+        )
+    };
+
+    Ok(GridReference {
+        zone,
+        band,
+        column,
+        row,
+        easting,
+        northing,
+        precision,
+    })
+}
+
+fn decode_utm(r: &GridReference, e: f64, n: f64) -> Result<Coor4D, Error> {
+    let zone = r.zone;
+    let column_index = COLUMNS[(zone - 1) % 3]
+        .iter()
+        .position(|&c| c == r.column)
+        .unwrap_or_default();
+    let row_index = ROWS.iter().position(|&c| c == r.row).unwrap_or_default();
+    let offset = if zone % 2 == 0 { 5 } else { 0 };
+
+    let e = (column_index + 1) as f64 * 100_000. + e;
+    let n = ((row_index + 20 - offset) % 20) as f64 * 100_000. + n;
+
+    // The latitude band resolves the 2000 km ambiguity of the row letters:
+    // Select the northing giving the latitude closest to the band
+    let band_index = BANDS.iter().position(|&c| c == r.band).unwrap_or_default();
+    let lo = -80. + 8. * band_index as f64;
+    let hi = if r.band == b'X' { 84. } else { lo + 8. };
+    let south = if r.band < b'N' { " south" } else { "" };
+
+    let definition = format!("utm zone={zone}{south} ellps=WGS84");
+    let mut best = Coor4D::nan();
+    let mut best_distance = f64::INFINITY;
+    for k in 0..5 {
+        let coord = Coor4D::raw(e, n + k as f64 * 2_000_000., 0., 0.);
+        let Ok(geo) = project(&definition, &coord, Inv) else {
+            continue;
+        };
+        let lat = geo[1].to_degrees();
+        let distance = (lo - lat).max(lat - hi).max(0.);
+        if distance < best_distance {
+            best_distance = distance;
+            best = geo;
+        }
+    }
+
+    // Allow for cells straddling the band limits
+    if best_distance > 1. {
+        return Err(Error::Invalid(format!(
+            "Inconsistent MGRS latitude band '{}'",
+            r.band as char
+        )));
+    }
+    Ok(best)
+}
+
+fn decode_ups(r: &GridReference, e: f64, n: f64) -> Result<Coor4D, Error> {
+    let Some(&(_, first_column, false_easting, false_northing)) =
+        UPS_GRID.iter().find(|g| g.0 == r.band)
+    else {
+        return Err(Error::Invalid(format!("Unknown UPS band '{}'", r.band)));
+    };
+    let east = r.band == b'B' || r.band == b'Z';
+    let north = r.band == b'Y' || r.band == b'Z';
+
+    // Undo the letter skipping of the encoder
+    let mut row = r.row;
+    if row > b'O' {
+        row -= 1;
+    }
+    if row > b'I' {
+        row -= 1;
+    }
+    let mut column = r.column;
+    if east {
+        if column > b'O' {
+            column -= 3;
+        }
+        if column > b'I' {
+            column -= 1;
+        }
+        if column > b'E' {
+            column -= 2;
+        }
+    } else {
+        if column > b'W' {
+            column -= 2;
+        }
+        if column > b'O' {
+            column -= 3;
+        }
+    }
+    if row < b'A' || column < first_column {
+        return Err(Error::Invalid(format!(
+            "Malformed UPS grid square '{}{}'",
+            r.column as char, r.row as char
+        )));
+    }
+
+    let e = false_easting + (column - first_column) as f64 * 100_000. + e;
+    let n = false_northing + (row - b'A') as f64 * 100_000. + n;
+    let definition = if north {
+        "ups ellps=WGS84"
+    } else {
+        "ups south ellps=WGS84"
+    };
+    project(definition, &Coor4D::raw(e, n, 0., 0.), Inv)
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+fn project(definition: &str, coord: &Coor4D, direction: Direction) -> Result<Coor4D, Error> {
+    let mut projections = PROJECTIONS.lock().unwrap();
+    let Projections(ctx, ops) = &mut *projections;
+    let op = match ops.get(definition) {
+        Some(&op) => op,
+        None => {
+            let op = ctx.op(definition)?;
+            ops.insert(definition.to_string(), op);
+            op
+        }
+    };
+    let mut operands = [*coord];
+    if ctx.apply(op, direction, &mut operands)? != 1 {
+        return Err(Error::Invalid(format!(
+            "Cannot convert {coord:?} using '{definition}'"
+        )));
+    }
+    Ok(operands[0])
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn utm() -> Result<(), Error> {
+        let copenhagen = Coor4D::geo(55.68, 12.57, 0., 0.);
+        assert_eq!(encode(copenhagen, 0)?, "33UUB");
+        assert_eq!(encode(copenhagen, 1)?, "33UUB47");
+        assert_eq!(encode_usng(copenhagen, 0)?, "33U UB");
+
+        // Check the numerical part against the plain UTM coordinates
+        let mut ctx = Minimal::new();
+        let op = ctx.op("utm zone=33 ellps=WGS84")?;
+        let mut utm = [copenhagen];
+        ctx.apply(op, Fwd, &mut utm)?;
+        let e = (utm[0][0] % 100_000.).floor();
+        let n = (utm[0][1] % 100_000.).floor();
+        assert_eq!(encode(copenhagen, 5)?, format!("33UUB{e:05}{n:05}"));
+
+        // Southern hemisphere, even zone: Row letters offset by 5
+        let sydney = Coor4D::geo(-33.86, 151.21, 0., 0.);
+        let reference = encode_usng(sydney, 5)?;
+        assert!(reference.starts_with("56H LH "));
+
+        // Roundtrips, including cells straddling the equator
+        for coord in [
+            copenhagen,
+            sydney,
+            Coor4D::geo(0.0001, -75., 0., 0.),
+            Coor4D::geo(-0.0001, 100., 0., 0.),
+            Coor4D::geo(83.9, 20., 0., 0.),
+            Coor4D::geo(-79.9, -170., 0., 0.),
+        ] {
+            let reference = encode(coord, 5)?;
+            let decoded = decode(&reference)?;
+            assert_float_eq!(decoded[0], coord[0], abs <= 1e-6);
+            assert_float_eq!(decoded[1], coord[1], abs <= 1e-6);
+            assert_eq!(encode(decoded, 5)?, reference);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn exceptions() -> Result<(), Error> {
+        // Bergen is in the widened zone 32V
+        let bergen = Coor4D::geo(60.39, 5.32, 0., 0.);
+        assert!(encode(bergen, 0)?.starts_with("32V"));
+
+        // Longyearbyen, Svalbard, is in zone 33X
+        let longyearbyen = Coor4D::geo(78.22, 15.65, 0., 0.);
+        assert!(encode(longyearbyen, 0)?.starts_with("33X"));
+        Ok(())
+    }
+
+    #[test]
+    fn ups() -> Result<(), Error> {
+        let north_pole = Coor4D::geo(90., 0., 0., 0.);
+        let south_pole = Coor4D::geo(-90., 0., 0., 0.);
+        assert_eq!(encode(north_pole, 5)?, "ZAH0000000000");
+        assert_eq!(encode(south_pole, 5)?, "BAN0000000000");
+        assert_eq!(encode_usng(north_pole, 2)?, "Z AH 00 00");
+
+        for coord in [
+            Coor4D::geo(85., 45., 0., 0.),
+            Coor4D::geo(87., -135., 0., 0.),
+            Coor4D::geo(-82., 10., 0., 0.),
+            Coor4D::geo(-85., -100., 0., 0.),
+        ] {
+            let reference = encode(coord, 5)?;
+            let decoded = decode(&reference)?;
+            assert_float_eq!(decoded[1], coord[1], abs <= 1e-6);
+            assert_eq!(encode(decoded, 5)?, reference);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parsing() -> Result<(), Error> {
+        // MGRS and USNG forms, and the cell center
+        let a = decode("33UUB4721373141")?;
+        let b = decode("33u ub 47213 73141")?;
+        assert_eq!(a, b);
+        assert_eq!(precision("33U UB 474 709")?, 3);
+        assert_eq!(precision("33UUB")?, 0);
+
+        // Leading zero in the zone number
+        assert_eq!(decode("04QFJ1234567890")?, decode("4QFJ1234567890")?);
+
+        for bad in [
+            "",
+            "33U",
+            "33UUB472137314",
+            "33IUB47",
+            "61UUB47",
+            "33UAB47",
+            "33UUW47",
+            "CAH00",
+            "33UUB4x",
+        ] {
+            assert!(decode(bad).is_err(), "{bad}");
+        }
+        assert!(encode(Coor4D::geo(55., 12., 0., 0.), 6).is_err());
+        assert!(encode(Coor4D::nan(), 3).is_err());
+        Ok(())
+    }
+}
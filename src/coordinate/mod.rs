@@ -3,6 +3,8 @@ pub mod coor2d;
 pub mod coor32;
 pub mod coor3d;
 pub mod coor4d;
+pub mod geocode;
+pub mod mgrs;
 pub mod set;
 
 /// Methods for changing the coordinate representation of angles.
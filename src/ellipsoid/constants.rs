@@ -53,6 +53,16 @@ pub(super) const ELLIPSOID_LIST: [(&str, &str, &str, &str, &str); 47] = [
     ("unitsphere",      "1",             "1",      "0.",                 "Unit Sphere (r=1)"),
 ];
 
+// Geocentric gravitational constant, GM, and angular velocity, ω, of GRS80
+pub(super) const GRS80_GM: f64 = 3.986_005e14;
+pub(super) const GRS80_OMEGA: f64 = 7.292_115e-5;
+
+// Ellipsoids with gravity constants different from those of GRS80
+#[rustfmt::skip]
+pub(super) const GRAVITY_LIST: [(&str, f64, f64); 1] = [
+    ("WGS84",     3.986_004_418e14, 7.292_115e-5),
+];
+
 #[rustfmt::skip]
 pub(super) const RECTIFYING: PolynomialCoefficients = PolynomialCoefficients {
     // Geodetic to rectifying: Coefficients for converting 𝜙 to 𝜇.
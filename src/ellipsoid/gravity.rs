@@ -0,0 +1,200 @@
+use super::*;
+
+// Mean gradient of the actual gravity along the plumb line, in m/s² per m,
+// as assumed in Helmert's orthometric heights (0.0424 mGal/m)
+const HELMERT_GRADIENT: f64 = 0.0424e-5;
+
+// ----- Normal gravity --------------------------------------------------------
+impl Ellipsoid {
+    /// The geocentric gravitational constant, *GM*
+    #[must_use]
+    pub fn gravitational_constant(&self) -> f64 {
+        self.gm
+    }
+
+    /// The angular velocity, *ω*
+    #[must_use]
+    pub fn angular_velocity(&self) -> f64 {
+        self.omega
+    }
+
+    /// The auxiliary quantity *m = ω²a²b / GM*,
+    /// [Moritz (2000)](crate::Bibliography::Mor00) eq. (4)
+    #[must_use]
+    pub fn gravity_ratio(&self) -> f64 {
+        let b = self.semiminor_axis();
+        self.omega * self.omega * self.a * self.a * b / self.gm
+    }
+
+    /// Normal gravity at the equator and at the poles, *(γa, γb)*, following
+    /// [Moritz (2000)](crate::Bibliography::Mor00), eqs. (5-8)
+    #[must_use]
+    pub fn normal_gravity_at_equator_and_pole(&self) -> (f64, f64) {
+        let b = self.semiminor_axis();
+        let m = self.gravity_ratio();
+        let ep = self.second_eccentricity();
+        let aep = ep.atan();
+
+        let q0 = ((1. + 3. / (ep * ep)) * aep - 3. / ep) / 2.;
+        let q0p = 3. * (1. + 1. / (ep * ep)) * (1. - aep / ep) - 1.;
+        let ratio = m * ep * q0p / q0;
+
+        let gamma_a = self.gm / (self.a * b) * (1. - m - ratio / 6.);
+        let gamma_b = self.gm / (self.a * self.a) * (1. + ratio / 3.);
+        (gamma_a, gamma_b)
+    }
+
+    /// Normal gravity, *γ*, at the surface of the ellipsoid, using Somigliana's
+    /// closed formula, [Moritz (2000)](crate::Bibliography::Mor00), eq. (20)
+    #[must_use]
+    pub fn normal_gravity(&self, latitude: f64) -> f64 {
+        let b = self.semiminor_axis();
+        let (gamma_a, gamma_b) = self.normal_gravity_at_equator_and_pole();
+        let (s, c) = latitude.sin_cos();
+        let (ac, bs) = (self.a * c, b * s);
+        (ac * gamma_a * c + bs * gamma_b * s) / ac.hypot(bs)
+    }
+
+    /// Normal gravity at the given height above the ellipsoid, using the
+    /// second order expansion of [Heiskanen & Moritz (1967)](crate::Bibliography::Hei67),
+    /// eq. (2-124)
+    #[must_use]
+    pub fn normal_gravity_at_height(&self, latitude: f64, height: f64) -> f64 {
+        let (first, second) = self.height_factors(latitude, height);
+        self.normal_gravity(latitude) * (1. - 2. * first + 3. * second)
+    }
+
+    /// The mean normal gravity along the normal plumb line, from the ellipsoid
+    /// to the given height, [Heiskanen & Moritz (1967)](crate::Bibliography::Hei67),
+    /// eq. (4-60)
+    #[must_use]
+    pub fn mean_normal_gravity(&self, latitude: f64, height: f64) -> f64 {
+        let (first, second) = self.height_factors(latitude, height);
+        self.normal_gravity(latitude) * (1. - first + second)
+    }
+
+    // The first and second order terms of the height expansions
+    fn height_factors(&self, latitude: f64, height: f64) -> (f64, f64) {
+        let f = self.f;
+        let m = self.gravity_ratio();
+        let s = latitude.sin();
+        let h = height / self.a;
+        ((1. + f + m - 2. * f * s * s) * h, h * h)
+    }
+}
+
+// ----- Gravity related heights -----------------------------------------------
+impl Ellipsoid {
+    /// The geopotential number, *C*, corresponding to the given normal height
+    #[must_use]
+    pub fn geopotential_number(&self, latitude: f64, normal_height: f64) -> f64 {
+        self.mean_normal_gravity(latitude, normal_height) * normal_height
+    }
+
+    /// The dynamic height corresponding to the given normal height, i.e. the
+    /// geopotential number scaled by the normal gravity at latitude 45°
+    #[must_use]
+    pub fn dynamic_height(&self, latitude: f64, normal_height: f64) -> f64 {
+        let gamma_45 = self.normal_gravity(45_f64.to_radians());
+        self.geopotential_number(latitude, normal_height) / gamma_45
+    }
+
+    /// The normal height corresponding to the given dynamic height. Inverse of
+    /// [dynamic_height](Ellipsoid::dynamic_height)
+    #[must_use]
+    pub fn normal_height_from_dynamic(&self, latitude: f64, dynamic_height: f64) -> f64 {
+        let c = dynamic_height * self.normal_gravity(45_f64.to_radians());
+        let mut h = dynamic_height;
+        for _ in 0..5 {
+            h = c / self.mean_normal_gravity(latitude, h);
+        }
+        h
+    }
+
+    /// The Helmert orthometric height corresponding to the given normal height,
+    /// given the actual surface gravity, *g* (m/s²). The mean gravity along the
+    /// plumb line is estimated as *g + 0.0424 mGal/m · H*
+    #[must_use]
+    pub fn orthometric_height(&self, latitude: f64, normal_height: f64, gravity: f64) -> f64 {
+        let c = self.geopotential_number(latitude, normal_height);
+        let mut h = normal_height;
+        for _ in 0..5 {
+            h = c / (gravity + HELMERT_GRADIENT * h);
+        }
+        h
+    }
+
+    /// The normal height corresponding to the given Helmert orthometric height.
+    /// Inverse of [orthometric_height](Ellipsoid::orthometric_height)
+    #[must_use]
+    pub fn normal_height_from_orthometric(
+        &self,
+        latitude: f64,
+        orthometric_height: f64,
+        gravity: f64,
+    ) -> f64 {
+        let c = (gravity + HELMERT_GRADIENT * orthometric_height) * orthometric_height;
+        let mut h = orthometric_height;
+        for _ in 0..5 {
+            h = c / self.mean_normal_gravity(latitude, h);
+        }
+        h
+    }
+}
+
+// ----- Tests ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_gravity() -> Result<(), Error> {
+        // Numerical values from Moritz (2000)
+        let ellps = Ellipsoid::named("GRS80")?;
+        let (gamma_a, gamma_b) = ellps.normal_gravity_at_equator_and_pole();
+        assert!((gamma_a - 9.780_326_771_5).abs() < 1e-10);
+        assert!((gamma_b - 9.832_186_368_5).abs() < 1e-10);
+        assert!((ellps.gravity_ratio() - 0.003_449_786_003_08).abs() < 1e-14);
+        assert!((ellps.normal_gravity(45_f64.to_radians()) - 9.806_199_203).abs() < 1e-9);
+        assert!((ellps.normal_gravity(0.) - gamma_a).abs() < 1e-12);
+        assert!((ellps.normal_gravity(90_f64.to_radians()) - gamma_b).abs() < 1e-12);
+
+        // WGS84 has its own GM, hence slightly different values
+        let ellps = Ellipsoid::named("WGS84")?;
+        let (gamma_a, gamma_b) = ellps.normal_gravity_at_equator_and_pole();
+        assert!((gamma_a - 9.780_325_335_9).abs() < 1e-9);
+        assert!((gamma_b - 9.832_184_937_8).abs() < 1e-9);
+
+        // Other ellipsoids default to the GRS80 gravity constants
+        let ellps = Ellipsoid::named("intl")?;
+        assert_eq!(ellps.gravitational_constant(), 3.986_005e14);
+        assert_eq!(ellps.angular_velocity(), 7.292_115e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn heights() -> Result<(), Error> {
+        let ellps = Ellipsoid::named("GRS80")?;
+        let lat = 55_f64.to_radians();
+
+        // Gravity decreases by approximately 0.3086 mGal/m
+        let gamma = ellps.normal_gravity(lat);
+        let gradient = (gamma - ellps.normal_gravity_at_height(lat, 1000.)) / 1000.;
+        assert!((gradient - 0.3086e-5).abs() < 0.0005e-5);
+        assert!((ellps.normal_gravity_at_height(lat, 1000.) - 9.811_990_290_55).abs() < 1e-9);
+        assert!((ellps.mean_normal_gravity(lat, 1000.) - 9.813_532_214_36).abs() < 1e-9);
+
+        // Dynamic heights
+        let dynamic = ellps.dynamic_height(lat, 1000.);
+        assert!((dynamic - 1_000.747_793_481).abs() < 1e-6);
+        assert!((ellps.normal_height_from_dynamic(lat, dynamic) - 1000.).abs() < 1e-9);
+
+        // Orthometric heights
+        let orthometric = ellps.orthometric_height(lat, 1000., 9.81);
+        assert!((orthometric - 1_000.316_814_033).abs() < 1e-6);
+        let normal = ellps.normal_height_from_orthometric(lat, orthometric, 9.81);
+        assert!((normal - 1000.).abs() < 1e-9);
+        Ok(())
+    }
+}
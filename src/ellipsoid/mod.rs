@@ -1,17 +1,25 @@
 mod cartesians;
 mod constants;
 mod geodesics;
+mod gravity;
 mod latitudes;
 mod meridians;
 
 use crate::prelude::*;
 
 /// Representation of a (potentially triaxial) ellipsoid.
+///
+/// In addition to the geometrical parameters, the ellipsoid carries the
+/// geocentric gravitational constant, *GM*, and the angular velocity, *ω*,
+/// needed for computing normal gravity. Unless given explicitly, these
+/// default to the GRS80 values.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Ellipsoid {
     a: f64,
     ay: f64,
     f: f64,
+    gm: f64,
+    omega: f64,
 }
 
 /// GRS80 is the default ellipsoid.
@@ -29,6 +37,8 @@ impl Ellipsoid {
             a: semimajor_axis,
             ay: semimajor_axis,
             f: flattening,
+            gm: constants::GRS80_GM,
+            omega: constants::GRS80_OMEGA,
         }
     }
 
@@ -37,9 +47,18 @@ impl Ellipsoid {
             a: semimajor_x_axis,
             ay: semimajor_y_axis,
             f: flattening,
+            gm: constants::GRS80_GM,
+            omega: constants::GRS80_OMEGA,
         }
     }
 
+    /// The same ellipsoid, but with the given geocentric gravitational constant,
+    /// *GM*, and angular velocity, *ω*
+    #[must_use]
+    pub fn with_gravity(self, gm: f64, omega: f64) -> Ellipsoid {
+        Ellipsoid { gm, omega, ..self }
+    }
+
     /// Predefined ellipsoid; built-in or defined in asset collections
     pub fn named(name: &str) -> Result<Ellipsoid, Error> {
         // Is it one of the few builtins?
@@ -52,7 +71,13 @@ impl Ellipsoid {
             let ay: f64 = e.2.parse().unwrap();
             let rf: f64 = e.3.parse().unwrap();
             let f = if rf != 0.0 { 1.0 / rf } else { rf };
-            return Ok(Ellipsoid::triaxial(ax, ay, f));
+            let ellps = Ellipsoid::triaxial(ax, ay, f);
+
+            // Ellipsoids with their own gravity constants
+            if let Some(g) = constants::GRAVITY_LIST.iter().find(|g| g.0 == name) {
+                return Ok(ellps.with_gravity(g.1, g.2));
+            }
+            return Ok(ellps);
         }
 
         // The "semiminor, reciproque-flattening" form, e.g. "6378137, 298.3"
@@ -0,0 +1,120 @@
+use super::*;
+
+const HEADER_SIZE: usize = 80;
+
+// Buffer offsets for the BYN header fields
+const SOUTH: usize = 0; // (i32) Boundaries and spacing, in seconds of arc
+const NORTH: usize = 4;
+const WEST: usize = 8;
+const EAST: usize = 12;
+const DLAT: usize = 16; // (i16)
+const DLON: usize = 18; // (i16)
+const FACTOR: usize = 24; // (f64) Divide the stored values by this to obtain metres
+const SIZE_OF: usize = 32; // (i16) Size of the stored values: 2 or 4 bytes
+const BYTE_ORDER: usize = 44; // (i16) 0: big endian, 1: little endian
+const SCALE: usize = 46; // (i16) 1: Boundaries and spacing are scaled by 1000
+
+impl BaseGrid {
+    /// Read a grid in the NRCan BYN format, as used for the Canadian
+    /// geoid models.
+    ///
+    /// A BYN file is an 80 byte header, followed by the grid values as
+    /// scaled 16 or 32 bit integers, from north to south, and west to east.
+    /// Missing values (32767 for 16 bit grids, 9999 m for 32 bit grids)
+    /// are marked as NaN.
+    pub fn byn(buf: &[u8]) -> Result<Self, Error> {
+        let (header, grid) = byn_grid_reader(buf)?;
+        BaseGrid::plain(&header, Some(&grid), None)
+    }
+}
+
+fn byn_grid_reader(buf: &[u8]) -> Result<([f64; 7], Vec<f32>), Error> {
+    if buf.len() < HEADER_SIZE {
+        return Err(Error::Invalid("BYN: Incomplete header".to_string()));
+    }
+
+    // The byte order flag is 1 for little endian, i.e. the first byte
+    // is non-zero exactly when the file is little endian
+    let little_endian = buf[BYTE_ORDER] != 0;
+    let bytes = |i: usize, n: usize| {
+        let mut b = [0_u8; 8];
+        b[..n].copy_from_slice(&buf[i..i + n]);
+        if !little_endian {
+            b[..n].reverse();
+        }
+        b
+    };
+    let i16_at = |i: usize| i16::from_le_bytes(bytes(i, 2)[..2].try_into().unwrap()) as f64;
+    let i32_at = |i: usize| i32::from_le_bytes(bytes(i, 4)[..4].try_into().unwrap()) as f64;
+    let f64_at = |i: usize| f64::from_le_bytes(bytes(i, 8));
+
+    let scale = if i16_at(SCALE) == 1. { 1000. } else { 1. };
+    let arcsec = |value: f64| (value / scale / 3600.).to_radians();
+    let (lat_s, lat_n) = (arcsec(i32_at(SOUTH)), arcsec(i32_at(NORTH)));
+    let (lon_w, lon_e) = (arcsec(i32_at(WEST)), arcsec(i32_at(EAST)));
+    let (dlat, dlon) = (arcsec(i16_at(DLAT)), arcsec(i16_at(DLON)));
+
+    let factor = f64_at(FACTOR);
+    let size = i16_at(SIZE_OF) as usize;
+    if dlat.is_nan()
+        || dlon.is_nan()
+        || dlat <= 0.
+        || dlon <= 0.
+        || factor == 0.
+        || (size != 2 && size != 4)
+    {
+        return Err(Error::Invalid("BYN: Bad header".to_string()));
+    }
+
+    let rows = ((lat_n - lat_s) / dlat + 1.5).floor() as usize;
+    let cols = ((lon_e - lon_w) / dlon + 1.5).floor() as usize;
+    let Some(nodes) = rows
+        .checked_mul(cols)
+        .filter(|n| n.checked_mul(size).is_some())
+    else {
+        return Err(Error::Invalid("BYN: Grid size too large".to_string()));
+    };
+    if buf.len() - HEADER_SIZE < size * nodes {
+        return Err(Error::Invalid("BYN: Incomplete grid".to_string()));
+    }
+
+    let nodata = if size == 2 { 32767. } else { 9999. * factor };
+    let mut grid = Vec::with_capacity(nodes);
+    for i in 0..nodes {
+        let at = HEADER_SIZE + size * i;
+        let value = if size == 2 { i16_at(at) } else { i32_at(at) };
+        grid.push(if value == nodata {
+            f32::NAN
+        } else {
+            (value / factor) as f32
+        });
+    }
+
+    Ok(([lat_n, lat_s, lon_w, lon_e, dlat, dlon, 1.], grid))
+}
+
+// ----- T E S T S ------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byn() -> Result<(), Error> {
+        let grid = BaseGrid::byn(&std::fs::read("geodesy/byn/test.byn")?)?;
+        let gravsoft = BaseGrid::gravsoft(&std::fs::read("geodesy/geoid/test.geoid")?)?;
+        assert_eq!(grid.bands, 1);
+
+        for (lat, lon) in [(54., 8.), (55.5, 12.25), (57.9, 8.1), (56.1, 9.9)] {
+            let c = Coor4D::geo(lat, lon, 0., 0.);
+            let (n, m) = (grid.at(&c, 0.).unwrap(), gravsoft.at(&c, 0.).unwrap());
+            assert!((n[0] - m[0]).abs() < 1e-5);
+        }
+
+        // The north eastern corner node is missing
+        assert!(grid.at(&Coor4D::geo(57.5, 15.5, 0., 0.), 0.).is_none());
+
+        assert!(BaseGrid::byn(&[0; 79]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+//! Decompression of the TIFF data blocks: Deflate (RFC 1950/1951) and
+//! TIFF flavoured LZW.
+//!
+//! Both decoders are straightforward and unoptimized, but grids are read
+//! only once, and typically sized in the megabytes, so simplicity wins.
+//! The inflater follows the structure of Mark Adler's `puff.c`, the
+//! reference implementation distributed with zlib.
+use crate::Error;
+
+fn corrupt(what: &str) -> Error {
+    Error::Invalid(format!("GeoTIFF: Corrupt {what} data"))
+}
+
+// ----- D E F L A T E -----------------------------------------------------------------
+
+/// Inflate a zlib wrapped deflate stream
+pub(super) fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    // The zlib header: Compression method 8 (deflate), and a check sum
+    let [cmf, flg, ..] = data else {
+        return Err(corrupt("deflate"));
+    };
+    if cmf & 0x0f != 8 || (*cmf as u16 * 256 + *flg as u16) % 31 != 0 || flg & 0x20 != 0 {
+        return Err(corrupt("deflate"));
+    }
+
+    let mut input = BitReader::new(&data[2..]);
+    let mut output = Vec::new();
+    loop {
+        let last = input.bits(1)? == 1;
+        match input.bits(2)? {
+            0 => stored(&mut input, &mut output)?,
+            1 => {
+                let (literals, distances) = fixed_tables();
+                codes(&mut input, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut input)?;
+                codes(&mut input, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(corrupt("deflate")),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+// Deflate streams are read from the least significant bit of each byte
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn bits(&mut self, need: u32) -> Result<u32, Error> {
+        while self.count < need {
+            let Some(&byte) = self.data.get(self.position) else {
+                return Err(corrupt("deflate"));
+            };
+            self.buffer |= (byte as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1_u32 << need) - 1);
+        self.buffer >>= need;
+        self.count -= need;
+        Ok(value)
+    }
+}
+
+// A canonical Huffman code, represented by the number of codes of each
+// length, and the symbols ordered by code
+struct Huffman {
+    count: [u16; 16],
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut count = [0_u16; 16];
+        for &length in lengths {
+            count[length as usize] += 1;
+        }
+
+        // Offsets into the symbol table, for each code length
+        let mut offsets = [0_u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + count[length];
+        }
+
+        let mut symbol = vec![0_u16; lengths.len()];
+        for (s, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbol[offsets[length as usize] as usize] = s as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Self { count, symbol }
+    }
+
+    fn decode(&self, input: &mut BitReader) -> Result<u16, Error> {
+        let mut code = 0_i32; // The bits read so far
+        let mut first = 0_i32; // The first code of the current length
+        let mut index = 0_i32; // Index of the first code of the current length
+        for length in 1..16 {
+            code |= input.bits(1)? as i32;
+            let count = self.count[length] as i32;
+            if code - count < first {
+                return Ok(self.symbol[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(corrupt("deflate"))
+    }
+}
+
+// A stored (uncompressed) block
+fn stored(input: &mut BitReader, output: &mut Vec<u8>) -> Result<(), Error> {
+    // Discard the remaining bits of the current byte
+    input.buffer = 0;
+    input.count = 0;
+
+    let position = input.position;
+    let Some(header) = input.data.get(position..position + 4) else {
+        return Err(corrupt("deflate"));
+    };
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen {
+        return Err(corrupt("deflate"));
+    }
+
+    let start = position + 4;
+    let Some(block) = input.data.get(start..start + len as usize) else {
+        return Err(corrupt("deflate"));
+    };
+    output.extend_from_slice(block);
+    input.position = start + len as usize;
+    Ok(())
+}
+
+// The code tables of a fixed Huffman block
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0_u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+// Read the code tables of a dynamic Huffman block
+fn dynamic_tables(input: &mut BitReader) -> Result<(Huffman, Huffman), Error> {
+    const ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+
+    let nlen = input.bits(5)? as usize + 257;
+    let ndist = input.bits(5)? as usize + 1;
+    let ncode = input.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return Err(corrupt("deflate"));
+    }
+
+    // The code length code
+    let mut lengths = [0_u8; 19];
+    for &i in ORDER.iter().take(ncode) {
+        lengths[i] = input.bits(3)? as u8;
+    }
+    let lencode = Huffman::new(&lengths);
+
+    // The literal/length and distance code lengths, run length encoded
+    let mut lengths = Vec::with_capacity(nlen + ndist);
+    while lengths.len() < nlen + ndist {
+        let symbol = lencode.decode(input)?;
+        let (length, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let Some(&previous) = lengths.last() else {
+                    return Err(corrupt("deflate"));
+                };
+                (previous, 3 + input.bits(2)?)
+            }
+            17 => (0, 3 + input.bits(3)?),
+            _ => (0, 11 + input.bits(7)?),
+        };
+        for _ in 0..repeat {
+            lengths.push(length);
+        }
+    }
+    if lengths.len() > nlen + ndist || lengths[256] == 0 {
+        return Err(corrupt("deflate"));
+    }
+
+    let literals = Huffman::new(&lengths[..nlen]);
+    let distances = Huffman::new(&lengths[nlen..]);
+    Ok((literals, distances))
+}
+
+// Decode the literals and length/distance pairs of a compressed block
+fn codes(
+    input: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), Error> {
+    #[rustfmt::skip]
+    const LENGTH_BASE: [u16; 29] = [
+        3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+        35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+    ];
+    #[rustfmt::skip]
+    const LENGTH_EXTRA: [u32; 29] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+        3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+    ];
+    #[rustfmt::skip]
+    const DISTANCE_BASE: [u16; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385,
+        513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    #[rustfmt::skip]
+    const DISTANCE_EXTRA: [u32; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7,
+        8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+    ];
+
+    loop {
+        let symbol = literals.decode(input)? as usize;
+        if symbol < 256 {
+            output.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+
+        // A length/distance pair, referring back into the output
+        let symbol = symbol - 257;
+        if symbol >= 29 {
+            return Err(corrupt("deflate"));
+        }
+        let length = LENGTH_BASE[symbol] as usize + input.bits(LENGTH_EXTRA[symbol])? as usize;
+
+        let symbol = distances.decode(input)? as usize;
+        if symbol >= 30 {
+            return Err(corrupt("deflate"));
+        }
+        let distance =
+            DISTANCE_BASE[symbol] as usize + input.bits(DISTANCE_EXTRA[symbol])? as usize;
+        if distance > output.len() {
+            return Err(corrupt("deflate"));
+        }
+
+        // The copy may overlap its own output, so we go byte by byte
+        let start = output.len() - distance;
+        for i in 0..length {
+            output.push(output[start + i]);
+        }
+    }
+}
+
+// ----- L Z W -------------------------------------------------------------------------
+
+/// Decode TIFF flavoured LZW, i.e. with codes stored most significant bit
+/// first, and with the code width increasing one code "early"
+pub(super) fn lzw(data: &[u8]) -> Result<Vec<u8>, Error> {
+    const CLEAR: usize = 256;
+    const END_OF_INFORMATION: usize = 257;
+
+    // Each entry of the string table refers to a (start, length) slice of
+    // the output: A new entry is always the previous string, extended by
+    // the first byte of the string following it, i.e. a slice of the output
+    let mut table: Vec<(usize, usize)> = (0..258).map(|i| (i, 0)).collect();
+    let mut output = Vec::with_capacity(4 * data.len());
+    let mut previous: Option<(usize, usize)> = None;
+
+    let mut width = 9;
+    let (mut buffer, mut count, mut position) = (0_u32, 0_u32, 0_usize);
+    loop {
+        // Read the next code. A missing end-of-information code is tolerated
+        while count < width {
+            let Some(&byte) = data.get(position) else {
+                return Ok(output);
+            };
+            buffer = (buffer << 8) | byte as u32;
+            position += 1;
+            count += 8;
+        }
+        let code = ((buffer >> (count - width)) & ((1 << width) - 1)) as usize;
+        count -= width;
+
+        if code == END_OF_INFORMATION {
+            return Ok(output);
+        }
+        if code == CLEAR {
+            table.truncate(258);
+            width = 9;
+            previous = None;
+            continue;
+        }
+
+        let start = output.len();
+        let entry = if code < 256 {
+            output.push(code as u8);
+            (start, 1)
+        } else if code < table.len() {
+            let (from, length) = table[code];
+            output.extend_from_within(from..from + length);
+            (start, length)
+        } else if let (true, Some((from, length))) = (code == table.len(), previous) {
+            // The string being defined by this very code
+            output.extend_from_within(from..from + length);
+            output.push(output[from]);
+            (start, length + 1)
+        } else {
+            return Err(corrupt("LZW"));
+        };
+
+        if let Some((from, length)) = previous {
+            if table.len() < 4096 {
+                table.push((from, length + 1));
+            }
+        }
+        previous = Some(entry);
+
+        if table.len() + 1 >= 1 << width && width < 12 {
+            width += 1;
+        }
+    }
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Pseudo random decimal digits, from a linear congruential generator.
+    // The files `geodesy/tif/digits.*` hold these, compressed by zlib
+    // (using dynamic Huffman codes), and by a TIFF LZW encoder (growing
+    // the code width to 12 bits, and clearing the table when full)
+    fn digits() -> Vec<u8> {
+        let mut state = 1_u32;
+        (0..20_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                b'0' + (state >> 16) as u8 % 10
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deflate() -> Result<(), Error> {
+        // Compressed by zlib, using fixed Huffman codes
+        #[rustfmt::skip]
+        let data = [
+            0x78, 0xda, 0xf3, 0x48, 0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0xf0,
+            0x40, 0xa2, 0x14, 0x01, 0x46, 0x3e, 0x06, 0x96,
+        ];
+        assert_eq!(inflate(&data)?, b"Hello, Hello, Hello!");
+
+        // A stored block
+        let data = [0x78, 0x01, 0x01, 0x03, 0x00, 0xfc, 0xff, 0x61, 0x62, 0x63];
+        assert_eq!(inflate(&data)?, b"abc");
+
+        // Dynamic Huffman codes
+        let data = std::fs::read("geodesy/tif/digits.zlib")?;
+        assert_eq!((data[2] >> 1) & 3, 2);
+        assert_eq!(inflate(&data)?, digits());
+
+        // Not zlib
+        assert!(inflate(&[0x1f, 0x8b, 0x08]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn lzw() -> Result<(), Error> {
+        // The classic example, compressed with 9 bit codes
+        #[rustfmt::skip]
+        let data = [
+            0x80, 0x15, 0x09, 0xe4, 0x22, 0x29, 0x3c, 0xa4, 0x4e, 0x27, 0x95,
+            0x20, 0x50, 0x48, 0x34, 0x2e, 0x0b, 0x07, 0x84, 0xc0, 0x40,
+        ];
+        assert_eq!(super::lzw(&data)?, b"TOBEORNOTTOBEORTOBEORNOT");
+        assert!(super::lzw(&[0xff, 0xff]).is_err());
+
+        // A long stream, with all code widths from 9 to 12 bits
+        let data = std::fs::read("geodesy/tif/digits.lzw")?;
+        assert_eq!(super::lzw(&data)?, digits());
+        Ok(())
+    }
+}
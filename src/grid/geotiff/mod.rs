@@ -0,0 +1,211 @@
+mod decompress;
+mod parser;
+mod subgrid;
+
+use super::BaseGrid;
+use crate::{Coor4D, Error, Grid};
+use parser::TiffParser;
+
+/// Grid for using the GeoTIFF format, following the PROJ
+/// [conventions for geodetic grids](https://proj.org/specifications/geodetictiffgrids.html),
+/// i.e. the format of the grids distributed in PROJ-data.
+///
+/// Each full resolution image of the file is read as a separate subgrid,
+/// while overviews are ignored. The band semantics are read from the GDAL
+/// metadata, and the values are converted to the conventions of the other
+/// grid readers: Horizontal offsets are delivered as (longitude, latitude)
+/// offsets in radians, velocities as (east, north, up) in metres per year,
+/// and vertical offsets in metres. Metric horizontal offsets are delivered
+/// as (east, north, up) offsets in metres, with a zero up component, so a
+/// 2 band grid always holds angular offsets. Projected grids are not
+/// supported. Nodes marked as "nodata" are treated as being outside of
+/// the grid.
+#[derive(Debug, Default, Clone)]
+pub struct GeoTiffGrid {
+    // The subgrids, in file order
+    subgrids: Vec<BaseGrid>,
+}
+
+impl GeoTiffGrid {
+    pub fn new(buf: &[u8]) -> Result<Self, Error> {
+        let parser = TiffParser::new(buf)?;
+
+        let mut subgrids = Vec::new();
+        for ifd in parser.ifds()? {
+            if let Some(grid) = subgrid::geotiff_subgrid(&parser, &ifd)? {
+                subgrids.push(grid);
+            }
+        }
+
+        let Some(first) = subgrids.first() else {
+            return Err(Error::Invalid("GeoTIFF: No grids found".to_string()));
+        };
+        if subgrids.iter().any(|grid| grid.bands != first.bands) {
+            return Err(Error::Unsupported(
+                "GeoTIFF: Subgrids with differing number of bands".to_string(),
+            ));
+        }
+        Ok(Self { subgrids })
+    }
+
+    // Subgrids may be nested, with denser grids covering parts of their
+    // parent grid. So we look for the densest grid containing the point,
+    // and only if none is found, consider the margin
+    fn find_grid(&self, coord: &Coor4D, margin: f64) -> Option<&BaseGrid> {
+        let cell_size = |grid: &BaseGrid| (grid.dlat * grid.dlon).abs();
+        self.subgrids
+            .iter()
+            .filter(|grid| grid.contains(coord, 0.))
+            .min_by(|a, b| cell_size(a).total_cmp(&cell_size(b)))
+            .or_else(|| self.subgrids.iter().find(|g| g.contains(coord, margin)))
+    }
+}
+
+impl Grid for GeoTiffGrid {
+    fn bands(&self) -> usize {
+        self.subgrids[0].bands
+    }
+
+    /// Checks if a `Coord4D` is within the grid limits +- `margin` grid units
+    fn contains(&self, position: &Coor4D, margin: f64) -> bool {
+        self.find_grid(position, margin).is_some()
+    }
+
+    fn at(&self, coord: &Coor4D, margin: f64) -> Option<Coor4D> {
+        self.find_grid(coord, margin)?.at(coord, margin)
+    }
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn read(name: &str) -> Vec<u8> {
+        std::fs::read(name).unwrap()
+    }
+
+    // Tiled BigTIFF, deflate compressed, using the floating point predictor
+    #[test]
+    fn geoid() -> Result<(), Error> {
+        let grid = GeoTiffGrid::new(&read("geodesy/tif/test_geoid.tif"))?;
+        let gravsoft = BaseGrid::gravsoft(&read("geodesy/geoid/test.geoid"))?;
+        assert_eq!(grid.subgrids.len(), 1);
+        assert_eq!(grid.bands(), 1);
+
+        for (lat, lon) in [(58., 8.), (55.5, 12.25), (54., 16.), (56.1, 9.9)] {
+            let c = Coor4D::geo(lat, lon, 0., 0.);
+            assert_eq!(grid.at(&c, 0.), gravsoft.at(&c, 0.));
+        }
+        let n = grid.at(&Coor4D::geo(55., 12., 0., 0.), 0.).unwrap();
+        assert!((n[0] - 55.12).abs() < 1e-5);
+
+        let outside = Coor4D::geo(58.5, 12., 0., 0.);
+        assert!(!grid.contains(&outside, 0.));
+        assert!(grid.contains(&outside, 1.));
+        Ok(())
+    }
+
+    // Big endian, striped, LZW compressed, scaled 16 bit integers in
+    // separate planes, with longitude positive west, and a subgrid
+    #[test]
+    fn datum() -> Result<(), Error> {
+        let grid = GeoTiffGrid::new(&read("geodesy/tif/test_datum.tif"))?;
+        let gravsoft = BaseGrid::gravsoft(&read("geodesy/datum/test.datum"))?;
+
+        // The overview is ignored
+        assert_eq!(grid.subgrids.len(), 2);
+        assert_eq!(grid.bands(), 2);
+
+        // Outside of the subgrid, we get the values of the main grid
+        let c = Coor4D::geo(57.25, 9.5, 0., 0.);
+        let (v, w) = (grid.at(&c, 0.).unwrap(), gravsoft.at(&c, 0.).unwrap());
+        assert!(v.to_arcsec().hypot2(&w.to_arcsec()) < 1e-5);
+        let arcsec = v.to_arcsec();
+        assert!((arcsec[0] - 9.5).abs() < 1e-5);
+        assert!((arcsec[1] - 57.25).abs() < 1e-5);
+
+        // Inside the subgrid, the values are offset by 1 arcsec
+        let c = Coor4D::geo(55.25, 12.75, 0., 0.);
+        let (v, w) = (grid.at(&c, 0.).unwrap(), gravsoft.at(&c, 0.).unwrap());
+        let d = (v - w).to_arcsec();
+        assert!((d[0] - 1.).abs() < 1e-5 && (d[1] - 1.).abs() < 1e-5);
+        Ok(())
+    }
+
+    // Uncompressed, pixel interleaved, with reordering of the bands, unit
+    // conversion, and a nodata value
+    #[test]
+    fn deformation() -> Result<(), Error> {
+        let grid = GeoTiffGrid::new(&read("geodesy/tif/test_deformation.tif"))?;
+        let gravsoft = BaseGrid::gravsoft(&read("geodesy/deformation/test.deformation"))?;
+        assert_eq!(grid.bands(), 3);
+
+        let c = Coor4D::geo(56.5, 10.25, 0., 0.);
+        assert_eq!(grid.at(&c, 0.), gravsoft.at(&c, 0.));
+
+        // The south eastern corner node is nodata
+        assert!(grid.at(&Coor4D::geo(54.5, 15.5, 0., 0.), 0.).is_none());
+        assert!(grid.at(&Coor4D::geo(54.5, 14.5, 0., 0.), 0.).is_some());
+        Ok(())
+    }
+
+    // Replace all occurrences of `from` in `buf` by `to`, of the same length
+    fn patch(buf: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+        let mut buf = buf.to_vec();
+        let mut i = 0;
+        while i + from.len() <= buf.len() {
+            if &buf[i..i + from.len()] == from {
+                buf[i..i + from.len()].copy_from_slice(to);
+            }
+            i += 1;
+        }
+        buf
+    }
+
+    // Metric horizontal offsets are delivered with a zero up band
+    #[test]
+    fn east_north() -> Result<(), Error> {
+        let buf = read("geodesy/tif/test_datum.tif");
+        let buf = patch(&buf, b"longitude_offset", b"     east_offset");
+        let buf = patch(&buf, b"latitude_offset", b"   north_offset");
+        let buf = patch(&buf, b"arc-second", b"     metre");
+        let grid = GeoTiffGrid::new(&buf)?;
+        assert_eq!(grid.bands(), 3);
+
+        let v = grid.at(&Coor4D::geo(57.25, 9.5, 0., 0.), 0.).unwrap();
+        assert!((v[0] - 9.5).abs() < 1e-5);
+        assert!((v[1] - 57.25).abs() < 1e-5);
+        assert_eq!(v[2], 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            GeoTiffGrid::new(b"GIF89a"),
+            Err(Error::Unsupported(_))
+        ));
+        let buf = read("geodesy/tif/test_datum.tif");
+        assert!(GeoTiffGrid::new(&buf[..buf.len() / 2]).is_err());
+
+        // Projected grids (GTModelTypeGeoKey = 1) are not supported
+        let projected = patch(&buf, &[4, 0, 0, 0, 0, 1, 0, 2], &[4, 0, 0, 0, 0, 1, 0, 1]);
+        assert!(matches!(
+            GeoTiffGrid::new(&projected),
+            Err(Error::Unsupported(_))
+        ));
+
+        // Corrupt image dimensions are detected before allocating
+        let buf = read("geodesy/tif/test_deformation.tif");
+        let huge = [0, 1, 4, 0, 1, 0, 0, 0, 255, 255, 255, 255];
+        let buf = patch(&buf, &[0, 1, 3, 0, 1, 0, 0, 0, 9, 0, 0, 0], &huge);
+        assert!(matches!(GeoTiffGrid::new(&buf), Err(Error::Invalid(_))));
+        let mut huge = huge;
+        huge[0] = 1;
+        let buf = patch(&buf, &[1, 1, 3, 0, 1, 0, 0, 0, 5, 0, 0, 0], &huge);
+        assert!(matches!(GeoTiffGrid::new(&buf), Err(Error::Invalid(_))));
+    }
+}
@@ -0,0 +1,260 @@
+use crate::Error;
+use std::collections::BTreeMap;
+
+// Baseline and extension TIFF tags
+pub(super) const NEW_SUBFILE_TYPE: u16 = 254;
+pub(super) const IMAGE_WIDTH: u16 = 256;
+pub(super) const IMAGE_LENGTH: u16 = 257;
+pub(super) const BITS_PER_SAMPLE: u16 = 258;
+pub(super) const COMPRESSION: u16 = 259;
+pub(super) const STRIP_OFFSETS: u16 = 273;
+pub(super) const SAMPLES_PER_PIXEL: u16 = 277;
+pub(super) const ROWS_PER_STRIP: u16 = 278;
+pub(super) const STRIP_BYTE_COUNTS: u16 = 279;
+pub(super) const PLANAR_CONFIGURATION: u16 = 284;
+pub(super) const PREDICTOR: u16 = 317;
+pub(super) const TILE_WIDTH: u16 = 322;
+pub(super) const TILE_LENGTH: u16 = 323;
+pub(super) const TILE_OFFSETS: u16 = 324;
+pub(super) const TILE_BYTE_COUNTS: u16 = 325;
+pub(super) const SAMPLE_FORMAT: u16 = 339;
+
+// GeoTIFF tags
+pub(super) const MODEL_PIXEL_SCALE: u16 = 33550;
+pub(super) const MODEL_TIEPOINT: u16 = 33922;
+pub(super) const MODEL_TRANSFORMATION: u16 = 34264;
+pub(super) const GEO_KEY_DIRECTORY: u16 = 34735;
+
+// GDAL tags
+pub(super) const GDAL_METADATA: u16 = 42112;
+pub(super) const GDAL_NODATA: u16 = 42113;
+
+/// The value of a TIFF tag. Unsigned integer types are kept as integers,
+/// while signed, rational and floating point types are converted to `f64`
+#[derive(Debug, Clone)]
+pub(super) enum Value {
+    Integers(Vec<u64>),
+    Reals(Vec<f64>),
+    Ascii(String),
+}
+
+/// An Image File Directory, i.e. the tags describing one image of the file
+#[derive(Debug, Default, Clone)]
+pub(super) struct Ifd(BTreeMap<u16, Value>);
+
+impl Ifd {
+    pub fn integers(&self, tag: u16) -> Option<&[u64]> {
+        match self.0.get(&tag) {
+            Some(Value::Integers(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self, tag: u16) -> Option<u64> {
+        self.integers(tag).and_then(|v| v.first().copied())
+    }
+
+    pub fn reals(&self, tag: u16) -> Option<Vec<f64>> {
+        match self.0.get(&tag) {
+            Some(Value::Integers(v)) => Some(v.iter().map(|&i| i as f64).collect()),
+            Some(Value::Reals(v)) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn ascii(&self, tag: u16) -> Option<&str> {
+        match self.0.get(&tag) {
+            Some(Value::Ascii(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    // Required integer tags
+    pub fn require(&self, tag: u16) -> Result<u64, Error> {
+        self.integer(tag)
+            .ok_or_else(|| Error::Invalid(format!("GeoTIFF: Missing tag {tag}")))
+    }
+}
+
+/// A parser for the TIFF file structure, supporting classic TIFF as well
+/// as BigTIFF, in both byte orders. Based on the
+/// [TIFF 6.0 specification](https://www.itu.int/itudoc/itu-t/com16/tiff-fx/docs/tiff6.pdf)
+/// and the [BigTIFF design](https://www.awaresystems.be/imaging/tiff/bigtiff.html)
+pub(super) struct TiffParser<'a> {
+    buf: &'a [u8],
+    pub is_big_endian: bool,
+    is_bigtiff: bool,
+}
+
+impl<'a> TiffParser<'a> {
+    pub fn new(buf: &'a [u8]) -> Result<Self, Error> {
+        let is_big_endian = match buf.get(0..2) {
+            Some(b"II") => false,
+            Some(b"MM") => true,
+            _ => return Err(Error::Unsupported("Not a TIFF file".to_string())),
+        };
+        let mut parser = Self {
+            buf,
+            is_big_endian,
+            is_bigtiff: false,
+        };
+        match parser.get_u16(2)? {
+            42 => {}
+            43 => parser.is_bigtiff = true,
+            _ => return Err(Error::Unsupported("Not a TIFF file".to_string())),
+        }
+        Ok(parser)
+    }
+
+    pub fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8], Error> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.buf.get(offset..end))
+            .ok_or_else(|| Error::Invalid("GeoTIFF: Unexpected end of file".to_string()))
+    }
+
+    // Read an unsigned integer of `len` bytes, respecting the byte order
+    fn get_uint(&self, offset: usize, len: usize) -> Result<u64, Error> {
+        let bytes = self.bytes(offset, len)?;
+        let fold = |acc: u64, &b: &u8| (acc << 8) | b as u64;
+        Ok(match self.is_big_endian {
+            true => bytes.iter().fold(0, fold),
+            false => bytes.iter().rev().fold(0, fold),
+        })
+    }
+
+    pub fn get_u16(&self, offset: usize) -> Result<u16, Error> {
+        Ok(self.get_uint(offset, 2)? as u16)
+    }
+
+    pub fn get_u32(&self, offset: usize) -> Result<u32, Error> {
+        Ok(self.get_uint(offset, 4)? as u32)
+    }
+
+    pub fn get_u64(&self, offset: usize) -> Result<u64, Error> {
+        self.get_uint(offset, 8)
+    }
+
+    // The size of file offsets and value fields
+    fn offset_size(&self) -> usize {
+        if self.is_bigtiff {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn get_offset(&self, offset: usize) -> Result<usize, Error> {
+        Ok(self.get_uint(offset, self.offset_size())? as usize)
+    }
+
+    /// Read all the image file directories of the file
+    pub fn ifds(&self) -> Result<Vec<Ifd>, Error> {
+        let mut ifds = Vec::new();
+        let mut next = self.get_offset(if self.is_bigtiff { 8 } else { 4 })?;
+        while next != 0 {
+            // Guard against circular IFD chains in corrupt files
+            if ifds.len() > 1000 {
+                return Err(Error::Invalid("GeoTIFF: Too many IFDs".to_string()));
+            }
+            let (ifd, following) = self.ifd(next)?;
+            ifds.push(ifd);
+            next = following;
+        }
+        Ok(ifds)
+    }
+
+    // Read the IFD at `offset`. Return it, and the offset of the next IFD
+    fn ifd(&self, offset: usize) -> Result<(Ifd, usize), Error> {
+        let (count, entry_size, start) = if self.is_bigtiff {
+            (self.get_u64(offset)? as usize, 20, offset + 8)
+        } else {
+            (self.get_u16(offset)? as usize, 12, offset + 2)
+        };
+
+        // Check that the entire directory is inside the file
+        let size = count.saturating_mul(entry_size);
+        self.bytes(start, size)?;
+
+        let mut ifd = Ifd::default();
+        for i in 0..count {
+            let entry = start + i * entry_size;
+            let tag = self.get_u16(entry)?;
+            let kind = self.get_u16(entry + 2)?;
+            let (count, field) = if self.is_bigtiff {
+                (self.get_u64(entry + 4)? as usize, entry + 12)
+            } else {
+                (self.get_u32(entry + 4)? as usize, entry + 8)
+            };
+            // Silently ignore tags of unknown types, as required by the spec
+            if let Some(value) = self.value(kind, count, field)? {
+                ifd.0.insert(tag, value);
+            }
+        }
+        let next = self.get_offset(start + size)?;
+        Ok((ifd, next))
+    }
+
+    // Read the value of an IFD entry, given its type, count, and the
+    // location of its value field
+    fn value(&self, kind: u16, count: usize, field: usize) -> Result<Option<Value>, Error> {
+        let size: usize = match kind {
+            1 | 2 | 6 | 7 => 1,
+            3 | 8 => 2,
+            4 | 9 | 11 => 4,
+            5 | 10 | 12 | 16 | 17 | 18 => 8,
+            _ => return Ok(None),
+        };
+
+        // Small values are stored directly in the value field
+        let Some(total) = size.checked_mul(count) else {
+            return Err(Error::Invalid("GeoTIFF: Bad tag count".to_string()));
+        };
+        let at = if total <= self.offset_size() {
+            field
+        } else {
+            self.get_offset(field)?
+        };
+        self.bytes(at, total)?;
+
+        let value = match kind {
+            2 => {
+                let bytes = self.bytes(at, total)?;
+                let text = String::from_utf8_lossy(bytes);
+                Value::Ascii(text.trim_end_matches('\0').to_string())
+            }
+            1 | 3 | 4 | 7 | 16 | 18 => {
+                let v = (0..count).map(|i| self.get_uint(at + i * size, size));
+                Value::Integers(v.collect::<Result<_, _>>()?)
+            }
+            5 | 10 => {
+                let mut v = Vec::with_capacity(count);
+                for i in 0..count {
+                    let (n, d) = (self.get_u32(at + 8 * i)?, self.get_u32(at + 8 * i + 4)?);
+                    v.push(if kind == 5 {
+                        n as f64 / d as f64
+                    } else {
+                        n as i32 as f64 / d as i32 as f64
+                    });
+                }
+                Value::Reals(v)
+            }
+            _ => {
+                let mut v = Vec::with_capacity(count);
+                for i in 0..count {
+                    let raw = self.get_uint(at + i * size, size)?;
+                    v.push(match kind {
+                        6 => raw as u8 as i8 as f64,
+                        8 => raw as u16 as i16 as f64,
+                        9 => raw as u32 as i32 as f64,
+                        11 => f32::from_bits(raw as u32) as f64,
+                        12 => f64::from_bits(raw),
+                        _ => raw as i64 as f64,
+                    });
+                }
+                Value::Reals(v)
+            }
+        };
+        Ok(Some(value))
+    }
+}
@@ -0,0 +1,502 @@
+use super::decompress::{inflate, lzw};
+use super::parser::*;
+use super::*;
+
+/// Read the grid described by `ifd`. Reduced resolution versions of other
+/// grids (overviews), and transparency masks, are ignored, returning `None`
+pub(super) fn geotiff_subgrid(parser: &TiffParser, ifd: &Ifd) -> Result<Option<BaseGrid>, Error> {
+    if ifd.integer(NEW_SUBFILE_TYPE).unwrap_or(0) & 5 != 0 {
+        return Ok(None);
+    }
+
+    let layout = Layout::new(ifd)?;
+    let bands = band_metadata(ifd, layout.bands)?;
+    let nodata = ifd
+        .ascii(GDAL_NODATA)
+        .and_then(|n| n.trim().parse::<f64>().ok());
+    let samples = read_samples(parser, &layout, nodata)?;
+
+    // Select and order the bands, and convert to the units used by Geodesy
+    let mut grid = Vec::with_capacity(samples.len() / layout.bands * bands.len());
+    for node in samples.chunks_exact(layout.bands) {
+        for band in &bands {
+            let value = node[band.index] * band.scale + band.offset;
+            grid.push((value * band.factor) as f32);
+        }
+    }
+
+    let mut header = georeference(ifd, layout.width, layout.height)?;
+    header[6] = bands.len() as f64;
+    Ok(Some(BaseGrid::plain(&header, Some(&grid), None)?))
+}
+
+// ----- Layout of the image data ------------------------------------------------------
+
+// The organization of the image data: Either tiles or strips, with the
+// samples of each pixel stored together (chunky), or in separate planes
+struct Layout {
+    width: usize,
+    height: usize,
+    bands: usize,
+    size: usize,  // Bytes per sample
+    format: u64,  // Sample format: 1: unsigned integer, 2: signed integer, 3: float
+    planar: bool, // Separate planes for each band?
+    tiled: bool,
+    block_width: usize,
+    block_height: usize,
+    offsets: Vec<u64>,
+    counts: Vec<u64>,
+    compression: u64,
+    predictor: u64,
+}
+
+impl Layout {
+    fn new(ifd: &Ifd) -> Result<Self, Error> {
+        let width = ifd.require(IMAGE_WIDTH)? as usize;
+        let height = ifd.require(IMAGE_LENGTH)? as usize;
+        if width < 2 || height < 2 {
+            return Err(Error::Invalid("GeoTIFF: Grid too small".to_string()));
+        }
+        let bands = ifd.integer(SAMPLES_PER_PIXEL).unwrap_or(1) as usize;
+        if bands == 0 {
+            return Err(Error::Invalid("GeoTIFF: No bands".to_string()));
+        }
+
+        // We require all bands to share the same sample type
+        let bits = ifd.integers(BITS_PER_SAMPLE).unwrap_or(&[1]);
+        let formats = ifd.integers(SAMPLE_FORMAT).unwrap_or(&[1]);
+        if bits.iter().any(|&b| b != bits[0]) || formats.iter().any(|&f| f != formats[0]) {
+            return Err(Error::Unsupported(
+                "GeoTIFF: Bands of differing types".to_string(),
+            ));
+        }
+        let (bits, format) = (bits[0], formats[0]);
+        match (format, bits) {
+            (1 | 2, 8 | 16 | 32) | (3, 32 | 64) => {}
+            _ => {
+                return Err(Error::Unsupported(format!(
+                    "GeoTIFF: Sample format {format} with {bits} bits"
+                )))
+            }
+        }
+
+        let planar = ifd.integer(PLANAR_CONFIGURATION).unwrap_or(1) == 2;
+        let compression = ifd.integer(COMPRESSION).unwrap_or(1);
+        if ![1, 5, 8, 32946].contains(&compression) {
+            return Err(Error::Unsupported(format!(
+                "GeoTIFF: Compression type {compression}"
+            )));
+        }
+        let predictor = ifd.integer(PREDICTOR).unwrap_or(1);
+        if predictor > 3 || (predictor == 3 && format != 3) || (predictor == 2 && format == 3) {
+            return Err(Error::Unsupported(format!(
+                "GeoTIFF: Predictor {predictor}"
+            )));
+        }
+
+        // Tiles or strips?
+        let tiled = ifd.integer(TILE_WIDTH).is_some();
+        let (block_width, block_height, offsets, counts) = if tiled {
+            (
+                ifd.require(TILE_WIDTH)? as usize,
+                ifd.require(TILE_LENGTH)? as usize,
+                ifd.integers(TILE_OFFSETS),
+                ifd.integers(TILE_BYTE_COUNTS),
+            )
+        } else {
+            let rows = ifd.integer(ROWS_PER_STRIP).unwrap_or(u32::MAX as u64) as usize;
+            (
+                width,
+                rows.min(height),
+                ifd.integers(STRIP_OFFSETS),
+                ifd.integers(STRIP_BYTE_COUNTS),
+            )
+        };
+        let (Some(offsets), Some(counts)) = (offsets, counts) else {
+            return Err(Error::Invalid("GeoTIFF: Missing data offsets".to_string()));
+        };
+        if block_width == 0 || block_height == 0 {
+            return Err(Error::Invalid("GeoTIFF: Bad block size".to_string()));
+        }
+
+        let layout = Layout {
+            width,
+            height,
+            bands,
+            size: bits as usize / 8,
+            format,
+            planar,
+            tiled,
+            block_width,
+            block_height,
+            offsets: offsets.to_vec(),
+            counts: counts.to_vec(),
+            compression,
+            predictor,
+        };
+
+        let blocks = layout.blocks_across().saturating_mul(layout.blocks_down());
+        let blocks = blocks.saturating_mul(layout.planes());
+        if layout.offsets.len() < blocks || layout.counts.len() < blocks {
+            return Err(Error::Invalid("GeoTIFF: Missing data blocks".to_string()));
+        }
+
+        // The image size comes from the header, so before allocating space for
+        // the samples, we check that it can actually be held by the data blocks.
+        // Neither LZW nor deflate compress by more than a factor of about 1400
+        let ratio = if compression == 1 { 1 } else { 1400 };
+        let capacity = layout.counts[..blocks]
+            .iter()
+            .fold(0_u64, |sum, &count| sum.saturating_add(count))
+            .saturating_mul(ratio);
+        let size = width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(bands))
+            .and_then(|n| n.checked_mul(layout.size));
+        let block_size = block_width
+            .checked_mul(block_height)
+            .and_then(|n| n.checked_mul(layout.samples_per_pixel()))
+            .and_then(|n| n.checked_mul(layout.size));
+        if block_size.is_none() || size.map_or(true, |size| size as u64 > capacity) {
+            return Err(Error::Invalid(
+                "GeoTIFF: Image size exceeds the data".to_string(),
+            ));
+        }
+        Ok(layout)
+    }
+
+    fn blocks_across(&self) -> usize {
+        (self.width - 1) / self.block_width + 1
+    }
+
+    fn blocks_down(&self) -> usize {
+        (self.height - 1) / self.block_height + 1
+    }
+
+    fn planes(&self) -> usize {
+        if self.planar {
+            self.bands
+        } else {
+            1
+        }
+    }
+
+    // The number of samples per pixel of each block
+    fn samples_per_pixel(&self) -> usize {
+        if self.planar {
+            1
+        } else {
+            self.bands
+        }
+    }
+}
+
+// Read the entire image into a vector of band interleaved values, in row
+// major order. Values equal to `nodata` are replaced by NaN
+fn read_samples(
+    parser: &TiffParser,
+    layout: &Layout,
+    nodata: Option<f64>,
+) -> Result<Vec<f64>, Error> {
+    let (width, height, bands) = (layout.width, layout.height, layout.bands);
+    let mut samples = vec![0.; width * height * bands];
+
+    let spp = layout.samples_per_pixel();
+    let row_length = layout.block_width * spp;
+    let row_size = row_length * layout.size;
+    let (across, down) = (layout.blocks_across(), layout.blocks_down());
+
+    for plane in 0..layout.planes() {
+        for block_row in 0..down {
+            for block_col in 0..across {
+                let index = (plane * down + block_row) * across + block_col;
+                let mut block = decompress(parser, layout, index)?;
+
+                // Tiles are always full size, while the last strip may be shorter
+                let first_row = block_row * layout.block_height;
+                let rows = if layout.tiled {
+                    layout.block_height
+                } else {
+                    layout.block_height.min(height - first_row)
+                };
+                if block.len() < rows * row_size {
+                    return Err(Error::Invalid("GeoTIFF: Short data block".to_string()));
+                }
+
+                for (r, row) in block.chunks_exact_mut(row_size).take(rows).enumerate() {
+                    let y = first_row + r;
+                    if y >= height {
+                        break;
+                    }
+                    unpredict(row, layout, parser.is_big_endian);
+
+                    for c in 0..layout.block_width {
+                        let x = block_col * layout.block_width + c;
+                        if x >= width {
+                            break;
+                        }
+                        for s in 0..spp {
+                            let at = (c * spp + s) * layout.size;
+                            let mut value = sample(row, at, layout, parser.is_big_endian);
+                            if Some(value) == nodata {
+                                value = f64::NAN;
+                            }
+                            let band = if layout.planar { plane } else { s };
+                            samples[(y * width + x) * bands + band] = value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(samples)
+}
+
+fn decompress(parser: &TiffParser, layout: &Layout, index: usize) -> Result<Vec<u8>, Error> {
+    let offset = layout.offsets[index] as usize;
+    let count = layout.counts[index] as usize;
+    let data = parser.bytes(offset, count)?;
+    match layout.compression {
+        5 => lzw(data),
+        8 | 32946 => inflate(data),
+        _ => Ok(data.to_vec()),
+    }
+}
+
+// Read an unsigned integer of `size` bytes from `buf`, in the given byte order
+fn get(buf: &[u8], at: usize, size: usize, big_endian: bool) -> u64 {
+    let bytes = &buf[at..at + size];
+    let fold = |acc: u64, &b: &u8| (acc << 8) | b as u64;
+    match big_endian {
+        true => bytes.iter().fold(0, fold),
+        false => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+fn put(buf: &mut [u8], at: usize, size: usize, big_endian: bool, value: u64) {
+    for i in 0..size {
+        let byte = (value >> (8 * i)) as u8;
+        match big_endian {
+            true => buf[at + size - 1 - i] = byte,
+            false => buf[at + i] = byte,
+        }
+    }
+}
+
+// Decode the sample starting at byte `at` of `row`
+fn sample(row: &[u8], at: usize, layout: &Layout, big_endian: bool) -> f64 {
+    let raw = get(row, at, layout.size, big_endian);
+    match (layout.format, layout.size) {
+        (3, 4) => f32::from_bits(raw as u32) as f64,
+        (3, _) => f64::from_bits(raw),
+        (2, 1) => raw as u8 as i8 as f64,
+        (2, 2) => raw as u16 as i16 as f64,
+        (2, _) => raw as u32 as i32 as f64,
+        _ => raw as f64,
+    }
+}
+
+// Undo the differencing of the horizontal (2) or floating point (3) predictor,
+// for a single row of samples
+fn unpredict(row: &mut [u8], layout: &Layout, big_endian: bool) {
+    let size = layout.size;
+    let spp = layout.samples_per_pixel();
+
+    if layout.predictor == 2 {
+        let mask = u64::MAX >> (64 - 8 * size);
+        for at in (spp * size..row.len()).step_by(size) {
+            let sum = get(row, at, size, big_endian) + get(row, at - spp * size, size, big_endian);
+            put(row, at, size, big_endian, sum & mask);
+        }
+    }
+
+    // The floating point predictor differences the bytes, after reordering
+    // them into planes of most significant bytes first, then next, etc.
+    if layout.predictor == 3 {
+        for i in spp..row.len() {
+            row[i] = row[i].wrapping_add(row[i - spp]);
+        }
+        let n = row.len() / size;
+        let planes = row.to_vec();
+        for i in 0..n {
+            for b in 0..size {
+                let byte = planes[b * n + i];
+                let at = if big_endian { b } else { size - 1 - b };
+                row[i * size + at] = byte;
+            }
+        }
+    }
+}
+
+// ----- Band semantics ----------------------------------------------------------------
+
+// Band descriptions in the order expected by Geodesy, with their default unit
+const BAND_ORDERS: [(&[&str], &str); 5] = [
+    (&["longitude_offset", "latitude_offset"], "arc-second"),
+    (
+        &["east_velocity", "north_velocity", "up_velocity"],
+        "millimetres per year",
+    ),
+    (&["east_offset", "north_offset", "vertical_offset"], "metre"),
+    (&["east_offset", "north_offset"], "metre"),
+    (
+        &["x_translation", "y_translation", "z_translation"],
+        "metre",
+    ),
+];
+
+// A band of the output grid: Its index in the file, and the conversion
+// from the raw sample value to the value delivered
+#[derive(Debug)]
+struct Band {
+    index: usize,
+    scale: f64,
+    offset: f64,
+    factor: f64,
+}
+
+// Parse the band semantics from the GDAL metadata, and select the bands
+// to deliver, in the order expected by Geodesy. Bands of unknown semantics
+// are delivered in file order
+fn band_metadata(ifd: &Ifd, bands: usize) -> Result<Vec<Band>, Error> {
+    let mut description = vec![String::new(); bands];
+    let mut unit = vec![None; bands];
+    let mut scale = vec![1.; bands];
+    let mut offset = vec![0.; bands];
+    let mut west = vec![false; bands];
+
+    for (key, sample, value) in metadata_items(ifd.ascii(GDAL_METADATA).unwrap_or_default()) {
+        let Some(i) = sample.filter(|&i| i < bands) else {
+            continue;
+        };
+        match key.as_str() {
+            "description" => description[i] = value,
+            "unittype" => unit[i] = Some(value),
+            "scale" => scale[i] = value.parse().unwrap_or(1.),
+            "offset" => offset[i] = value.parse().unwrap_or(0.),
+            "positive_value" => west[i] = value == "west",
+            _ => {}
+        }
+    }
+
+    let position = |name: &&str| description.iter().position(|d| d == name);
+    let order = BAND_ORDERS
+        .iter()
+        .find(|(names, _)| names.iter().all(|name| position(name).is_some()));
+    let (indices, default_unit) = order
+        .map(|(names, unit)| (names.iter().filter_map(position).collect(), *unit))
+        .unwrap_or_else(|| ((0..bands).collect::<Vec<_>>(), "metre"));
+
+    let mut selected = Vec::with_capacity(indices.len());
+    for index in indices {
+        let unit = unit[index].as_deref().unwrap_or(default_unit);
+        let sign = if west[index] { -1. } else { 1. };
+        selected.push(Band {
+            index,
+            scale: scale[index],
+            offset: offset[index],
+            factor: sign * unit_factor(unit)?,
+        });
+    }
+
+    // Metric horizontal offsets are delivered as (east, north, up) grids, with
+    // a zero up band, so 2 band grids always hold angular offsets. Multiplying
+    // by zero retains the NaN of nodata values
+    if order.map(|(names, _)| names.len()) == Some(2) && default_unit == "metre" {
+        selected.push(Band {
+            index: selected[0].index,
+            scale: 0.,
+            offset: 0.,
+            factor: 0.,
+        });
+    }
+    Ok(selected)
+}
+
+// The factor converting a value in `unit` to the units used by Geodesy,
+// i.e. metres and radians (per year, for velocities)
+fn unit_factor(unit: &str) -> Result<f64, Error> {
+    Ok(match unit {
+        "arc-second" | "arc-seconds per year" => (1. / 3600_f64).to_radians(),
+        "degree" | "degrees per year" => 1_f64.to_radians(),
+        "radian" | "radians per year" => 1.,
+        "metre" | "meter" | "m" | "metres per year" | "m/year" => 1.,
+        "millimetre" | "millimeter" | "mm" | "millimetres per year" | "mm/year" => 1e-3,
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "GeoTIFF: Unknown unit '{unit}'"
+            )))
+        }
+    })
+}
+
+// Extract the items of the GDAL metadata XML, as (key, sample, value).
+// The key is the role of the item, if given, otherwise its lower cased name
+fn metadata_items(xml: &str) -> Vec<(String, Option<usize>, String)> {
+    let attribute = |attributes: &str, key: &str| {
+        let (_, rest) = attributes.split_once(&format!(" {key}=\""))?;
+        rest.split('"').next().map(|value| value.to_string())
+    };
+
+    let mut items = Vec::new();
+    for item in xml.split("<Item").skip(1) {
+        let Some((attributes, rest)) = item.split_once('>') else {
+            continue;
+        };
+        let value = rest.split("</Item>").next().unwrap_or_default().trim();
+        let Some(key) = attribute(attributes, "role").or_else(|| attribute(attributes, "name"))
+        else {
+            continue;
+        };
+        let sample = attribute(attributes, "sample").and_then(|s| s.parse().ok());
+        items.push((key.to_lowercase(), sample, value.to_string()));
+    }
+    items
+}
+
+// ----- Georeferencing ----------------------------------------------------------------
+
+// Determine the grid header: [lat_n, lat_s, lon_w, lon_e, dlat, dlon, bands],
+// where `bands` is left for the caller to fill in
+fn georeference(ifd: &Ifd, width: usize, height: usize) -> Result<[f64; 7], Error> {
+    let scale = ifd.reals(MODEL_PIXEL_SCALE).unwrap_or_default();
+    let tiepoint = ifd.reals(MODEL_TIEPOINT).unwrap_or_default();
+    if scale.len() < 2 || tiepoint.len() < 6 {
+        if ifd.reals(MODEL_TRANSFORMATION).is_some() {
+            return Err(Error::Unsupported(
+                "GeoTIFF: Georeference by transformation matrix".to_string(),
+            ));
+        }
+        return Err(Error::Invalid("GeoTIFF: Missing georeference".to_string()));
+    }
+
+    // The GeoKey directory is a header of 4 values, followed by a number
+    // of (key, location, count, value) quadruples
+    let keys = ifd.integers(GEO_KEY_DIRECTORY).unwrap_or_default();
+    let key = |id: u64| {
+        keys.chunks_exact(4)
+            .skip(1)
+            .find(|k| k[0] == id && k[1] == 0)
+            .map(|k| k[3])
+    };
+
+    // The tie point refers to the corner of the pixel, unless the
+    // GTRasterTypeGeoKey tells it refers to the center (PixelIsPoint)
+    let half = if key(1025) == Some(2) { 0. } else { 0.5 };
+    let (dlon, dlat) = (scale[0], scale[1]);
+    let lon_w = tiepoint[3] + (half - tiepoint[0]) * dlon;
+    let lat_n = tiepoint[4] - (half - tiepoint[1]) * dlat;
+    let lon_e = lon_w + (width - 1) as f64 * dlon;
+    let lat_s = lat_n - (height - 1) as f64 * dlat;
+    let mut header = [lat_n, lat_s, lon_w, lon_e, dlat, dlon, 0.];
+
+    // Grids in projected coordinates would need the projection for the
+    // lookup, so we support only those with a georeference in degrees
+    if key(1024) == Some(1) {
+        return Err(Error::Unsupported("GeoTIFF: Projected grids".to_string()));
+    }
+    for h in header.iter_mut().take(6) {
+        *h = h.to_radians();
+    }
+    Ok(header)
+}
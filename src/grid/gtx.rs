@@ -0,0 +1,99 @@
+use super::*;
+
+// The GTX header: lat_s, lon_w, dlat, dlon as f64, followed by rows, cols as i32
+const HEADER_SIZE: usize = 40;
+
+// GTX grids mark missing values by this (somewhat arbitrary) value
+const NODATA: f32 = -88.8888;
+
+impl BaseGrid {
+    /// Read a geoid grid in the NOAA VDatum GTX format.
+    ///
+    /// A GTX file is a 40 byte header, followed by the grid values in
+    /// metres, from south to north, and west to east. Everything is
+    /// big endian. Missing values are marked as NaN.
+    pub fn gtx(buf: &[u8]) -> Result<Self, Error> {
+        let (header, grid) = gtx_grid_reader(buf)?;
+        BaseGrid::plain(&header, Some(&grid), None)
+    }
+}
+
+fn gtx_grid_reader(buf: &[u8]) -> Result<([f64; 7], Vec<f32>), Error> {
+    if buf.len() < HEADER_SIZE {
+        return Err(Error::Invalid("GTX: Incomplete header".to_string()));
+    }
+    let f64_at = |i: usize| f64::from_be_bytes(buf[i..i + 8].try_into().unwrap());
+    let i32_at = |i: usize| i32::from_be_bytes(buf[i..i + 4].try_into().unwrap());
+
+    let (lat_s, mut lon_w, dlat, dlon) = (f64_at(0), f64_at(8), f64_at(16), f64_at(24));
+    let (rows, cols) = (i32_at(32), i32_at(36));
+    if rows < 2 || cols < 2 || dlat.is_nan() || dlon.is_nan() || dlat <= 0. || dlon <= 0. {
+        return Err(Error::Invalid("GTX: Bad header".to_string()));
+    }
+
+    let (rows, cols) = (rows as usize, cols as usize);
+    let Some(size) = rows
+        .checked_mul(cols)
+        .filter(|n| n.checked_mul(4).is_some())
+    else {
+        return Err(Error::Invalid("GTX: Grid size too large".to_string()));
+    };
+    if buf.len() - HEADER_SIZE != 4 * size {
+        return Err(Error::Invalid(
+            "GTX: Grid size does not match header".to_string(),
+        ));
+    }
+
+    // Some GTX grids use longitudes in the range [0; 360]
+    if lon_w >= 180. {
+        lon_w -= 360.;
+    }
+
+    // The GTX scan order is south to north, so we reverse the row order
+    let mut grid = Vec::with_capacity(size);
+    for row in (0..rows).rev() {
+        for col in 0..cols {
+            let i = HEADER_SIZE + 4 * (row * cols + col);
+            let value = f32::from_be_bytes(buf[i..i + 4].try_into().unwrap());
+            grid.push(if value == NODATA { f32::NAN } else { value });
+        }
+    }
+
+    let lat_n = lat_s + (rows - 1) as f64 * dlat;
+    let lon_e = lon_w + (cols - 1) as f64 * dlon;
+    let mut header = [lat_n, lat_s, lon_w, lon_e, dlat, dlon, 1.];
+    for h in header.iter_mut().take(6) {
+        *h = h.to_radians();
+    }
+    Ok((header, grid))
+}
+
+// ----- T E S T S ------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gtx() -> Result<(), Error> {
+        let grid = BaseGrid::gtx(&std::fs::read("geodesy/gtx/test.gtx")?)?;
+        let gravsoft = BaseGrid::gravsoft(&std::fs::read("geodesy/geoid/test.geoid")?)?;
+        assert_eq!(grid.bands, 1);
+
+        for (lat, lon) in [(54., 8.), (55.5, 12.25), (57.9, 8.1), (56.1, 9.9)] {
+            let c = Coor4D::geo(lat, lon, 0., 0.);
+            assert_eq!(grid.at(&c, 0.), gravsoft.at(&c, 0.));
+        }
+
+        // The north eastern corner node is missing
+        assert!(grid.at(&Coor4D::geo(57.5, 15.5, 0., 0.), 0.).is_none());
+
+        assert!(BaseGrid::gtx(&[0; 39]).is_err());
+
+        // A header claiming a huge grid is refused, rather than overflowing
+        let mut buf = std::fs::read("geodesy/gtx/test.gtx")?;
+        buf[32..40].copy_from_slice(&[0x7f, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff]);
+        assert!(matches!(BaseGrid::gtx(&buf), Err(Error::Invalid(_))));
+        Ok(())
+    }
+}
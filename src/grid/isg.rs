@@ -0,0 +1,229 @@
+use super::*;
+use std::collections::BTreeMap;
+
+impl BaseGrid {
+    /// Read a grid in the ISG format of the International Service for the
+    /// Geoid, versions 1.0 and 2.0.
+    ///
+    /// The header declares the units of the coordinates (`deg` or `dms`)
+    /// and of the values, and whether the values refer to the grid nodes,
+    /// or to the center of the grid cells. Missing values, as given by the
+    /// `nodata` header item, are marked as NaN. Projected grids are not
+    /// supported.
+    pub fn isg(buf: &[u8]) -> Result<Self, Error> {
+        let (header, grid) = isg_grid_reader(buf)?;
+        BaseGrid::plain(&header, Some(&grid), None)
+    }
+}
+
+fn isg_grid_reader(buf: &[u8]) -> Result<([f64; 7], Vec<f32>), Error> {
+    let text = std::str::from_utf8(buf)?;
+    let invalid = |message: &str| Error::Invalid(format!("ISG: {message}"));
+
+    // Everything before "begin_of_head" is comments
+    let Some((_, text)) = text.split_once("begin_of_head") else {
+        return Err(invalid("Missing header"));
+    };
+    let Some((head, body)) = text.split_once("end_of_head") else {
+        return Err(invalid("Incomplete header"));
+    };
+
+    // Header items are given as "key : value" (ISG 2.0), or "key = value"
+    let mut items = BTreeMap::new();
+    for line in head.lines().skip(1) {
+        if let Some((key, value)) = line.split_once([':', '=']) {
+            items.insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let item = |key: &str| items.get(key).map(|s| s.as_str()).unwrap_or_default();
+
+    if !item("data format").is_empty() && item("data format") != "grid" {
+        return Err(Error::Unsupported(
+            "ISG: Only gridded data supported".to_string(),
+        ));
+    }
+    if !item("data ordering").is_empty() && item("data ordering") != "N-to-S, W-to-E" {
+        return Err(Error::Unsupported(format!(
+            "ISG: Data ordering '{}'",
+            item("data ordering")
+        )));
+    }
+
+    if item("coord type") == "projected" {
+        return Err(Error::Unsupported("ISG: Projected grids".to_string()));
+    }
+    let keys = [
+        "lat min",
+        "lat max",
+        "lon min",
+        "lon max",
+        "delta lat",
+        "delta lon",
+    ];
+    let dms = item("coord units") == "dms";
+    let mut extent = [0.; 6];
+    for (value, key) in extent.iter_mut().zip(keys) {
+        *value = if dms {
+            parse_dms(item(key))
+        } else {
+            item(key).parse().ok()
+        }
+        .ok_or_else(|| invalid(&format!("Bad value for '{key}'")))?;
+    }
+    let [mut lat_s, mut lat_n, mut lon_w, mut lon_e, dlat, dlon] = extent;
+    if !dlat.is_finite() || !dlon.is_finite() || dlat <= 0. || dlon <= 0. {
+        return Err(invalid("Bad grid spacing"));
+    }
+
+    let rows: usize = item("nrows").parse().map_err(|_| invalid("Bad 'nrows'"))?;
+    let cols: usize = item("ncols").parse().map_err(|_| invalid("Bad 'ncols'"))?;
+    let size = rows
+        .checked_mul(cols)
+        .ok_or_else(|| invalid("Grid size too large"))?;
+    let nodata: Option<f64> = item("nodata").parse().ok();
+    let factor = match item("data units") {
+        "" | "meters" | "metres" | "m" => 1.,
+        "centimeters" | "centimetres" | "cm" => 0.01,
+        "millimeters" | "millimetres" | "mm" => 0.001,
+        unit => return Err(Error::Unsupported(format!("ISG: Data units '{unit}'"))),
+    };
+
+    // The extent is either that of the grid nodes, or that of the grid
+    // cells, in which case the nodes are the cell centers
+    let node_rows = ((lat_n - lat_s) / dlat + 0.5).floor() as usize;
+    let node_cols = ((lon_e - lon_w) / dlon + 0.5).floor() as usize;
+    if (node_rows, node_cols) == (rows, cols) {
+        lat_s += dlat / 2.;
+        lat_n -= dlat / 2.;
+        lon_w += dlon / 2.;
+        lon_e -= dlon / 2.;
+    } else if (node_rows.checked_add(1), node_cols.checked_add(1)) != (Some(rows), Some(cols)) {
+        return Err(invalid("Grid size does not match header"));
+    }
+
+    let mut grid = Vec::with_capacity(size);
+    for item in body
+        .lines()
+        .skip(1)
+        .flat_map(|line| line.split_whitespace())
+    {
+        let value: f64 = item.parse().map_err(|_| invalid("Bad grid value"))?;
+        grid.push(if Some(value) == nodata {
+            f32::NAN
+        } else {
+            (value * factor) as f32
+        });
+    }
+    if grid.len() != size {
+        return Err(invalid("Grid size does not match header"));
+    }
+
+    let mut header = [lat_n, lat_s, lon_w, lon_e, dlat, dlon, 1.];
+    for h in header.iter_mut().take(6) {
+        *h = h.to_radians();
+    }
+    Ok((header, grid))
+}
+
+// Parse an angle given as degrees, minutes and seconds, e.g. 45°30'00"
+fn parse_dms(value: &str) -> Option<f64> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1., value),
+        None => (1., value),
+    };
+    let mut parts = value.split(['°', '\'', '"']).map(str::trim);
+    let d: f64 = parts.next()?.parse().ok()?;
+    let m: f64 = parts.next().unwrap_or("0").parse().ok()?;
+    let s: f64 = parts
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("0")
+        .parse()
+        .ok()?;
+    Some(sign * (d + m / 60. + s / 3600.))
+}
+
+// ----- T E S T S ------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Node registered, decimal degrees, ISG 1.0
+    const ISG_1: &str = "
+        begin_of_head ================================================
+        lat min        =    54.000000
+        lat max        =    58.000000
+        lon min        =     8.000000
+        lon max        =    16.000000
+        delta lat      =     2.000000
+        delta lon      =     4.000000
+        nrows          =            3
+        ncols          =            3
+        nodata         =   -9999.0000
+        ISG format     =          1.0
+        end_of_head ==================================================
+           5800.0000 5812.0000 5816.0000
+           5600.0000 5612.0000 5616.0000
+           5400.0000 5412.0000 5416.0000
+    ";
+
+    #[test]
+    fn isg() -> Result<(), Error> {
+        // Cell registered, degrees-minutes-seconds, ISG 2.0
+        let grid = BaseGrid::isg(&std::fs::read("geodesy/isg/test.isg")?)?;
+        let gravsoft = BaseGrid::gravsoft(&std::fs::read("geodesy/geoid/test.geoid")?)?;
+        assert_eq!(grid.bands, 1);
+
+        for (lat, lon) in [(54., 8.), (55.5, 12.25), (57.9, 8.1), (56.1, 9.9)] {
+            let c = Coor4D::geo(lat, lon, 0., 0.);
+            let (n, m) = (grid.at(&c, 0.).unwrap(), gravsoft.at(&c, 0.).unwrap());
+            assert!((n[0] - m[0]).abs() < 1e-5);
+        }
+
+        // The north eastern corner node is missing
+        assert!(grid.at(&Coor4D::geo(57.5, 15.5, 0., 0.), 0.).is_none());
+
+        // Node registered, in centimetres
+        let isg_1 = ISG_1.replace("ISG format", "data units : centimeters\nISG format");
+        let grid = BaseGrid::isg(isg_1.as_bytes())?;
+        let n = grid.at(&Coor4D::geo(55., 14., 0., 0.), 0.).unwrap();
+        assert!((n[0] - 55.14).abs() < 1e-5);
+
+        // Bad grid size, and missing header
+        let isg_1 = ISG_1.replace("nrows          =            3", "nrows = 4");
+        assert!(BaseGrid::isg(isg_1.as_bytes()).is_err());
+        assert!(BaseGrid::isg(b"54 58 8 16 1 1").is_err());
+
+        // Zero and negative grid spacing
+        let isg_1 = ISG_1.replace("2.000000", "0.000000");
+        assert!(matches!(
+            BaseGrid::isg(isg_1.as_bytes()),
+            Err(Error::Invalid(_))
+        ));
+        let isg_1 = ISG_1.replace("=     4.000000", "=    -4.000000");
+        assert!(matches!(
+            BaseGrid::isg(isg_1.as_bytes()),
+            Err(Error::Invalid(_))
+        ));
+
+        // Projected grids, and sizes beyond what we can hold
+        let isg_1 = ISG_1.replace("ISG format", "coord type : projected\nISG format");
+        assert!(matches!(
+            BaseGrid::isg(isg_1.as_bytes()),
+            Err(Error::Unsupported(_))
+        ));
+        let isg_1 = ISG_1.replace(
+            "ncols          =            3",
+            "ncols = 18446744073709551615",
+        );
+        assert!(matches!(
+            BaseGrid::isg(isg_1.as_bytes()),
+            Err(Error::Invalid(_))
+        ));
+
+        assert_eq!(parse_dms("-1°30'00\""), Some(-1.5));
+        assert_eq!(parse_dms("0°00'36\""), Some(0.01));
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
 //! Grid characteristics and interpolation.
 
+mod byn;
+pub mod geotiff;
+mod gtx;
+mod isg;
 pub mod ntv2;
 use crate::prelude::*;
 use std::{fmt::Debug, io::BufRead};
@@ -22,7 +26,7 @@ pub trait Grid: Debug + Sync + Send {
 /// provided externally (presumably by a [Context](crate::Context)).
 ///
 /// In principle grid format agnostic, but includes a parser for
-/// geodetic grids in the Gravsoft format.
+/// geodetic grids in the Gravsoft, GTX, BYN and ISG formats.
 #[derive(Debug, Default, Clone)]
 pub struct BaseGrid {
     lat_n: f64, // Latitude of the first (typically northernmost) row of the grid
@@ -148,6 +152,10 @@ impl Grid for BaseGrid {
             result[i] = (1. - rlon) * left[i] + rlon * right[i];
         }
 
+        // Nodes with missing values are treated as outside of the grid
+        if result.0.iter().any(|v| v.is_nan()) {
+            return None;
+        }
         Some(result)
     }
 }
@@ -398,6 +406,30 @@ mod tests {
         assert!((n[0] - (58.75 + 0.0825)).abs() < 0.0001);
         Ok(())
     }
+
+    #[test]
+    fn nodata() -> Result<(), Error> {
+        // A geoid grid with a missing value at the (57N, 10E) node
+        let mut header = Vec::from(HEADER);
+        header.push(1.);
+        let mut grid = Vec::from(GEOID);
+        grid[9 + 2] = f32::NAN;
+        let geoid = BaseGrid::plain(&header, Some(&grid), None)?;
+
+        // The four cells around the missing node are treated as outside of the grid
+        for (lat, lon) in [(57.5, 9.5), (57.5, 10.5), (56.5, 9.5), (56.5, 10.5)] {
+            let c = Coor4D::raw(lon, lat, 0., 0.);
+            assert!(geoid.contains(&c, 0.));
+            assert!(geoid.at(&c, 0.).is_none());
+        }
+
+        // While the neighbouring cells are not affected
+        let n = geoid.at(&Coor4D::raw(11.5, 57.5, 0., 0.), 0.).unwrap();
+        assert!((n[0] - (57.5 + 0.115)).abs() < 0.0001);
+        let n = geoid.at(&Coor4D::raw(9.5, 55.5, 0., 0.), 0.).unwrap();
+        assert!((n[0] - (55.5 + 0.095)).abs() < 0.0001);
+        Ok(())
+    }
 }
 
 // Additional tests for Grid in src/inner_op/gridshift.rs
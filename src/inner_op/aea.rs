@@ -0,0 +1,320 @@
+//! Albers Equal Area Conic
+use crate::authoring::*;
+use std::f64::consts::FRAC_PI_2;
+
+const EPS10: f64 = 1e-10;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+// Forward Albers equal area conic, following Snyder (1987), pp. 101-102,
+// and the IOGP Guidance Note 7-2, method 9822
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0);
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(n) = op.params.real("n") else { return 0 };
+    let Ok(c) = op.params.real("c") else { return 0 };
+    let Ok(rho0) = op.params.real("rho0") else {
+        return 0;
+    };
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0] - lon_0;
+        let q = ancillary::qs(coord[1].sin(), e);
+
+        // Snyder (1987) eq. 14-12
+        let rho_squared = c - n * q;
+        if rho_squared < 0. {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let rho = rho_squared.sqrt() / n;
+
+        let sc = (lam * n).sin_cos();
+        coord[0] = a * rho * sc.0 + x_0;
+        coord[1] = a * (rho0 - rho * sc.1) + y_0;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0);
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(n) = op.params.real("n") else { return 0 };
+    let Ok(c) = op.params.real("c") else { return 0 };
+    let Ok(rho0) = op.params.real("rho0") else {
+        return 0;
+    };
+    let Ok(authalic) = op.params.fourier_coefficients("authalic") else {
+        return 0;
+    };
+
+    // q at the pole - the normalizing factor for the authalic latitude
+    let qp = ancillary::qs(1., e);
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let mut x = (coord[0] - x_0) / a;
+        let mut y = rho0 - (coord[1] - y_0) / a;
+
+        // Standard parallels on the southern hemisphere?
+        if n < 0. {
+            x = -x;
+            y = -y;
+        }
+        let rho = x.hypot(y);
+
+        // Snyder (1987) eq. 14-19
+        let q = (c - rho * rho * n * n) / n;
+
+        // The authalic latitude, taking care of q slightly beyond the poles,
+        // due to numerical noise
+        let ratio = q / qp;
+        if ratio.abs() > 1. + EPS10 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let xi = ratio.clamp(-1., 1.).asin();
+
+        coord[0] = angular::normalize_symmetric(x.atan2(y) / n + lon_0);
+        coord[1] = ellps.latitude_authalic_to_geographic(xi, &authalic);
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 8] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_1", default: Some(0_f64) },
+    OpParameter::Real { key: "lat_2", default: Some(f64::NAN) },
+    OpParameter::Real { key: "lat_0", default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    let phi0 = params.lat(0).to_radians();
+    let phi1 = params.lat(1).to_radians();
+    let mut phi2 = params.lat(2).to_radians();
+
+    // One standard parallel?
+    if phi2.is_nan() {
+        phi2 = phi1;
+    }
+    params
+        .real
+        .insert("lon_0", params.real["lon_0"].to_radians());
+
+    if (phi1 + phi2).abs() < EPS10 {
+        return Err(Error::General(
+            "Aea: Invalid value for lat_1 and lat_2: |lat_1 + lat_2| should be > 0",
+        ));
+    }
+    if phi1.abs() > FRAC_PI_2 || phi2.abs() > FRAC_PI_2 || phi0.abs() > FRAC_PI_2 {
+        return Err(Error::General(
+            "Aea: Invalid value for lat_0, lat_1 or lat_2: should be within [-90°; 90°]",
+        ));
+    }
+
+    let ellps = params.ellps(0);
+    let e = ellps.eccentricity();
+    let es = ellps.eccentricity_squared();
+
+    // Snyder (1987) eqs. 14-15 and 3-12
+    let sc = phi1.sin_cos();
+    let m1 = ancillary::pj_msfn(sc, es);
+    let q1 = ancillary::qs(sc.0, e);
+
+    // Snyder (1987) eq. 14-14, with the one-parallel case as the limit
+    let mut n = sc.0;
+    if (phi1 - phi2).abs() >= EPS10 {
+        let sc = phi2.sin_cos();
+        let m2 = ancillary::pj_msfn(sc, es);
+        let q2 = ancillary::qs(sc.0, e);
+        n = (m1 * m1 - m2 * m2) / (q2 - q1);
+    }
+    if n.abs() < EPS10 {
+        return Err(Error::General("Aea: Invalid value for lat_1 and lat_2"));
+    }
+
+    // Snyder (1987) eqs. 14-13 and 14-12a
+    let c = m1 * m1 + n * q1;
+    let rho0 = (c - n * ancillary::qs(phi0.sin(), e)).sqrt() / n;
+
+    params.real.insert("c", c);
+    params.real.insert("n", n);
+    params.real.insert("rho0", rho0);
+
+    let authalic = ellps.coefficients_for_authalic_latitude_computations();
+    params.fourier_coefficients.insert("authalic", authalic);
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn two_standard_parallels() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // The worked example for the ellipsoidal case from Snyder (1987),
+        // pp. 292-294: Conterminous US Albers, on the Clarke 1866 ellipsoid
+        let definition = "aea lat_1=29.5 lat_2=45.5 lat_0=23 lon_0=-96 ellps=clrk66";
+        let op = ctx.op(definition)?;
+
+        let geo = [Coor4D::geo(35., -75., 0., 0.)];
+        let projected = [Coor4D::raw(1_885_472.7, 1_535_925.0, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.1);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+
+        // The origin maps to the false origin
+        let op = ctx.op("aea lat_1=29.5 lat_2=45.5 lat_0=23 lon_0=-96 x_0=1000 y_0=2000")?;
+        let mut operands = [Coor4D::geo(23., -96., 0., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][0], 1000., abs <= 1e-9);
+        assert_float_eq!(operands[0][1], 2000., abs <= 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn iogp() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // NAD83 / Conus Albers (EPSG:5070), checked against an independent
+        // evaluation of the formulas for EPSG method 9822, as given in the
+        // IOGP Guidance Note 7-2, rather than those of Snyder (1987)
+        let op = ctx.op("aea lat_1=29.5 lat_2=45.5 lat_0=23 lon_0=-96 x_0=1000 y_0=2000")?;
+        let ellps = Ellipsoid::named("GRS80")?;
+        let (a, e) = (ellps.semimajor_axis(), ellps.eccentricity());
+        let es = e * e;
+        let (ef, nf, lon_0) = (1000., 2000., -96_f64.to_radians());
+
+        let m = |phi: f64| phi.cos() / (1. - es * phi.sin().powi(2)).sqrt();
+        let alpha = |phi: f64| {
+            let s = phi.sin();
+            (1. - es) * (s / (1. - es * s * s) - ((1. - e * s) / (1. + e * s)).ln() / (2. * e))
+        };
+        let (phi_0, phi_1, phi_2) = (
+            23_f64.to_radians(),
+            29.5_f64.to_radians(),
+            45.5_f64.to_radians(),
+        );
+        let n = (m(phi_1).powi(2) - m(phi_2).powi(2)) / (alpha(phi_2) - alpha(phi_1));
+        let c = m(phi_1).powi(2) + n * alpha(phi_1);
+        let rho_0 = a * (c - n * alpha(phi_0)).sqrt() / n;
+
+        for (lat, lon) in [(35_f64, -75_f64), (50., -125.), (24., -80.)] {
+            // Forward
+            let (phi, lam) = (lat.to_radians(), lon.to_radians());
+            let theta = n * (lam - lon_0);
+            let rho = a * (c - n * alpha(phi)).sqrt() / n;
+            let easting = ef + rho * theta.sin();
+            let northing = nf + rho_0 - rho * theta.cos();
+
+            let mut operands = [Coor4D::geo(lat, lon, 0., 0.)];
+            ctx.apply(op, Fwd, &mut operands)?;
+            assert_float_eq!(operands[0][0], easting, abs <= 1e-6);
+            assert_float_eq!(operands[0][1], northing, abs <= 1e-6);
+
+            // Inverse
+            let (de, dn) = (easting - ef, rho_0 - (northing - nf));
+            let rho = de.hypot(dn);
+            let alpha = (c - rho * rho * n * n / (a * a)) / n;
+            let beta = (alpha / (1. - (1. - es) / (2. * e) * ((1. - e) / (1. + e)).ln())).asin();
+            let phi = beta
+                + (es / 3. + 31. * es * es / 180. + 517. * es.powi(3) / 5040.) * (2. * beta).sin()
+                + (23. * es * es / 360. + 251. * es.powi(3) / 3780.) * (4. * beta).sin()
+                + (761. * es.powi(3) / 45360.) * (6. * beta).sin();
+            let lam = lon_0 + (de / dn).atan() / n;
+
+            ctx.apply(op, Inv, &mut operands)?;
+            // The IOGP latitude series is truncated after the e⁶ terms
+            assert_float_eq!(operands[0][0], lam, abs <= 1e-12);
+            assert_float_eq!(operands[0][1], phi, abs <= 1e-9);
+            assert_float_eq!(operands[0][0], lon.to_radians(), abs <= 1e-12);
+            assert_float_eq!(operands[0][1], lat.to_radians(), abs <= 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn one_standard_parallel() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("aea lat_1=57 lon_0=12")?;
+
+        // The scale is true along the standard parallel, so a small step
+        // along the parallel must match the length of the corresponding arc
+        let ellps = Ellipsoid::named("GRS80")?;
+        let dlon = 0.001_f64;
+        let arc = ellps.prime_vertical_radius_of_curvature(57_f64.to_radians())
+            * 57_f64.to_radians().cos()
+            * dlon.to_radians();
+        let mut operands = [Coor4D::geo(57., 12. + dlon, 0., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][0], arc, abs <= 1e-6);
+
+        // Roundtrip, including the southern hemisphere
+        let op = ctx.op("aea lat_1=-40 lat_2=-20 lon_0=130 x_0=500000")?;
+        let geo = [
+            Coor4D::geo(-25., 120., 0., 0.),
+            Coor4D::geo(-35., 145., 0., 0.),
+            Coor4D::geo(-89.9, 0., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+
+        // Bad parameters
+        assert!(ctx.op("aea lat_1=30 lat_2=-30").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! Azimuthal Equidistant
+//!
+//! In the general ellipsoidal case, the projected coordinates are simply the
+//! polar coordinates (azimuth, distance) of the geodesic from the projection
+//! center, so the forward and inverse projections are handled by the geodesic
+//! solvers of [Ellipsoid]. On the sphere (i.e. with zero flattening), the
+//! closed form expressions from Snyder (1987), pp. 195-197 are used.
+//!
+//! With the `guam` flag, the operator implements the *Guam projection*
+//! (EPSG method 9831), a series approximation to the ellipsoidal case,
+//! valid over small areas only.
+use crate::authoring::*;
+use std::f64::consts::PI;
+
+const EPS10: f64 = 1e-10;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let lat_0 = op.params.lat(0).to_radians();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+    let guam = op.params.boolean("guam");
+    let spherical = ellps.flattening() == 0.;
+    let (sin_lat_0, cos_lat_0) = lat_0.sin_cos();
+    let origin = Coor4D::raw(lon_0, lat_0, 0., 0.);
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0] - lon_0;
+        let phi = coord[1];
+        let (sin_phi, cos_phi) = phi.sin_cos();
+
+        // The Guam projection, IOGP, 2019, method 9831
+        if guam {
+            let nu = ellps.prime_vertical_radius_of_curvature(phi);
+            coord[0] = x_0 + nu * lam * cos_phi;
+            coord[1] = y_0 + ellps.meridian_latitude_to_distance(phi) - m_0
+                + nu * lam * lam * cos_phi * sin_phi / 2.;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // The spherical case
+        if spherical {
+            let (sin_lam, cos_lam) = lam.sin_cos();
+            let cos_c = sin_lat_0 * sin_phi + cos_lat_0 * cos_phi * cos_lam;
+
+            // The antipode of the center maps to a circle, hence has no unique image
+            if cos_c + 1. < EPS10 {
+                operands.set_coord(i, &Coor4D::nan());
+                continue;
+            }
+            let c = cos_c.clamp(-1., 1.).acos();
+            let k = if c < EPS10 { 1. } else { c / c.sin() };
+
+            coord[0] = x_0 + a * k * cos_phi * sin_lam;
+            coord[1] = y_0 + a * k * (cos_lat_0 * sin_phi - sin_lat_0 * cos_phi * cos_lam);
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // The general ellipsoidal case: azimuth and distance from the center
+        let geodesic = ellps.geodesic_inv(&origin, &Coor4D::raw(coord[0], phi, 0., 0.));
+        let (azimuth, distance) = (geodesic[0], geodesic[2]);
+
+        // Vincenty's method does not converge near the antipode of the center
+        if !distance.is_finite() || geodesic[3] >= 1000. {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let (sin_az, cos_az) = azimuth.sin_cos();
+        coord[0] = x_0 + distance * sin_az;
+        coord[1] = y_0 + distance * cos_az;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let e = ellps.eccentricity();
+    let lat_0 = op.params.lat(0).to_radians();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+    let guam = op.params.boolean("guam");
+    let spherical = ellps.flattening() == 0.;
+    let (sin_lat_0, cos_lat_0) = lat_0.sin_cos();
+    let origin = Coor4D::raw(lon_0, lat_0, 0., 0.);
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let x = coord[0] - x_0;
+        let y = coord[1] - y_0;
+
+        // The Guam projection: Three fixed point iterations suffice, cf. IOGP
+        if guam {
+            let mut phi = lat_0;
+            let mut w = 1.;
+            for _ in 0..3 {
+                let t = e * phi.sin();
+                w = (1. - t * t).sqrt();
+                phi =
+                    ellps.meridian_distance_to_latitude(m_0 + y - x * x * phi.tan() * w / (2. * a));
+            }
+            coord[0] = angular::normalize_symmetric(lon_0 + x * w / (a * phi.cos()));
+            coord[1] = phi;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        let rho = x.hypot(y);
+
+        // At the center
+        if rho < EPS10 {
+            coord[0] = lon_0;
+            coord[1] = lat_0;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // The spherical case
+        if spherical {
+            let c = rho / a;
+            if c > PI {
+                operands.set_coord(i, &Coor4D::nan());
+                continue;
+            }
+            let (sin_c, cos_c) = c.sin_cos();
+            let lat = (cos_c * sin_lat_0 + y * sin_c * cos_lat_0 / rho).asin();
+            let lon = (x * sin_c).atan2(rho * cos_lat_0 * cos_c - y * sin_lat_0 * sin_c);
+            coord[0] = angular::normalize_symmetric(lon_0 + lon);
+            coord[1] = lat;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // The general ellipsoidal case
+        let destination = ellps.geodesic_fwd(&origin, x.atan2(y), rho);
+        if !destination[0].is_finite() || !destination[1].is_finite() {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        coord[0] = angular::normalize_symmetric(destination[0]);
+        coord[1] = destination[1];
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 7] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Flag { key: "guam" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_0", default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    let ellps = params.ellps(0);
+
+    let lat_0 = params.lat(0);
+    if lat_0.abs() > 90. {
+        return Err(Error::BadParam("lat_0".to_string(), def.clone()));
+    }
+
+    // The meridian distance from the equator to the latitude of origin,
+    // needed for the Guam projection
+    let m_0 = ellps.meridian_latitude_to_distance(lat_0.to_radians());
+    params.real.insert("m_0", m_0);
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn ellipsoidal() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("aeqd lat_0=55 lon_0=12 x_0=100000 y_0=200000")?;
+
+        // Copenhagen--Paris: Distance and azimuth from Karney's GeodSolve,
+        // cf. the tests in ellipsoid/geodesics.rs
+        let geo = [
+            Coor4D::geo(49., 2., 0., 0.),
+            Coor4D::geo(55., 12., 0., 0.),
+            Coor4D::geo(-33.9, 151.2, 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+
+        let (x, y) = (operands[0][0] - 100_000., operands[0][1] - 200_000.);
+        assert_float_eq!(x.hypot(y), 956_066.231_959, abs <= 1e-5);
+        assert_float_eq!(x.atan2(y).to_degrees(), -130.154_060_420_72, abs <= 1e-9);
+        assert_float_eq!(operands[1][0], 100_000., abs <= 1e-9);
+        assert_float_eq!(operands[1][1], 200_000., abs <= 1e-9);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+
+        // Near the antipode of the center, the geodesic computation does
+        // not converge, so the point is flagged as a failure
+        let mut operands = [Coor4D::geo(-55., -167.9, 0., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn spherical() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("aeqd lat_0=90 ellps=sphere")?;
+
+        // On the sphere, with the center at the North Pole, the distance
+        // to the equator is a quarter of a great circle
+        let r = 6_370_997.;
+        let geo = [
+            Coor4D::geo(0., 0., 0., 0.),
+            Coor4D::geo(0., 90., 0., 0.),
+            Coor4D::geo(-45., -135., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][0], 0., abs <= 1e-9);
+        assert_float_eq!(operands[0][1], -r * PI / 2., abs <= 1e-6);
+        assert_float_eq!(operands[1][0], r * PI / 2., abs <= 1e-6);
+        assert_float_eq!(operands[1][1], 0., abs <= 1e-6);
+        assert_float_eq!(
+            operands[2].0[0].hypot(operands[2][1]),
+            r * PI * 0.75,
+            abs <= 1e-6
+        );
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+
+        // The antipode of the center has no unique image
+        let mut operands = [Coor4D::geo(-90., 0., 0., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn guam() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:3993 Guam 1963 / Yap Islands. The IOGP example for method 9831
+        let lat_0 = angular::dms_to_dd(9, 32, 48.15);
+        let lon_0 = angular::dms_to_dd(138, 10, 7.48);
+        let definition =
+            format!("aeqd guam lat_0={lat_0} lon_0={lon_0} x_0=40000 y_0=60000 ellps=clrk66");
+        let op = ctx.op(&definition)?;
+
+        let lat = angular::dms_to_dd(9, 35, 47.493);
+        let lon = angular::dms_to_dd(138, 11, 34.908);
+        let geo = [Coor4D::geo(lat, lon, 0., 0.)];
+        let projected = [Coor4D::raw(42_665.90, 65_509.82, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.01);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+//! Affine transformation in up to 4 dimensions, following the PROJ operator of
+//! the same name:
+//!
+//! ```txt
+//! | x' |   | xoff |   | s11 s12 s13  0     |   | x |
+//! | y' | = | yoff | + | s21 s22 s23  0     | * | y |
+//! | z' |   | zoff |   | s31 s32 s33  0     |   | z |
+//! | t' |   | toff |   |  0   0   0  tscale |   | t |
+//! ```
+//!
+//! The matrix elements may be given individually (`s11`, `s12`...), or compactly,
+//! as the row major 2x2 or 3x3 `matrix` series. The offsets may correspondingly
+//! be given as the 2, 3, or 4 element `offset` series.
+//!
+//! The inverse is computed from the matrix. Hence, the operator is
+//! non-invertible if the matrix is singular.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let Ok(o) = op.params.series("OFFSET") else {
+        return 0;
+    };
+    let Ok(m) = op.params.series("MATFLAT") else {
+        return 0;
+    };
+    let Ok(tscale) = op.params.real("tscale") else {
+        return 0;
+    };
+
+    let n = operands.len();
+    for i in 0..n {
+        let mut c = operands.get_coord(i);
+        let (x, y, z) = (c[0], c[1], c[2]);
+        c[0] = o[0] + m[0] * x + m[1] * y + m[2] * z;
+        c[1] = o[1] + m[3] * x + m[4] * y + m[5] * z;
+        c[2] = o[2] + m[6] * x + m[7] * y + m[8] * z;
+        c[3] = o[3] + tscale * c[3];
+        operands.set_coord(i, &c);
+    }
+    n
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let Ok(o) = op.params.series("OFFSET") else {
+        return 0;
+    };
+    let Ok(m) = op.params.series("INVFLAT") else {
+        return 0;
+    };
+    let Ok(tscale) = op.params.real("tscale") else {
+        return 0;
+    };
+
+    let n = operands.len();
+    for i in 0..n {
+        let mut c = operands.get_coord(i);
+        let (x, y, z) = (c[0] - o[0], c[1] - o[1], c[2] - o[2]);
+        c[0] = m[0] * x + m[1] * y + m[2] * z;
+        c[1] = m[3] * x + m[4] * y + m[5] * z;
+        c[2] = m[6] * x + m[7] * y + m[8] * z;
+        c[3] = (c[3] - o[3]) / tscale;
+        operands.set_coord(i, &c);
+    }
+    n
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+// The individual matrix elements, in row major order
+const ELEMENTS: [&str; 9] = [
+    "s11", "s12", "s13", "s21", "s22", "s23", "s31", "s32", "s33",
+];
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 17] = [
+    OpParameter::Flag   { key: "inv" },
+
+    // Offsets
+    OpParameter::Real   { key: "xoff",   default: Some(0_f64) },
+    OpParameter::Real   { key: "yoff",   default: Some(0_f64) },
+    OpParameter::Real   { key: "zoff",   default: Some(0_f64) },
+    OpParameter::Real   { key: "toff",   default: Some(0_f64) },
+    OpParameter::Series { key: "offset", default: Some("") },
+
+    // Matrix elements
+    OpParameter::Real   { key: "s11",    default: Some(1_f64) },
+    OpParameter::Real   { key: "s12",    default: Some(0_f64) },
+    OpParameter::Real   { key: "s13",    default: Some(0_f64) },
+    OpParameter::Real   { key: "s21",    default: Some(0_f64) },
+    OpParameter::Real   { key: "s22",    default: Some(1_f64) },
+    OpParameter::Real   { key: "s23",    default: Some(0_f64) },
+    OpParameter::Real   { key: "s31",    default: Some(0_f64) },
+    OpParameter::Real   { key: "s32",    default: Some(0_f64) },
+    OpParameter::Real   { key: "s33",    default: Some(1_f64) },
+    OpParameter::Series { key: "matrix", default: Some("") },
+    OpParameter::Real   { key: "tscale", default: Some(1_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    // The offsets, given individually or as a series
+    let mut offset = [
+        params.real("xoff")?,
+        params.real("yoff")?,
+        params.real("zoff")?,
+        params.real("toff")?,
+    ];
+    if let Ok(series) = params.series("offset") {
+        let individual = ["xoff", "yoff", "zoff", "toff"];
+        if individual.iter().any(|&key| params.given.contains_key(key)) {
+            return Err(Error::Invalid(
+                "affine: 'offset' cannot be combined with individual offsets".to_string(),
+            ));
+        }
+        if !(2..=4).contains(&series.len()) {
+            return Err(Error::BadParam("offset".to_string(), def.clone()));
+        }
+        offset[..series.len()].copy_from_slice(series);
+    }
+
+    // The matrix, given elementwise or as a 2x2 or 3x3 series
+    let mut matrix = [0.; 9];
+    for (i, key) in ELEMENTS.iter().enumerate() {
+        matrix[i] = params.real(key)?;
+    }
+    if let Ok(series) = params.series("matrix") {
+        if ELEMENTS.iter().any(|&key| params.given.contains_key(key)) {
+            return Err(Error::Invalid(
+                "affine: 'matrix' cannot be combined with individual matrix elements".to_string(),
+            ));
+        }
+        match series.len() {
+            4 => {
+                matrix[0] = series[0];
+                matrix[1] = series[1];
+                matrix[3] = series[2];
+                matrix[4] = series[3];
+            }
+            9 => matrix.copy_from_slice(series),
+            _ => return Err(Error::BadParam("matrix".to_string(), def.clone())),
+        }
+    }
+
+    params.series.insert("OFFSET", Vec::from(offset));
+    params.series.insert("MATFLAT", Vec::from(matrix));
+
+    // The inverse is only available for non-singular matrices
+    let tscale = params.real("tscale")?;
+    let inverse = invert(&matrix);
+    let inv = match inverse {
+        Some(inverse) if tscale != 0. => {
+            params.series.insert("INVFLAT", Vec::from(inverse));
+            Some(InnerOp(inv))
+        }
+        _ => None,
+    };
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), inv);
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// Invert a row major 3x3 matrix, using the adjugate and the determinant
+fn invert(m: &[f64; 9]) -> Option<[f64; 9]> {
+    // The cofactors of the first row
+    let c0 = m[4] * m[8] - m[5] * m[7];
+    let c1 = m[5] * m[6] - m[3] * m[8];
+    let c2 = m[3] * m[7] - m[4] * m[6];
+
+    let det = m[0] * c0 + m[1] * c1 + m[2] * c2;
+    if det == 0. || !det.is_finite() {
+        return None;
+    }
+
+    Some([
+        c0 / det,
+        (m[2] * m[7] - m[1] * m[8]) / det,
+        (m[1] * m[5] - m[2] * m[4]) / det,
+        c1 / det,
+        (m[0] * m[8] - m[2] * m[6]) / det,
+        (m[2] * m[3] - m[0] * m[5]) / det,
+        c2 / det,
+        (m[1] * m[6] - m[0] * m[7]) / det,
+        (m[0] * m[4] - m[1] * m[3]) / det,
+    ])
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn affine() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // Scale by 2, rotate 90 degrees, and offset, in 2D, plus a time offset
+        let op =
+            ctx.op("affine xoff=100 yoff=200 s11=0 s12=-2 s21=2 s22=0 toff=2000 tscale=0.5")?;
+        let mut operands = [Coor4D::raw(1., 2., 3., 10.), Coor4D::raw(-5., 0., 0., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 2);
+        assert_float_eq!(operands[0].0, [96., 202., 3., 2005.], abs_all <= 1e-12);
+        assert_float_eq!(operands[1].0, [100., 190., 0., 2000.], abs_all <= 1e-12);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 2);
+        assert_float_eq!(operands[0].0, [1., 2., 3., 10.], abs_all <= 1e-12);
+        assert_float_eq!(operands[1].0, [-5., 0., 0., 0.], abs_all <= 1e-12);
+
+        // The same, using the compact series form
+        let compact = ctx.op("affine offset=100,200,0,2000 matrix=0,-2,2,0 tscale=0.5")?;
+        let mut other = [Coor4D::raw(1., 2., 3., 10.)];
+        ctx.apply(compact, Fwd, &mut other)?;
+        assert_float_eq!(other[0].0, [96., 202., 3., 2005.], abs_all <= 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn three_dimensional() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("affine matrix=1,2,3,0,1,4,5,6,0 offset=1,2,3")?;
+
+        let mut operands = [Coor4D::raw(1., 1., 1., 0.), Coor4D::raw(3., -2., 7., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 2);
+        assert_float_eq!(operands[0].0, [7., 7., 14., 0.], abs_all <= 1e-12);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 2);
+        assert_float_eq!(operands[0].0, [1., 1., 1., 0.], abs_all <= 1e-12);
+        assert_float_eq!(operands[1].0, [3., -2., 7., 0.], abs_all <= 1e-12);
+
+        // The inverse of the inverse is the matrix itself
+        let m = [1., 2., 3., 0., 1., 4., 5., 6., 0.];
+        assert_float_eq!(invert(&invert(&m).unwrap()).unwrap(), m, abs_all <= 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // A singular matrix has no inverse
+        let op = ctx.op("affine matrix=1,2,2,4")?;
+        let mut operands = [Coor4D::raw(1., 1., 0., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 0);
+        assert!(ctx.op("affine inv matrix=1,2,2,4").is_err());
+        assert!(ctx.op("affine inv tscale=0").is_err());
+
+        assert!(ctx.op("affine matrix=1,2,3").is_err());
+        assert!(ctx.op("affine matrix=1,0,0,1 s11=2").is_err());
+        assert!(ctx.op("affine offset=1").is_err());
+        assert!(ctx.op("affine offset=1,2 xoff=3").is_err());
+        Ok(())
+    }
+}
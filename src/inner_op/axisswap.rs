@@ -0,0 +1,219 @@
+//! Axis order and orientation swapping, following the PROJ operator of the same name.
+//!
+//! The axes are given either by `order`, a list of signed, 1-based axis indices,
+//! or by `axis`, a 3 letter designation of the orientation of the output axes
+//! (e.g. `neu` or `wsu`), in the style of the PROJ `+axis` parameter.
+//!
+//! EXAMPLE: swap the first two axes, and negate the third
+//! ```sh
+//! $ echo 1 2 3 4 | kp "axisswap order=2,1,-3"
+//! > 2 1 -3 4
+//! ```
+//!
+//! `axisswap` covers part of the ground of the `adapt` operator, but in the
+//! imperative (PROJ) style, rather than the declarative style of `adapt`.
+//! It is mostly useful for instantiating pipelines written in PROJ syntax.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let Ok(post) = op.params.series("post") else {
+        return 0;
+    };
+    let Ok(mult) = op.params.series("mult") else {
+        return 0;
+    };
+
+    let n = operands.len();
+    for i in 0..n {
+        let coord = operands.get_coord(i);
+        let mut c = coord;
+        for j in 0..4 {
+            c[j] = coord[post[j] as usize] * mult[j];
+        }
+        operands.set_coord(i, &c);
+    }
+    n
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let Ok(post) = op.params.series("post") else {
+        return 0;
+    };
+    let Ok(mult) = op.params.series("mult") else {
+        return 0;
+    };
+
+    let n = operands.len();
+    for i in 0..n {
+        let coord = operands.get_coord(i);
+        let mut c = coord;
+        for j in 0..4 {
+            c[post[j] as usize] = coord[j] * mult[j];
+        }
+        operands.set_coord(i, &c);
+    }
+    n
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 3] = [
+    OpParameter::Flag   { key: "inv" },
+    OpParameter::Series { key: "order", default: Some("") },
+    OpParameter::Text   { key: "axis",  default: Some("") },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    let order = params.series("order").map(|o| o.to_vec()).ok();
+    let axis = params.text("axis")?;
+    let order = match (order, axis.is_empty()) {
+        (Some(order), true) => order,
+        (None, false) => {
+            axis_to_order(&axis).ok_or_else(|| Error::BadParam("axis".to_string(), def.clone()))?
+        }
+        (None, true) => return Err(Error::MissingParam("order or axis".to_string())),
+        (Some(_), false) => {
+            return Err(Error::Invalid(
+                "axisswap: 'order' and 'axis' are mutually exclusive".to_string(),
+            ))
+        }
+    };
+
+    let (post, mult) = order_to_permutation(&order)
+        .ok_or_else(|| Error::BadParam("order".to_string(), def.clone()))?;
+    params.series.insert("post", post.to_vec());
+    params.series.insert("mult", mult.to_vec());
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// Convert a PROJ style axis designation, e.g. "neu" or "wsu", to the
+// equivalent signed, 1-based axis order
+fn axis_to_order(axis: &str) -> Option<Vec<f64>> {
+    if axis.len() != 3 {
+        return None;
+    }
+    let order: Vec<f64> = axis
+        .chars()
+        .map(|c| match c {
+            'e' => 1.,
+            'w' => -1.,
+            'n' => 2.,
+            's' => -2.,
+            'u' => 3.,
+            'd' => -3.,
+            _ => 0.,
+        })
+        .collect();
+    Some(order)
+}
+
+// Convert a signed, 1-based axis order, to the source index and sign of each
+// output axis. Axes not mentioned in the order are passed through unchanged
+fn order_to_permutation(order: &[f64]) -> Option<([f64; 4], [f64; 4])> {
+    if order.is_empty() || order.len() > 4 {
+        return None;
+    }
+
+    let mut post = [0., 1., 2., 3.];
+    let mut mult = [1.; 4];
+    let mut used = [false; 4];
+    for (i, &o) in order.iter().enumerate() {
+        let index = o.abs();
+        if index.fract() != 0. || !(1. ..=4.).contains(&index) || used[index as usize - 1] {
+            return None;
+        }
+        used[index as usize - 1] = true;
+        post[i] = index - 1.;
+        mult[i] = o.signum();
+    }
+
+    // The order must be a permutation of the axes it mentions
+    if used[..order.len()].contains(&false) {
+        return None;
+    }
+    Some((post, mult))
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("axisswap order=2,1,-3")?;
+
+        let mut operands = [Coor4D::raw(1., 2., 3., 4.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_eq!(operands[0].0, [2., 1., -3., 4.]);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_eq!(operands[0].0, [1., 2., 3., 4.]);
+
+        // A cyclic permutation, to check the inverse is not just the forward
+        let op = ctx.op("axisswap order=3,-1,2,4")?;
+        let mut operands = [Coor4D::raw(1., 2., 3., 4.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_eq!(operands[0].0, [3., -1., 2., 4.]);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert_eq!(operands[0].0, [1., 2., 3., 4.]);
+
+        for bad in [
+            "axisswap",
+            "axisswap order=1,1",
+            "axisswap order=1,3",
+            "axisswap order=0,1",
+            "axisswap order=2,1,3,4,5",
+            "axisswap order=1.5,2",
+            "axisswap order=2,1 axis=neu",
+        ] {
+            assert!(ctx.op(bad).is_err(), "{bad}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn axis() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("axisswap axis=wsu")?;
+        let mut operands = [Coor4D::raw(1., 2., 3., 4.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_eq!(operands[0].0, [-1., -2., 3., 4.]);
+
+        let op = ctx.op("axisswap axis=neu")?;
+        let mut operands = [Coor4D::raw(1., 2., 3., 4.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_eq!(operands[0].0, [2., 1., 3., 4.]);
+
+        // Equivalent to the corresponding `adapt` operation
+        let adapt = ctx.op("adapt to=neuf")?;
+        let mut expected = [Coor4D::raw(1., 2., 3., 4.)];
+        ctx.apply(adapt, Fwd, &mut expected)?;
+        assert_eq!(operands, expected);
+
+        for bad in ["axisswap axis=ne", "axisswap axis=nnu", "axisswap axis=xyz"] {
+            assert!(ctx.op(bad).is_err(), "{bad}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! Cassini-Soldner
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+// Following IOGP, 2019, method 9806. PROJ implements the same series, but
+// with the opposite sign of the A⁵ term of the easting
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let es = ellps.eccentricity_squared();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let phi = coord[1];
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let tan_phi = sin_phi / cos_phi;
+
+        let nu = ellps.prime_vertical_radius_of_curvature(phi);
+        let a = (coord[0] - lon_0) * cos_phi;
+        let t = tan_phi * tan_phi;
+        let c = es * cos_phi * cos_phi / (1. - es);
+        let a2 = a * a;
+
+        let x = a * (1. - a2 * t * (1. / 6. + (8. - t + 8. * c) * a2 / 120.));
+        let y = nu * tan_phi * a2 * (0.5 + (5. - t + 6. * c) * a2 / 24.);
+
+        coord[0] = x_0 + nu * x;
+        coord[1] = y_0 + ellps.meridian_latitude_to_distance(phi) - m_0 + y;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+
+        // The footpoint latitude
+        let phi_1 = ellps.meridian_distance_to_latitude(m_0 + coord[1] - y_0);
+        let (sin_phi_1, cos_phi_1) = phi_1.sin_cos();
+        let tan_phi_1 = sin_phi_1 / cos_phi_1;
+
+        let nu = ellps.prime_vertical_radius_of_curvature(phi_1);
+        let rho = ellps.meridian_radius_of_curvature(phi_1);
+        let t = tan_phi_1 * tan_phi_1;
+        let d = (coord[0] - x_0) / nu;
+        let d2 = d * d;
+
+        let dphi = nu * tan_phi_1 / rho * d2 * (0.5 - (1. + 3. * t) * d2 / 24.);
+        let dlam = d * (1. - t * d2 * (1. / 3. - (1. + 3. * t) * d2 / 15.)) / cos_phi_1;
+
+        coord[0] = angular::normalize_symmetric(lon_0 + dlam);
+        coord[1] = phi_1 - dphi;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 6] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_0", default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    let ellps = params.ellps(0);
+
+    // The meridian distance from the equator to the latitude of origin
+    let m_0 = ellps.meridian_latitude_to_distance(params.lat(0).to_radians());
+    params.real.insert("m_0", m_0);
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn cass() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:30200 Trinidad 1903 / Trinidad Grid. The IOGP example for
+        // method 9806. Note that all linear units are Clarke's links, so
+        // the semimajor axis of the Clarke 1858 ellipsoid is given in links
+        let lat_0 = angular::dms_to_dd(10, 26, 30.);
+        let lon_0 = -angular::dms_to_dd(61, 20, 0.);
+        let definition = format!(
+            "cass lat_0={lat_0} lon_0={lon_0} x_0=430000 y_0=325000 ellps=31706587.88,294.2606763"
+        );
+        let op = ctx.op(&definition)?;
+
+        let geo = [Coor4D::geo(10., -62., 0., 0.)];
+        let projected = [Coor4D::raw(66_644.94, 82_536.22, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.01);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-9);
+
+        // The IOGP example for the reverse conversion, from the rounded
+        // projected coordinates, i.e. at the level of 0.01 links
+        let mut operands = projected;
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0][0], geo[0][0], abs <= 1e-9);
+        assert_float_eq!(operands[0][1], geo[0][1], abs <= 1e-9);
+
+        // The origin maps to the false origin
+        let mut operands = [Coor4D::geo(lat_0, lon_0, 0., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][0], 430_000., abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 325_000., abs <= 1e-6);
+        Ok(())
+    }
+}
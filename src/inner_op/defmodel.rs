@@ -0,0 +1,735 @@
+//! Multi-component deformation model, following the PROJ operator of the same
+//! name, and reading the model from a PROJ style JSON master file.
+//!
+//! The displacement at a given position and time is the sum of the
+//! contributions of the components of the model, each consisting of a
+//! spatial model (a grid), scaled by a time function evaluated at the
+//! epoch of the coordinate, `c[3]`.
+//!
+//! The operator works on geographical coordinates. The grids are read
+//! through the `Context`, so the supported grid formats depend on the
+//! `Context` in use.
+use super::deformation::rotate_and_integrate_velocity;
+use super::gridshift::grid_value;
+use crate::authoring::*;
+use serde_json::{Map, Value};
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let coord = operands.get_coord(i);
+        match displace(op, &coord) {
+            Some(displaced) => {
+                operands.set_coord(i, &displaced);
+                successes += 1;
+            }
+            None => operands.set_coord(i, &Coor4D::nan()),
+        }
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let mut successes = 0_usize;
+    let n = operands.len();
+
+    // The spatial models are given in the source system, so we need to iterate
+    'points: for i in 0..n {
+        let target = operands.get_coord(i);
+        let mut coord = target;
+        for _ in 0..10 {
+            let Some(displaced) = displace(op, &coord) else {
+                break;
+            };
+            let d = displaced - target;
+            coord[0] -= d[0];
+            coord[1] -= d[1];
+            coord[2] -= d[2];
+            if d[0].hypot(d[1]) < 1e-12 {
+                operands.set_coord(i, &coord);
+                successes += 1;
+                continue 'points;
+            }
+        }
+
+        // The iteration did not converge, or wandered off the model
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 3] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "model", default: None },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    let buf = ctx.get_blob(&params.text("model")?)?;
+    let grid_names = read_model(&buf, &mut params)?;
+
+    // One grid per component, checking that it has the bands needed
+    let angular = params.boolean("angular");
+    let components = params.series("COMPONENTS")?.to_vec();
+    for (component, name) in Component::all(&components).zip(grid_names) {
+        let grid = ctx.get_grid(&name)?;
+        if let Some(expected) = bad_bands(component.displacement, angular, grid.bands()) {
+            return Err(Error::Unexpected {
+                message: format!("Bad dimensionality of deformation model grid '{name}'"),
+                expected: expected.to_string(),
+                found: grid.bands().to_string(),
+            });
+        }
+        params.grids.push(grid);
+    }
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// Displacement types
+const HORIZONTAL: usize = 0;
+const VERTICAL: usize = 1;
+const THREE_D: usize = 2;
+const NO_DISPLACEMENT: usize = 3;
+
+// Time functions
+const CONSTANT: usize = 0;
+const VELOCITY: usize = 1;
+const STEP: usize = 2;
+const REVERSE_STEP: usize = 3;
+const PIECEWISE: usize = 4;
+const EXPONENTIAL: usize = 5;
+const LOGARITHMIC: usize = 6;
+
+// Extrapolation outside of the epochs of a piecewise time function
+const ZERO: f64 = 0.;
+const CONSTANT_VALUE: f64 = 1.;
+const LINEAR: f64 = 2.;
+
+// The components are stored in the "COMPONENTS" series, as consecutive records
+// of the form [record length, displacement type, west, south, east, north,
+// time function, time function parameters...], with the extent in radians
+struct Component<'a> {
+    displacement: usize,
+    extent: &'a [f64],
+    function: usize,
+    parameters: &'a [f64],
+}
+
+impl<'a> Component<'a> {
+    fn all(series: &'a [f64]) -> impl Iterator<Item = Component<'a>> {
+        let mut offset = 0;
+        std::iter::from_fn(move || {
+            if offset >= series.len() {
+                return None;
+            }
+            let record = &series[offset..offset + series[offset] as usize];
+            offset += record.len();
+            Some(Component {
+                displacement: record[1] as usize,
+                extent: &record[2..6],
+                function: record[6] as usize,
+                parameters: &record[7..],
+            })
+        })
+    }
+
+    // The time function, evaluated at the epoch t (in decimal years)
+    fn scale(&self, t: f64) -> f64 {
+        let p = self.parameters;
+        match self.function {
+            CONSTANT => 1.,
+            VELOCITY => t - p[0],
+            STEP => (t >= p[0]) as i32 as f64,
+            REVERSE_STEP => -((t < p[0]) as i32 as f64),
+            PIECEWISE => piecewise(p, t),
+            EXPONENTIAL => {
+                // reference epoch, end epoch, relaxation constant, and
+                // the before, initial, and final scale factors
+                if t < p[0] {
+                    return p[3];
+                }
+                let decay = (-(t.min(p[1]) - p[0]) / p[2]).exp();
+                p[4] + (p[5] - p[4]) * (1. - decay)
+            }
+            LOGARITHMIC => {
+                // reference epoch, end epoch, relaxation constant, and scale factor
+                if t < p[0] {
+                    return 0.;
+                }
+                p[3] * (1. + (t.min(p[1]) - p[0]) / p[2]).ln()
+            }
+            _ => f64::NAN,
+        }
+    }
+}
+
+// Linear interpolation between the (epoch, scale factor) pairs following the
+// extrapolation modes for epochs before the first and after the last pair
+fn piecewise(p: &[f64], t: f64) -> f64 {
+    let (before_first, after_last, model) = (p[0], p[1], &p[2..]);
+    let n = model.len() / 2;
+    let epoch = |i: usize| model[2 * i];
+    let factor = |i: usize| model[2 * i + 1];
+    let line = |i: usize, j: usize| {
+        if epoch(i) == epoch(j) {
+            return factor(j);
+        }
+        factor(i) + (factor(j) - factor(i)) * (t - epoch(i)) / (epoch(j) - epoch(i))
+    };
+
+    if t < epoch(0) {
+        if before_first == ZERO {
+            return 0.;
+        }
+        if before_first == LINEAR && n > 1 {
+            return line(0, 1);
+        }
+        return factor(0);
+    }
+    if t > epoch(n - 1) {
+        if after_last == ZERO {
+            return 0.;
+        }
+        if after_last == LINEAR && n > 1 {
+            return line(n - 2, n - 1);
+        }
+        return factor(n - 1);
+    }
+    for i in 1..n {
+        if t < epoch(i) {
+            return line(i - 1, i);
+        }
+    }
+    factor(n - 1)
+}
+
+// Compute the displaced position of coord, or None if the coordinate is
+// outside of the spatial or temporal extent of the model
+fn displace(op: &Op, coord: &Coor4D) -> Option<Coor4D> {
+    let time_extent = op.params.series("TIME_EXTENT").ok()?;
+    let t = coord[3];
+    if !(time_extent[0]..=time_extent[1]).contains(&t) {
+        return None;
+    }
+    if !contains(op.params.series("EXTENT").ok()?, coord) {
+        return None;
+    }
+
+    // Sum up the contributions from the components
+    let components = op.params.series("COMPONENTS").ok()?;
+    let mut enu = [0.; 3];
+    for (i, component) in Component::all(components).enumerate() {
+        if component.displacement == NO_DISPLACEMENT || !contains(component.extent, coord) {
+            continue;
+        }
+        let scale = component.scale(t);
+        if scale == 0. {
+            continue;
+        }
+        let grid = &op.params.grids[i..=i];
+        let v = grid_value(grid, coord)?;
+        match component.displacement {
+            HORIZONTAL => {
+                enu[0] += scale * v[0];
+                enu[1] += scale * v[1];
+            }
+            // The up component, for 3 band grids
+            VERTICAL if grid[0].bands() >= 3 => enu[2] += scale * v[2],
+            VERTICAL => enu[2] += scale * v[0],
+            _ => {
+                enu[0] += scale * v[0];
+                enu[1] += scale * v[1];
+                enu[2] += scale * v[2];
+            }
+        }
+    }
+
+    Some(apply_offset(op, coord, enu))
+}
+
+// Apply the offset, given in the local east-north-up system, to coord
+fn apply_offset(op: &Op, coord: &Coor4D, enu: [f64; 3]) -> Coor4D {
+    let ellps = op.params.ellps(0);
+    let mut c = *coord;
+
+    // Add the offset in the geocentric cartesian system
+    if op.params.boolean("geocentric") {
+        let v = Coor4D([enu[0], enu[1], enu[2], 0.]);
+        let cart = ellps.cartesian(coord) + rotate_and_integrate_velocity(v, c[0], c[1], 1.);
+        return ellps.geographic(&cart);
+    }
+
+    // ...or add it directly to the geographical coordinates
+    if op.params.boolean("angular") {
+        c[0] += enu[0];
+        c[1] += enu[1];
+    } else {
+        let n = ellps.prime_vertical_radius_of_curvature(c[1]);
+        let m = ellps.meridian_radius_of_curvature(c[1]);
+        c[0] += enu[0] / (n * c[1].cos());
+        c[1] += enu[1] / m;
+    }
+    c[2] += enu[2];
+    c
+}
+
+// The grid readers deliver horizontal offsets as 2 band (longitude, latitude)
+// grids in radians, while metric offsets come as 3 band (east, north, up) grids.
+// So the number of bands must match the declared horizontal offset unit. Returns
+// the expected number of bands, if `bands` does not match
+fn bad_bands(displacement: usize, angular: bool, bands: usize) -> Option<&'static str> {
+    let (ok, expected) = match displacement {
+        HORIZONTAL if angular => (bands == 2, "2 (angular)"),
+        HORIZONTAL | THREE_D => (bands >= 3, "3 (metric)"),
+        VERTICAL => (bands == 1 || bands >= 3, "1 or 3 (metric)"),
+        _ => (true, ""),
+    };
+    if ok {
+        None
+    } else {
+        Some(expected)
+    }
+}
+
+// Is coord inside the extent [west, south, east, north]?
+fn contains(extent: &[f64], coord: &Coor4D) -> bool {
+    (extent[0]..=extent[2]).contains(&coord[0]) && (extent[1]..=extent[3]).contains(&coord[1])
+}
+
+// Read a PROJ style deformation model master file, store the model parameters
+// in `params`, and return the names of the grids of the components
+fn read_model(buf: &[u8], params: &mut ParsedParameters) -> Result<Vec<String>, Error> {
+    let invalid = |message: &str| Error::Invalid(format!("defmodel: {message}"));
+    let json: Value =
+        serde_json::from_slice(buf).map_err(|e| invalid(&format!("malformed JSON: {e}")))?;
+
+    if json["file_type"] != "deformation_model_master_file" {
+        return Err(invalid("not a deformation model master file"));
+    }
+    let version = json["format_version"].as_str().unwrap_or_default();
+    if version != "1.0" {
+        return Err(invalid(&format!("unsupported format version '{version}'")));
+    }
+
+    // Units and methods
+    match json["horizontal_offset_unit"].as_str() {
+        Some("metre") | None => {}
+        Some("degree") => {
+            params.boolean.insert("angular");
+        }
+        Some(unit) => return Err(invalid(&format!("unsupported horizontal unit '{unit}'"))),
+    }
+    match json["vertical_offset_unit"].as_str() {
+        Some("metre") | None => {}
+        Some(unit) => return Err(invalid(&format!("unsupported vertical unit '{unit}'"))),
+    }
+    match json["horizontal_offset_method"].as_str() {
+        Some("addition") | None => {}
+        Some("geocentric") => {
+            params.boolean.insert("geocentric");
+        }
+        Some(method) => return Err(invalid(&format!("unsupported method '{method}'"))),
+    }
+    if params.boolean("angular") && params.boolean("geocentric") {
+        return Err(invalid("geocentric offsets must be given in metres"));
+    }
+
+    // Spatial and temporal extent of the model
+    params.series.insert(
+        "EXTENT",
+        extent(&json["extent"]).ok_or_else(|| invalid("bad extent"))?,
+    );
+    let first = json["time_extent"]["first"].as_str().map(decimal_year);
+    let last = json["time_extent"]["last"].as_str().map(decimal_year);
+    let time_extent = match (first, last) {
+        (None, None) => vec![f64::NEG_INFINITY, f64::INFINITY],
+        (Some(Some(first)), Some(Some(last))) => vec![first, last],
+        _ => return Err(invalid("bad time extent")),
+    };
+    params.series.insert("TIME_EXTENT", time_extent);
+
+    // The components
+    let Some(components) = json["components"].as_array() else {
+        return Err(invalid("missing 'components'"));
+    };
+    let mut series = Vec::new();
+    let mut grid_names = Vec::new();
+    for component in components {
+        let bad = |what: &str| {
+            invalid(&format!(
+                "bad {what} in component '{}'",
+                component["description"]
+            ))
+        };
+
+        let displacement = match component["displacement_type"].as_str() {
+            Some("horizontal") => HORIZONTAL,
+            Some("vertical") => VERTICAL,
+            Some("3d") => THREE_D,
+            Some("none") => NO_DISPLACEMENT,
+            _ => return Err(bad("displacement type")),
+        };
+        let extent = extent(&component["extent"]).ok_or_else(|| bad("extent"))?;
+
+        // 3D displacements in degrees would need a grid mixing angular and
+        // metric bands, which the grid readers do not provide
+        if displacement == THREE_D && params.boolean("angular") {
+            return Err(Error::Unsupported(
+                "defmodel: 3d displacements require a horizontal offset unit of 'metre'"
+                    .to_string(),
+            ));
+        }
+
+        // PROJ deformation models use GeoTIFF grids exclusively
+        let spatial_model = &component["spatial_model"];
+        if spatial_model["type"] != "GeoTIFF" {
+            return Err(bad("spatial model type"));
+        }
+        match spatial_model["interpolation_method"].as_str() {
+            Some("bilinear") | Some("geocentric_bilinear") | None => {}
+            _ => return Err(bad("interpolation method")),
+        }
+        let Some(filename) = spatial_model["filename"].as_str() else {
+            return Err(bad("spatial model"));
+        };
+
+        let time_function = &component["time_function"];
+        let empty = Map::new();
+        let p = time_function["parameters"].as_object().unwrap_or(&empty);
+        let (function, parameters) =
+            time_function_parameters(time_function["type"].as_str().unwrap_or_default(), p)
+                .ok_or_else(|| bad("time function"))?;
+
+        series.push((7 + parameters.len()) as f64);
+        series.push(displacement as f64);
+        series.extend(extent);
+        series.push(function as f64);
+        series.extend(parameters);
+        grid_names.push(filename.to_string());
+    }
+    params.series.insert("COMPONENTS", series);
+    Ok(grid_names)
+}
+
+// Parse the parameters of a time function into the internal representation
+fn time_function_parameters(kind: &str, p: &Map<String, Value>) -> Option<(usize, Vec<f64>)> {
+    let epoch = |key: &str| p.get(key).and_then(Value::as_str).and_then(decimal_year);
+    let real = |key: &str| p.get(key).and_then(Value::as_f64);
+    let mode = |key: &str| match p.get(key).and_then(Value::as_str) {
+        Some("zero") => Some(ZERO),
+        Some("constant") => Some(CONSTANT_VALUE),
+        Some("linear") => Some(LINEAR),
+        _ => None,
+    };
+
+    Some(match kind {
+        "constant" => (CONSTANT, vec![]),
+        "velocity" => (VELOCITY, vec![epoch("reference_epoch")?]),
+        "step" => (STEP, vec![epoch("step_epoch")?]),
+        "reverse_step" => (REVERSE_STEP, vec![epoch("step_epoch")?]),
+        "piecewise" => {
+            let mut parameters = vec![mode("before_first")?, mode("after_last")?];
+            let model = p.get("model")?.as_array()?;
+            if model.is_empty() {
+                return None;
+            }
+            for point in model {
+                parameters.push(decimal_year(point["epoch"].as_str()?)?);
+                parameters.push(point["scale_factor"].as_f64()?);
+            }
+            (PIECEWISE, parameters)
+        }
+        "exponential" => {
+            let end = p
+                .get("end_epoch")
+                .map_or(Some(f64::INFINITY), |e| decimal_year(e.as_str()?));
+            (
+                EXPONENTIAL,
+                vec![
+                    epoch("reference_epoch")?,
+                    end?,
+                    real("relaxation_constant")?,
+                    real("before_scale_factor")?,
+                    real("initial_scale_factor")?,
+                    real("final_scale_factor")?,
+                ],
+            )
+        }
+        "logarithmic" => {
+            let end = p
+                .get("end_epoch")
+                .map_or(Some(f64::INFINITY), |e| decimal_year(e.as_str()?));
+            (
+                LOGARITHMIC,
+                vec![
+                    epoch("reference_epoch")?,
+                    end?,
+                    real("relaxation_constant")?,
+                    real("scale_factor")?,
+                ],
+            )
+        }
+        _ => return None,
+    })
+}
+
+// Read a bounding box extent, [west, south, east, north], and convert to radians
+fn extent(extent: &Value) -> Option<Vec<f64>> {
+    if extent.is_null() {
+        return Some(vec![
+            -f64::INFINITY,
+            -f64::INFINITY,
+            f64::INFINITY,
+            f64::INFINITY,
+        ]);
+    }
+    if extent["type"] != "bbox" {
+        return None;
+    }
+    let bbox = extent["parameters"]["bbox"].as_array()?;
+    let bbox: Option<Vec<f64>> = bbox
+        .iter()
+        .map(|v| v.as_f64().map(f64::to_radians))
+        .collect();
+    bbox.filter(|b| b.len() == 4)
+}
+
+/// Convert an ISO 8601 date-time of the form `YYYY-MM-DDThh:mm:ssZ`, as used
+/// in the PROJ deformation model files, to a decimal year. The time part is optional
+fn decimal_year(epoch: &str) -> Option<f64> {
+    const CUMULATIVE_DAYS: [u32; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+    let epoch = epoch.trim().trim_end_matches('Z');
+    let (date, time) = epoch.split_once('T').unwrap_or((epoch, "00:00:00"));
+
+    let date: Vec<u32> = date
+        .split('-')
+        .map(|x| x.parse().ok())
+        .collect::<Option<_>>()?;
+    let time: Vec<f64> = time
+        .split(':')
+        .map(|x| x.parse().ok())
+        .collect::<Option<_>>()?;
+    let (&[year, month, day], &[hour, minute, second]) = (&date[..], &time[..]) else {
+        return None;
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_year = if leap { 366. } else { 365. };
+    let mut day_of_year = CUMULATIVE_DAYS[month as usize - 1] + day - 1;
+    if leap && month > 2 {
+        day_of_year += 1;
+    }
+    let fraction = (hour + minute / 60. + second / 3600.) / 24.;
+    Some(year as f64 + (day_of_year as f64 + fraction) / days_in_year)
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn defmodel() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let op = ctx.op("defmodel model=test_defmodel.json")?;
+        let ellps = Ellipsoid::default();
+
+        // At (55, 12), the velocity grid gives 12 mm/y east, 55 mm/y north,
+        // and the earthquake adds the same in the horizontal. The geoid grid
+        // scales the post seismic vertical relaxation
+        let cph = Coor4D::geo(55., 12., 100., 2020.);
+        let mut operands = [cph];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        let (east, north) = (21. * 0.012, 21. * 0.055);
+        let up = 55.12 * 0.001 * (1. - (-5f64).exp());
+        let n = ellps.prime_vertical_radius_of_curvature(cph[1]);
+        let m = ellps.meridian_radius_of_curvature(cph[1]);
+        assert_float_eq!(
+            operands[0][0] - cph[0],
+            east / (n * cph[1].cos()),
+            abs <= 1e-12
+        );
+        assert_float_eq!(operands[0][1] - cph[1], north / m, abs <= 1e-12);
+        assert_float_eq!(operands[0][2] - cph[2], up, abs <= 1e-6);
+        assert_eq!(operands[0][3], 2020.);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, cph.0, abs_all <= 1e-10);
+
+        // Before the earthquake, and before the reference epoch
+        let mut operands = [Coor4D::geo(55., 12., 100., 1990.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][1] - cph[1], -10. * 0.055 / m, abs <= 1e-12);
+        assert_eq!(operands[0][2], 100.);
+
+        // West of the earthquake extent, only the secular velocity applies
+        let mut operands = [Coor4D::geo(55., 9., 100., 2020.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][1] - cph[1], 20. * 0.055 / m, abs <= 1e-12);
+
+        // Outside of the spatial and temporal extents of the model
+        let mut operands = [
+            Coor4D::geo(53., 12., 0., 2020.),
+            Coor4D::geo(55., 12., 0., 1800.),
+        ];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        assert!(operands[1][0].is_nan());
+
+        assert!(ctx.op("defmodel model=no_such_model.json").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn units() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+
+        // A model in degrees, with a constant horizontal offset from a
+        // (longitude, latitude) grid, is just a gridshift
+        let op = ctx.op("defmodel model=test_defmodel_degree.json")?;
+        let gridshift = ctx.op("gridshift grids=test_datum.tif")?;
+        let mut operands = [Coor4D::geo(55., 12., 0., 2020.)];
+        let mut expected = operands;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        ctx.apply(gridshift, Fwd, &mut expected)?;
+        assert_float_eq!(operands[0].0, expected[0].0, abs_all <= 1e-12);
+
+        // The grid bands must match the horizontal offset unit
+        assert!(bad_bands(HORIZONTAL, true, 2).is_none());
+        assert!(bad_bands(HORIZONTAL, true, 3).is_some());
+        assert!(bad_bands(HORIZONTAL, false, 2).is_some());
+        assert!(bad_bands(THREE_D, false, 3).is_none());
+        assert!(bad_bands(VERTICAL, false, 1).is_none());
+        assert!(bad_bands(VERTICAL, false, 2).is_some());
+
+        // Only GeoTIFF spatial models, and no 3d displacements in degrees
+        let mut params = ParsedParameters::new(
+            &RawParameters::new("defmodel model=x", &BTreeMap::new()),
+            &GAMUT,
+        )?;
+        let json = std::fs::read_to_string("geodesy/json/test_defmodel.json")?;
+        assert!(read_model(json.as_bytes(), &mut params).is_ok());
+        let gravsoft = json.replace("\"GeoTIFF\"", "\"Gravsoft\"");
+        assert!(read_model(gravsoft.as_bytes(), &mut params).is_err());
+        let degree = json.replace(
+            "\"horizontal_offset_unit\": \"metre\"",
+            "\"horizontal_offset_unit\": \"degree\"",
+        );
+        assert!(matches!(
+            read_model(degree.as_bytes(), &mut params),
+            Err(Error::Unsupported(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn time_functions() {
+        let component = |function, parameters| Component {
+            displacement: HORIZONTAL,
+            extent: &[],
+            function,
+            parameters,
+        };
+
+        assert_eq!(component(CONSTANT, &[]).scale(1234.), 1.);
+        assert_eq!(component(VELOCITY, &[2000.]).scale(2010.5), 10.5);
+        assert_eq!(component(STEP, &[2000.]).scale(1999.), 0.);
+        assert_eq!(component(STEP, &[2000.]).scale(2000.), 1.);
+        assert_eq!(component(REVERSE_STEP, &[2000.]).scale(1999.), -1.);
+        assert_eq!(component(REVERSE_STEP, &[2000.]).scale(2000.), 0.);
+
+        // reference epoch, end epoch, relaxation constant, before, initial, final
+        let exponential = [2000., 2010., 2., 0.5, 1., 3.];
+        let f = |t| component(EXPONENTIAL, &exponential).scale(t);
+        assert_eq!(f(1999.), 0.5);
+        assert_eq!(f(2000.), 1.);
+        assert_float_eq!(f(2002.), 1. + 2. * (1. - (-1f64).exp()), abs <= 1e-12);
+        assert_eq!(f(2020.), f(2010.));
+
+        // reference epoch, end epoch, relaxation constant, scale factor
+        let logarithmic = [2000., f64::INFINITY, 2., 0.5];
+        let f = |t| component(LOGARITHMIC, &logarithmic).scale(t);
+        assert_eq!(f(1999.), 0.);
+        assert_float_eq!(f(2002.), 0.5 * 2f64.ln(), abs <= 1e-12);
+
+        // A piecewise function with a step at 2010
+        let model = [2000., 0., 2010., 1., 2010., 3., 2020., 4.];
+        let piecewise = |before, after| [&[before, after][..], &model[..]].concat();
+        let p = piecewise(ZERO, CONSTANT_VALUE);
+        let f = |t| component(PIECEWISE, &p).scale(t);
+        assert_eq!(f(1990.), 0.);
+        assert_eq!(f(2005.), 0.5);
+        assert_eq!(f(2010.), 3.);
+        assert_eq!(f(2015.), 3.5);
+        assert_eq!(f(2020.), 4.);
+        assert_eq!(f(2030.), 4.);
+        let p = piecewise(LINEAR, LINEAR);
+        let f = |t| component(PIECEWISE, &p).scale(t);
+        assert_eq!(f(1990.), -1.);
+        assert_eq!(f(2030.), 5.);
+        let p = piecewise(CONSTANT_VALUE, ZERO);
+        let f = |t| component(PIECEWISE, &p).scale(t);
+        assert_eq!(f(1990.), 0.);
+        assert_eq!(f(2030.), 0.);
+    }
+
+    #[test]
+    fn geocentric() -> Result<(), Error> {
+        // The geocentric and the additive offset methods agree closely. The
+        // difference is dominated by the additive method ignoring the height
+        let ctx = Plain::default();
+        let mut op = Op::new("defmodel model=test_defmodel.json", &ctx)?;
+        let cph = Coor4D::geo(55., 12., 100., 2020.);
+        let enu = [1., 2., 3.];
+
+        let addition = apply_offset(&op, &cph, enu);
+        op.params.boolean.insert("geocentric");
+        let geocentric = apply_offset(&op, &cph, enu);
+
+        let ellps = Ellipsoid::default();
+        let d = ellps.cartesian(&addition) - ellps.cartesian(&geocentric);
+        assert!(d.dot(d).sqrt() < 1e-4);
+        Ok(())
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(decimal_year("2000-01-01T00:00:00Z"), Some(2000.));
+        assert_eq!(decimal_year("2000-01-01"), Some(2000.));
+        assert_eq!(decimal_year("2001-07-02T12:00:00Z"), Some(2001.5));
+        assert_eq!(
+            decimal_year("2000-12-31T12:00:00Z"),
+            Some(2000. + 365.5 / 366.)
+        );
+        assert_eq!(decimal_year("2000-13-01T00:00:00Z"), None);
+        assert_eq!(decimal_year("2000-01T00:00:00Z"), None);
+        assert_eq!(decimal_year("yesterday"), None);
+    }
+}
@@ -300,7 +300,7 @@ pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
 // the geocentric cartesian system, and multiply by the
 // deformation duration to obtain the total deformation
 #[inline]
-fn rotate_and_integrate_velocity(
+pub(super) fn rotate_and_integrate_velocity(
     v: Coor4D,
     longitude: f64,
     latitude: f64,
@@ -0,0 +1,166 @@
+//! Eckert IV, spherical form
+use crate::authoring::*;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+// C_X = 2/√(π(4+π)), C_Y = 2√(π/(4+π)), C_P = 2 + π/2
+const C_X: f64 = 0.422_238_200_315_771_2;
+const C_Y: f64 = 1.326_500_428_177_002_3;
+const C_P: f64 = 3.570_796_326_794_896_6;
+
+const MAX_ITERATIONS: usize = 30;
+const EPS: f64 = 1e-14;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = angular::normalize_symmetric(coord[0] - lon_0);
+        let theta = auxiliary_angle(coord[1]);
+        coord[0] = x_0 + r * C_X * lam * (1. + theta.cos());
+        coord[1] = y_0 + r * C_Y * theta.sin();
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let sin_theta = (coord[1] - y_0) / (r * C_Y);
+        if sin_theta.abs() > 1. + 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let theta = sin_theta.clamp(-1., 1.).asin();
+        let cos_theta = theta.cos();
+
+        let lam = (coord[0] - x_0) / (r * C_X * (1. + cos_theta));
+        if lam.abs() > PI + 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        let sin_phi = (theta + sin_theta * (cos_theta + 2.)) / C_P;
+        coord[0] = angular::normalize_symmetric(lam + lon_0);
+        coord[1] = sin_phi.clamp(-1., 1.).asin();
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    Op::plain(parameters, InnerOp(fwd), Some(InnerOp(inv)), &GAMUT, ctx)
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+/// The auxiliary angle, θ, given by θ + sin θ cos θ + 2 sin θ = (2 + π/2) sin φ,
+/// solved by Newton-Raphson iteration (Snyder, 1993)
+fn auxiliary_angle(phi: f64) -> f64 {
+    // At the poles, θ = φ
+    if (phi.abs() - FRAC_PI_2).abs() < 1e-12 {
+        return phi;
+    }
+    let p = C_P * phi.sin();
+    let v = phi * phi;
+    let mut theta = phi * (0.895168 + v * (0.0218849 + v * 0.00826809));
+    for _ in 0..MAX_ITERATIONS {
+        let (s, c) = theta.sin_cos();
+        let delta = (theta + s * (c + 2.) - p) / (1. + c * (c + 2.) - s * s);
+        theta -= delta;
+        if delta.abs() < EPS {
+            break;
+        }
+    }
+    theta
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn eck4() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("eck4 ellps=sphere")?;
+        let r = 6_370_997.;
+
+        // The poles are lines of half the length of the equator
+        let geo = [
+            Coor4D::geo(0., 90., 0., 0.),
+            Coor4D::geo(90., 90., 0., 0.),
+            Coor4D::geo(-35., -120., 0., 0.),
+            Coor4D::geo(89.99, 17., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 4);
+        assert_float_eq!(operands[0][0], C_X * PI * r, abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 0., abs <= 1e-6);
+        assert_float_eq!(operands[1][0], C_X * FRAC_PI_2 * r, abs <= 1e-6);
+        assert_float_eq!(operands[1][1], C_Y * r, abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 4);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn factors() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("eck4 ellps=sphere")?;
+        let ellps = Ellipsoid::named("sphere")?;
+
+        // Equal area, but not conformal
+        for at in [
+            Coor2D::geo(0., 0.),
+            Coor2D::geo(50., 60.),
+            Coor2D::geo(-70., 170.),
+        ] {
+            let factors =
+                Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?
+                    .factors();
+            assert_float_eq!(factors.areal_scale, 1., abs <= 1e-8);
+        }
+        let at = Coor2D::geo(50., 60.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert!(factors.angular_distortion > 0.1);
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+//! Equidistant Cylindrical (Plate Carrée)
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+// The ellipsoidal form of the projection, following IOGP, 2019, method 1028.
+// The spherical form (as implemented by PROJ) is obtained by selecting a
+// spherical ellipsoid
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(parallel_radius) = op.params.real("parallel_radius") else {
+        return 0;
+    };
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0] - lon_0;
+        let phi = coord[1];
+
+        coord[0] = x_0 + parallel_radius * lam;
+        coord[1] = y_0 + ellps.meridian_latitude_to_distance(phi) - m_0;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(parallel_radius) = op.params.real("parallel_radius") else {
+        return 0;
+    };
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let x = coord[0] - x_0;
+        let y = coord[1] - y_0;
+
+        coord[0] = angular::normalize_symmetric(lon_0 + x / parallel_radius);
+        coord[1] = ellps.meridian_distance_to_latitude(y + m_0);
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 7] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps",  default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_ts", default: Some(0_f64) },
+    OpParameter::Real { key: "lat_0",  default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0",  default: Some(0_f64) },
+
+    OpParameter::Real { key: "x_0",    default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",    default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    let ellps = params.ellps(0);
+
+    // The radius of the parallel of true scale
+    let lat_ts = params.real("lat_ts")?.to_radians();
+    let parallel_radius = ellps.prime_vertical_radius_of_curvature(lat_ts) * lat_ts.cos();
+    if parallel_radius <= 0. {
+        return Err(Error::BadParam("lat_ts".to_string(), def.clone()));
+    }
+
+    // The meridian distance from the equator to the latitude of origin
+    let m_0 = ellps.meridian_latitude_to_distance(params.lat(0).to_radians());
+
+    params.real.insert("parallel_radius", parallel_radius);
+    params.real.insert("m_0", m_0);
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn eqc() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:4087 WGS 84 / World Equidistant Cylindrical. The IOGP example
+        // for method 1028
+        let op = ctx.op("eqc ellps=WGS84")?;
+        let geo = [Coor4D::geo(55., 10., 0., 0.)];
+        let projected = [Coor4D::raw(1_113_194.91, 6_097_230.31, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.01);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-10);
+
+        // Latitude of true scale, and latitude of origin
+        let op = ctx.op("eqc lat_ts=60 lat_0=55 lon_0=10 x_0=1000 ellps=WGS84")?;
+        let geo = [Coor4D::geo(55., 10., 0., 0.), Coor4D::geo(65., 12., 0., 0.)];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 2);
+        assert_float_eq!(operands[0][0], 1000., abs <= 1e-9);
+        assert_float_eq!(operands[0][1], 0., abs <= 1e-9);
+
+        // The scale is true along the parallel of true scale, so the easting
+        // equals the length of the arc from the central meridian
+        let ellps = Ellipsoid::named("WGS84")?;
+        let arc = ellps.prime_vertical_radius_of_curvature(60_f64.to_radians())
+            * 60_f64.to_radians().cos()
+            * 2_f64.to_radians();
+        assert_float_eq!(operands[1][0] - 1000., arc, abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 2);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+        Ok(())
+    }
+}
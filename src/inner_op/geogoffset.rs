@@ -0,0 +1,92 @@
+//! Geographic offsets, following the PROJ operator of the same name, and EPSG
+//! methods 9619 (geographic 2D offsets) and 9660 (geographic 3D offsets).
+//!
+//! The latitude and longitude offsets, `dlat` and `dlon`, are given in
+//! arcseconds, and the height offset, `dh`, in metres. In the forward
+//! direction, the offsets are added to the input coordinates.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    offset(op, operands, 1.)
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    offset(op, operands, -1.)
+}
+
+// The forward and inverse cases differ only by the sign of the offsets
+fn offset(op: &Op, operands: &mut dyn CoordinateSet, sign: f64) -> usize {
+    let Ok(d) = op.params.series("OFFSET") else {
+        return 0;
+    };
+
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        coord[0] += sign * d[0];
+        coord[1] += sign * d[1];
+        coord[2] += sign * d[2];
+        operands.set_coord(i, &coord);
+    }
+    n
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 4] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Real { key: "dlat", default: Some(0_f64) },
+    OpParameter::Real { key: "dlon", default: Some(0_f64) },
+    OpParameter::Real { key: "dh",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    // The offsets, in the internal (longitude, latitude, height) order, and in radians
+    let dlon = (params.real("dlon")? / 3600.).to_radians();
+    let dlat = (params.real("dlat")? / 3600.).to_radians();
+    let dh = params.real("dh")?;
+    params.series.insert("OFFSET", Vec::from([dlon, dlat, dh]));
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geogoffset() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("geogoffset dlat=-36 dlon=72 dh=100")?;
+
+        let p = Coor4D::geo(55., 12., 10., 2020.);
+        let mut operands = [p];
+        ctx.apply(op, Fwd, &mut operands)?;
+        let expected = Coor4D::geo(54.99, 12.02, 110., 2020.);
+        assert!(expected.hypot2(&operands[0]) < 1e-15);
+        assert!((operands[0][2] - 110.).abs() < 1e-12);
+        assert_eq!(operands[0][3], 2020.);
+
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(p.hypot3(&operands[0]) < 1e-12);
+        Ok(())
+    }
+}
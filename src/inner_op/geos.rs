@@ -0,0 +1,253 @@
+//! Geostationary satellite view
+//!
+//! The projection describes the view from a geostationary satellite, at
+//! height `h` above the equator, by the scanning angles of the imaging
+//! instrument. The `sweep` parameter selects the axis of the outer gimbal
+//! of the instrument: `y` (the default) for Meteosat, and `x` for GOES.
+//!
+//! Following PROJ, the scanning angles are scaled by the height, `h`, so
+//! they come out as (approximately) metric units.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let es = ellps.eccentricity_squared();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(h) = op.params.real("h") else {
+        return 0;
+    };
+    let sweep_x = op.params.boolean("sweep_x");
+
+    // The satellite distance from the geocenter, and the polar radius, in units of `a`
+    let radius_g = 1. + h / a;
+    let radius_p = (1. - es).sqrt();
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0] - lon_0;
+
+        // Geocentric latitude, and the geocentric radius vector of the point
+        let phi = ((1. - es) * coord[1].tan()).atan();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let (sin_lam, cos_lam) = lam.sin_cos();
+        let r = radius_p / (radius_p * cos_phi).hypot(sin_phi);
+        let vx = r * cos_lam * cos_phi;
+        let vy = r * sin_lam * cos_phi;
+        let vz = r * sin_phi;
+
+        // Is the point visible from the satellite?
+        let tmp = radius_g - vx;
+        if tmp * vx - vy * vy - vz * vz / (1. - es) < 0. {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        // The scanning angles
+        let (x, y) = if sweep_x {
+            ((vy / vz.hypot(tmp)).atan(), (vz / tmp).atan())
+        } else {
+            ((vy / tmp).atan(), (vz / vy.hypot(tmp)).atan())
+        };
+        coord[0] = x_0 + h * x;
+        coord[1] = y_0 + h * y;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let es = ellps.eccentricity_squared();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(h) = op.params.real("h") else {
+        return 0;
+    };
+    let sweep_x = op.params.boolean("sweep_x");
+
+    let radius_g = 1. + h / a;
+    let radius_p = (1. - es).sqrt();
+    let c = radius_g * radius_g - 1.;
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let x = (coord[0] - x_0) / h;
+        let y = (coord[1] - y_0) / h;
+
+        // The direction of the line of sight from the satellite
+        let vx = -1.;
+        let (vy, vz) = if sweep_x {
+            let vz = y.tan();
+            (x.tan() * 1f64.hypot(vz), vz)
+        } else {
+            let vy = x.tan();
+            (vy, y.tan() * 1f64.hypot(vy))
+        };
+
+        // Intersection of the line of sight and the ellipsoid (if any)
+        let vz_p = vz / radius_p;
+        let aa = vx * vx + vy * vy + vz_p * vz_p;
+        let bb = 2. * radius_g * vx;
+        let det = bb * bb - 4. * aa * c;
+        if det < 0. {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let k = (-bb - det.sqrt()) / (2. * aa);
+        let vx = radius_g + k * vx;
+        let vy = k * vy;
+        let vz = k * vz;
+
+        let lam = vy.atan2(vx);
+        let phi = (vz * lam.cos() / vx).atan();
+        coord[0] = angular::normalize_symmetric(lon_0 + lam);
+        coord[1] = (phi.tan() / (1. - es)).atan();
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 7] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+    OpParameter::Text { key: "sweep", default: Some("y") },
+
+    OpParameter::Real { key: "h",     default: None },
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    if params.real("h")? <= 0. {
+        return Err(Error::BadParam("h".to_string(), def.clone()));
+    }
+
+    match params.text("sweep")?.as_str() {
+        "x" => {
+            params.boolean.insert("sweep_x");
+        }
+        "y" => (),
+        _ => return Err(Error::BadParam("sweep".to_string(), def.clone())),
+    }
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn geos() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // Test values from the PROJ test suite (builtins.gie)
+        let op = ctx.op("geos ellps=GRS80 h=35785831")?;
+        let geo = [
+            Coor4D::geo(1., 2., 0., 0.),
+            Coor4D::geo(-1., -2., 0., 0.),
+            Coor4D::geo(50., 30., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][0], 222_527.070_365_800, abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 110_551.303_413_329, abs <= 1e-6);
+        assert_float_eq!(operands[1][0], -222_527.070_365_800, abs <= 1e-6);
+        assert_float_eq!(operands[1][1], -110_551.303_413_329, abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn sweep() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op_x = ctx.op("geos ellps=GRS80 h=35785831 sweep=x lon_0=-75")?;
+        let op_y = ctx.op("geos ellps=GRS80 h=35785831 sweep=y lon_0=-75")?;
+        let geo = [
+            Coor4D::geo(40., -100., 0., 0.),
+            Coor4D::geo(-30., -50., 0., 0.),
+        ];
+
+        let mut x = geo;
+        let mut y = geo;
+        assert_eq!(ctx.apply(op_x, Fwd, &mut x)?, 2);
+        assert_eq!(ctx.apply(op_y, Fwd, &mut y)?, 2);
+
+        // The sweep axis makes a difference, except along the axes
+        assert!((x[0][0] - y[0][0]).abs() > 100.);
+        assert!((x[0][1] - y[0][1]).abs() > 100.);
+
+        assert_eq!(ctx.apply(op_x, Inv, &mut x)?, 2);
+        for i in 0..x.len() {
+            assert_float_eq!(x[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+
+        assert!(ctx.op("geos ellps=GRS80 h=35785831 sweep=z").is_err());
+        assert!(ctx.op("geos ellps=GRS80").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invisible() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("geos ellps=GRS80 h=35785831")?;
+
+        // Points on the far side of the globe are invisible from the satellite
+        let mut operands = [
+            Coor4D::geo(0., 100., 0., 0.),
+            Coor4D::geo(85., 0., 0., 0.),
+            Coor4D::geo(0., 10., 0., 0.),
+        ];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert!(operands[0][0].is_nan());
+        assert!(operands[1][0].is_nan());
+        assert!(!operands[2][0].is_nan());
+
+        // Lines of sight that miss the globe
+        let mut operands = [
+            Coor4D::raw(6_000_000., 0., 0., 0.),
+            Coor4D::raw(0., 0., 0., 0.),
+        ];
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert!(operands[0][0].is_nan());
+        assert_float_eq!(operands[1][0], 0., abs <= 1e-12);
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+//! Gnomonic, spherical form
+//!
+//! All great circles map to straight lines. Only the hemisphere centered on
+//! the projection center can be mapped: Points on, or beyond, the horizon
+//! are marked as invalid.
+use crate::authoring::*;
+
+const EPS10: f64 = 1e-10;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lat_0 = op.params.lat(0).to_radians();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let (sin_lat_0, cos_lat_0) = lat_0.sin_cos();
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0] - lon_0;
+        let (sin_phi, cos_phi) = coord[1].sin_cos();
+        let (sin_lam, cos_lam) = lam.sin_cos();
+
+        // The cosine of the angular distance from the center
+        let cos_c = sin_lat_0 * sin_phi + cos_lat_0 * cos_phi * cos_lam;
+        if cos_c < EPS10 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        coord[0] = x_0 + r * cos_phi * sin_lam / cos_c;
+        coord[1] = y_0 + r * (cos_lat_0 * sin_phi - sin_lat_0 * cos_phi * cos_lam) / cos_c;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lat_0 = op.params.lat(0).to_radians();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let (sin_lat_0, cos_lat_0) = lat_0.sin_cos();
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let x = (coord[0] - x_0) / r;
+        let y = (coord[1] - y_0) / r;
+        let rho = x.hypot(y);
+
+        // At the center
+        if rho < EPS10 {
+            coord[0] = lon_0;
+            coord[1] = lat_0;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        let c = rho.atan();
+        let (sin_c, cos_c) = c.sin_cos();
+        let lat = (cos_c * sin_lat_0 + y * sin_c * cos_lat_0 / rho).asin();
+        let lon = (x * sin_c).atan2(rho * cos_lat_0 * cos_c - y * sin_lat_0 * sin_c);
+        coord[0] = angular::normalize_symmetric(lon_0 + lon);
+        coord[1] = lat;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 6] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_0", default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    Op::plain(parameters, InnerOp(fwd), Some(InnerOp(inv)), &GAMUT, ctx)
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn gnom() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("gnom ellps=sphere lat_0=90")?;
+        let r = 6_370_997.;
+
+        // In the polar aspect, the distance from the center is R cot(φ)
+        let geo = [
+            Coor4D::geo(45., 0., 0., 0.),
+            Coor4D::geo(60., 90., 0., 0.),
+            Coor4D::geo(10., -150., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][0], 0., abs <= 1e-6);
+        assert_float_eq!(operands[0][1], -r, abs <= 1e-6);
+        assert_float_eq!(operands[1][0], r / 3f64.sqrt(), abs <= 1e-6);
+        assert_float_eq!(operands[1][1], 0., abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn great_circles() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("gnom ellps=sphere lat_0=55 lon_0=12")?;
+        let ellps = Ellipsoid::named("sphere")?;
+
+        // Points along a great circle map to a straight line
+        let from = Coor4D::geo(40., -20., 0., 0.);
+        let to = Coor4D::geo(60., 50., 0., 0.);
+        let (azimuth, distance) = {
+            let g = ellps.geodesic_inv(&from, &to);
+            (g[0], g[2])
+        };
+        let mut operands = [from, to, ellps.geodesic_fwd(&from, azimuth, distance / 3.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        let [p, q, m] = operands;
+        let cross = (q[0] - p[0]) * (m[1] - p[1]) - (q[1] - p[1]) * (m[0] - p[0]);
+        assert!(cross.abs() / (q[0] - p[0]).hypot(q[1] - p[1]) < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn invisible() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("gnom ellps=sphere lat_0=55 lon_0=12")?;
+
+        // The horizon, and the far side of the globe, cannot be mapped
+        let mut operands = [
+            Coor4D::geo(-35., 12., 0., 0.),
+            Coor4D::geo(-55., -168., 0., 0.),
+            Coor4D::geo(55., 12., 0., 0.),
+        ];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert!(operands[0][0].is_nan());
+        assert!(operands[1][0].is_nan());
+        Ok(())
+    }
+}
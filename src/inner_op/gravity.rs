@@ -0,0 +1,193 @@
+/// Normal gravity and gravity related heights
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let n = operands.len();
+    let sliced = 0..n;
+    let ellps = op.params.ellps(0);
+
+    let normal = op.params.boolean("normal");
+    let dynamic = op.params.boolean("dynamic");
+    let orthometric = op.params.boolean("orthometric");
+
+    let mut successes = 0_usize;
+
+    if normal {
+        for i in sliced {
+            let mut coord = operands.get_coord(i);
+            let lat = coord[0].to_radians();
+            coord[0] = ellps.normal_gravity_at_height(lat, coord[1]);
+            operands.set_coord(i, &coord);
+            successes += 1;
+        }
+        return successes;
+    }
+
+    if dynamic {
+        for i in sliced {
+            let mut coord = operands.get_coord(i);
+            let lat = coord[0].to_radians();
+            coord[1] = ellps.dynamic_height(lat, coord[1]);
+            operands.set_coord(i, &coord);
+            successes += 1;
+        }
+        return successes;
+    }
+
+    if orthometric {
+        for i in sliced {
+            let mut coord = operands.get_coord(i);
+            let lat = coord[0].to_radians();
+            coord[1] = ellps.orthometric_height(lat, coord[1], coord[2]);
+            operands.set_coord(i, &coord);
+            successes += 1;
+        }
+        return successes;
+    }
+
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let n = operands.len();
+    let sliced = 0..n;
+    let ellps = op.params.ellps(0);
+
+    let dynamic = op.params.boolean("dynamic");
+    let orthometric = op.params.boolean("orthometric");
+
+    let mut successes = 0_usize;
+
+    if dynamic {
+        for i in sliced {
+            let mut coord = operands.get_coord(i);
+            let lat = coord[0].to_radians();
+            coord[1] = ellps.normal_height_from_dynamic(lat, coord[1]);
+            operands.set_coord(i, &coord);
+            successes += 1;
+        }
+        return successes;
+    }
+
+    if orthometric {
+        for i in sliced {
+            let mut coord = operands.get_coord(i);
+            let lat = coord[0].to_radians();
+            coord[1] = ellps.normal_height_from_orthometric(lat, coord[1], coord[2]);
+            operands.set_coord(i, &coord);
+            successes += 1;
+        }
+        return successes;
+    }
+
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+// The input coordinates are (latitude, height, surface gravity), with latitude
+// in degrees. The `normal` flag replaces the latitude by the normal gravity at
+// the given height, while `dynamic` and `orthometric` replace the normal height
+// by the dynamic or the orthometric height. The latter needs the actual surface
+// gravity, in m/s²
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Flag { key: "normal" },
+    OpParameter::Flag { key: "dynamic" },
+    OpParameter::Flag { key: "orthometric" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") }
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    let flags = ["normal", "dynamic", "orthometric"];
+    let number_of_flags = flags.iter().filter(|&&key| params.boolean(key)).count();
+    if number_of_flags != 1 {
+        return Err(Error::MissingParam(
+            "gravity: must specify exactly one of flags normal/dynamic/orthometric".to_string(),
+        ));
+    }
+
+    // Check that we have a proper ellipsoid
+    let _ = Ellipsoid::named(params.text("ellps")?.as_str())?;
+
+    // Normal gravity is a one way street
+    let inverse = if params.boolean("normal") {
+        None
+    } else {
+        Some(InnerOp(inv))
+    };
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), inverse);
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gravity() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // Missing or too many flags, and unknown ellipsoid name
+        assert!(matches!(ctx.op("gravity"), Err(Error::MissingParam(_))));
+        let op = ctx.op("gravity normal dynamic");
+        assert!(matches!(op, Err(Error::MissingParam(_))));
+        let op = ctx.op("gravity ellps=non_existing normal");
+        assert!(matches!(op, Err(Error::NotFound(_, _))));
+
+        // Normal gravity at the equator, at 45°, and at the pole
+        let op = ctx.op("gravity normal ellps=GRS80")?;
+        let mut operands = [
+            Coor2D([0., 0.]),
+            Coor2D([45., 0.]),
+            Coor2D([90., 0.]),
+            Coor2D([55., 1000.]),
+        ];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!((operands[0][0] - 9.780_326_771_5).abs() < 1e-10);
+        assert!((operands[1][0] - 9.806_199_203).abs() < 1e-9);
+        assert!((operands[2][0] - 9.832_186_368_5).abs() < 1e-10);
+        assert!((operands[3][0] - 9.811_990_290_55).abs() < 1e-9);
+        assert!(matches!(
+            ctx.op("gravity normal inv"),
+            Err(Error::NonInvertible(_))
+        ));
+
+        // Dynamic heights
+        let op = ctx.op("gravity dynamic")?;
+        let mut operands = [Coor2D([55., 1000.])];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_eq!(operands[0][0], 55.);
+        assert!((operands[0][1] - 1_000.747_793_481).abs() < 1e-6);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!((operands[0][1] - 1000.).abs() < 1e-9);
+
+        // Orthometric heights, with the surface gravity in the third coordinate
+        let op = ctx.op("gravity orthometric")?;
+        let mut operands = [Coor3D([55., 1000., 9.81])];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!((operands[0][1] - 1_000.316_814_033).abs() < 1e-6);
+        assert_eq!(operands[0][2], 9.81);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!((operands[0][1] - 1000.).abs() < 1e-9);
+        Ok(())
+    }
+}
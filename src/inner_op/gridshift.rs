@@ -1,5 +1,6 @@
 /// Datum shift using grid interpolation.
 use crate::authoring::*;
+use std::sync::Arc;
 
 // ----- F O R W A R D --------------------------------------------------------------
 
@@ -10,30 +11,20 @@ fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
     let mut successes = 0_usize;
     let n = operands.len();
 
-    'points: for i in 0..n {
+    for i in 0..n {
         let mut coord = operands.get_coord(i);
 
-        for margin in [0.0, 0.5] {
-            for grid in grids.iter() {
-                if let Some(d) = grid.at(&coord, margin) {
-                    // Geoid
-                    if grid.bands() == 1 {
-                        coord[2] -= d[0];
-                        operands.set_coord(i, &coord);
-                        successes += 1;
-
-                        continue 'points;
-                    }
-
-                    // Datum shift
-                    coord[0] += d[0];
-                    coord[1] += d[1];
-                    operands.set_coord(i, &coord);
-                    successes += 1;
-
-                    continue 'points;
-                }
+        if let Some((d, grid)) = grid_lookup(grids, &coord) {
+            // Geoid, or datum shift
+            if grid.bands() == 1 {
+                coord[2] -= d[0];
+            } else {
+                coord[0] += d[0];
+                coord[1] += d[1];
             }
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
         }
 
         if use_null_grid {
@@ -57,60 +48,55 @@ fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
     let mut successes = 0_usize;
     let n = operands.len();
 
-    'points: for i in 0..n {
+    for i in 0..n {
         let mut coord = operands.get_coord(i);
 
-        for margin in [0.0, 0.5] {
-            for grid in grids.iter() {
-                if let Some(t) = grid.at(&coord, margin) {
-                    // Geoid
-                    if grid.bands() == 1 {
-                        coord[2] += t[0];
-                        operands.set_coord(i, &coord);
-                        successes += 1;
-
-                        continue 'points;
-                    }
-
-                    // Datum shift - here we need to iterate in the inverse case
-                    let mut t = coord - t;
-
-                    'iterate: for _ in 0..10 {
-                        if let Some(t2) = grid.at(&t, margin) {
-                            let d = t - coord + t2;
-                            t = t - d;
-                            // i.e. d.dot(d).sqrt() < 1e-10
-                            if d.dot(d) < 1e-20 {
-                                break 'iterate;
-                            }
-                            continue 'iterate;
-                        }
-
-                        if use_null_grid {
-                            successes += 1;
-                            break 'iterate;
-                        }
-
-                        // The iteration has wondered off the grid so we stomp on the coordinate
-                        t = Coor4D::nan();
-                        break 'iterate;
-                    }
-
-                    operands.set_coord(i, &t);
-                    successes += 1;
-
-                    continue 'points;
-                }
+        let Some((t, grid)) = grid_lookup(grids, &coord) else {
+            if use_null_grid {
+                successes += 1;
+                continue;
             }
-        }
 
-        if use_null_grid {
+            // No grid found so we stomp on the coordinate
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        };
+
+        // Geoid
+        if grid.bands() == 1 {
+            coord[2] += t[0];
+            operands.set_coord(i, &coord);
             successes += 1;
             continue;
         }
 
-        // No grid found so we stomp on the coordinate
-        operands.set_coord(i, &Coor4D::nan());
+        // Datum shift - here we need to iterate in the inverse case
+        let grid = std::slice::from_ref(grid);
+        let mut t = coord - t;
+
+        'iterate: for _ in 0..10 {
+            if let Some(t2) = grid_value(grid, &t) {
+                let d = t - coord + t2;
+                t = t - d;
+                // i.e. d.dot(d).sqrt() < 1e-10
+                if d.dot(d) < 1e-20 {
+                    break 'iterate;
+                }
+                continue 'iterate;
+            }
+
+            if use_null_grid {
+                successes += 1;
+                break 'iterate;
+            }
+
+            // The iteration has wondered off the grid so we stomp on the coordinate
+            t = Coor4D::nan();
+            break 'iterate;
+        }
+
+        operands.set_coord(i, &t);
+        successes += 1;
     }
     successes
 }
@@ -127,7 +113,36 @@ pub const GAMUT: [OpParameter; 3] = [
 pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
     let def = &parameters.definition;
     let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    load_grids(&mut params, ctx)?;
+
+    // Geoids have 1 band, and datum shifts 2 (angular) bands. Grids with more
+    // bands hold metric offsets or velocities, which we cannot apply here
+    if params.grids.iter().any(|grid| grid.bands() > 2) {
+        return Err(Error::BadParam("grids".to_string(), def.clone()));
+    }
+
+    let fwd = InnerOp(fwd);
+    let inv = InnerOp(inv);
+    let descriptor = OpDescriptor::new(def, fwd, Some(inv));
+    let steps = Vec::new();
+    let id = OpHandle::new();
+
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S --------------------------------------
+
+// Shared with the hgridshift and vgridshift operators
 
+/// Load the grids given by the `grids` parameter. Grids prefixed by `@` are
+/// optional, i.e. silently ignored if not found, and the pseudo-grid `null`
+/// indicates that points outside of the grids should be passed through unchanged
+pub(super) fn load_grids(params: &mut ParsedParameters, ctx: &dyn Context) -> Result<(), Error> {
     for mut grid_name in params.texts("grids")?.clone() {
         let optional = grid_name.starts_with('@');
         if optional {
@@ -148,19 +163,63 @@ pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
             }
         }
     }
+    Ok(())
+}
 
-    let fwd = InnerOp(fwd);
-    let inv = InnerOp(inv);
-    let descriptor = OpDescriptor::new(def, fwd, Some(inv));
-    let steps = Vec::new();
-    let id = OpHandle::new();
+/// The grid correction at `coord`, from the first grid containing it. Points
+/// just outside of all grids are extrapolated from a margin of half a grid cell
+pub(super) fn grid_value(grids: &[Arc<dyn Grid>], coord: &Coor4D) -> Option<Coor4D> {
+    grid_lookup(grids, coord).map(|(d, _)| d)
+}
 
-    Ok(Op {
-        descriptor,
-        params,
-        steps,
-        id,
-    })
+/// As [grid_value], but also returning the grid providing the correction
+fn grid_lookup<'a>(
+    grids: &'a [Arc<dyn Grid>],
+    coord: &Coor4D,
+) -> Option<(Coor4D, &'a Arc<dyn Grid>)> {
+    for margin in [0.0, 0.5] {
+        for grid in grids.iter() {
+            if let Some(d) = grid.at(coord, margin) {
+                return Some((d, grid));
+            }
+        }
+    }
+    None
+}
+
+/// Handle the PROJ style time window given by `t_epoch` and `t_final`: When
+/// both are given, the operation is only applied to coordinates observed
+/// before `t_epoch`, and only if `t_final` is after `t_epoch`. `t_final`
+/// may be given as `now`, meaning the current (decimal) year
+pub(super) fn time_window(params: &mut ParsedParameters, def: &str) -> Result<(), Error> {
+    let t_epoch = params.real("t_epoch")?;
+    let t_final = params.text("t_final")?;
+    let t_final = if t_final == "now" {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|_| Error::General("Unable to determine the current time"))?
+            .as_secs_f64();
+        1970. + seconds / (365.2425 * 86400.)
+    } else {
+        t_final
+            .parse::<f64>()
+            .map_err(|_| Error::BadParam("t_final".to_string(), def.to_string()))?
+    };
+    params.real.insert("t_final", t_final);
+    if t_epoch != 0. && t_final != 0. {
+        params.boolean.insert("time_restricted");
+    }
+    Ok(())
+}
+
+/// Is the operation to be applied at time `t`, according to the time window?
+pub(super) fn in_time_window(op: &Op, t: f64) -> bool {
+    if !op.params.boolean("time_restricted") {
+        return true;
+    }
+    let t_epoch = op.params.real("t_epoch").unwrap_or_default();
+    let t_final = op.params.real("t_final").unwrap_or_default();
+    t < t_epoch && t_final > t_epoch
 }
 
 // ----- T E S T S ------------------------------------------------------------------
@@ -189,6 +248,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn geotiff() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let op = ctx.op("gridshift grids=test_datum.tif")?;
+
+        // Outside of its subgrid, the GeoTIFF grid equals test.datum
+        let mut data = [Coor4D::geo(57., 10., 0., 0.)];
+        ctx.apply(op, Fwd, &mut data)?;
+        let res = data[0].to_geo();
+        assert!((res[0] - (57. + 57. / 3600.)).abs() < 1e-8);
+        assert!((res[1] - (10. + 10. / 3600.)).abs() < 1e-8);
+
+        // ...while the subgrid adds another arcsecond
+        let p = Coor4D::geo(55.5, 13., 0., 0.);
+        let mut data = [p];
+        ctx.apply(op, Fwd, &mut data)?;
+        let res = data[0].to_geo();
+        assert!((res[0] - (55.5 + 56.5 / 3600.)).abs() < 1e-8);
+        assert!((res[1] - (13. + 14. / 3600.)).abs() < 1e-8);
+
+        ctx.apply(op, Inv, &mut data)?;
+        assert!(data[0].hypot2(&p) < 1e-10);
+        Ok(())
+    }
+
     #[test]
     fn ntv2() -> Result<(), Error> {
         let mut ctx = Plain::default();
@@ -320,6 +404,8 @@ mod tests {
         let op = ctx.op("gridshift grids=missing.gsb");
         assert!(op.is_err());
 
+        // Metric 3 band grids cannot be used for datum shifts
+        assert!(ctx.op("gridshift grids=test.deformation").is_err());
         Ok(())
     }
 }
@@ -3,8 +3,13 @@
 /// space, transforming 3D cartesian coordinates between static and/or dynamic reference
 /// frames, e.g. from global reference frames to local static frames.
 ///
-/// While generally also applicable to 2D coordinates, this functionality is not yet
-/// implemented.
+/// When the rotation point, `px, py, pz`, is given, the rotation and scaling are carried
+/// out around that point, rather than around the origin. This is the Molodensky-Badekas
+/// variant (EPSG methods 1034 and 1061).
+///
+/// When the rotation angle `theta` is given, the 4 parameter 2D similarity transformation
+/// (EPSG method 9621) is carried out: The first two coordinates are rotated by `theta`
+/// around the third axis, scaled, and offset, while the third coordinate is left unchanged.
 use crate::authoring::*;
 
 // ----- C O M M O N -------------------------------------------------------------------
@@ -32,8 +37,12 @@ fn helmert_common(
     let M = op.params.series("ROTFLAT").unwrap();
     let mut ROT = [[M[0], M[1], M[2]], [M[3], M[4], M[5]], [M[6], M[7], M[8]]];
 
+    // The rotation point (Molodensky-Badekas), otherwise the origin
+    let P = op.params.series("P").unwrap();
+
     // Predicates
     let rotated = op.params.boolean("rotated");
+    let planar = op.params.boolean("planar");
     let dynamic = op.params.boolean("dynamic");
     let fixed_t = op.params.boolean("fixed_time");
     let exact = op.params.boolean("exact");
@@ -59,7 +68,7 @@ fn helmert_common(
                 TT[0] += dt * DT[0];
                 TT[1] += dt * DT[1];
                 TT[2] += dt * DT[2];
-                if rotated {
+                if rotated && !planar {
                     let RR = [R[0] + dt * DR[0], R[1] + dt * DR[1], R[2] + dt * DR[2]];
                     ROT = rotation_matrix(&RR, exact, position_vector);
                 }
@@ -70,24 +79,24 @@ fn helmert_common(
         // ----- Forward direction -----
 
         if direction == Direction::Fwd {
+            // Reduce to the rotation point
+            let (mut x, mut y, mut z) = (c[0] - P[0], c[1] - P[1], c[2] - P[2]);
+
+            // Rotate
             if rotated {
-                // Rotate
-                let x = c[0] * ROT[0][0] + c[1] * ROT[0][1] + c[2] * ROT[0][2];
-                let y = c[0] * ROT[1][0] + c[1] * ROT[1][1] + c[2] * ROT[1][2];
-                let z = c[0] * ROT[2][0] + c[1] * ROT[2][1] + c[2] * ROT[2][2];
-
-                // scale and offset
-                c[0] = SS * x + TT[0];
-                c[1] = SS * y + TT[1];
-                c[2] = SS * z + TT[2];
-                operands.set_coord(i, &c);
-                continue;
+                (x, y, z) = (
+                    x * ROT[0][0] + y * ROT[0][1] + z * ROT[0][2],
+                    x * ROT[1][0] + y * ROT[1][1] + z * ROT[1][2],
+                    x * ROT[2][0] + y * ROT[2][1] + z * ROT[2][2],
+                );
             }
 
-            // scale and offset without rotation
-            c[0] = SS * c[0] + TT[0];
-            c[1] = SS * c[1] + TT[1];
-            c[2] = SS * c[2] + TT[2];
+            // scale and offset - leaving the third coordinate alone in the 2D case
+            c[0] = SS * x + TT[0] + P[0];
+            c[1] = SS * y + TT[1] + P[1];
+            if !planar {
+                c[2] = SS * z + TT[2] + P[2];
+            }
             operands.set_coord(i, &c);
             continue;
         }
@@ -95,19 +104,24 @@ fn helmert_common(
         // ----- Inverse direction -----
 
         // Deoffset and unscale
-        let x = (c[0] - TT[0]) / SS;
-        let y = (c[1] - TT[1]) / SS;
-        let z = (c[2] - TT[2]) / SS;
+        let mut x = (c[0] - TT[0] - P[0]) / SS;
+        let mut y = (c[1] - TT[1] - P[1]) / SS;
+        let mut z = (c[2] - TT[2] - P[2]) / SS;
 
         // Inverse rotation by transposed multiplication
         if rotated {
-            c[0] = x * ROT[0][0] + y * ROT[1][0] + z * ROT[2][0];
-            c[1] = x * ROT[0][1] + y * ROT[1][1] + z * ROT[2][1];
-            c[2] = x * ROT[0][2] + y * ROT[1][2] + z * ROT[2][2];
-        } else {
-            c[0] = x;
-            c[1] = y;
-            c[2] = z;
+            (x, y, z) = (
+                x * ROT[0][0] + y * ROT[1][0] + z * ROT[2][0],
+                x * ROT[0][1] + y * ROT[1][1] + z * ROT[2][1],
+                x * ROT[0][2] + y * ROT[1][2] + z * ROT[2][2],
+            );
+        }
+
+        // Restore the rotation point
+        c[0] = x + P[0];
+        c[1] = y + P[1];
+        if !planar {
+            c[2] = z + P[2];
         }
         operands.set_coord(i, &c);
     }
@@ -129,7 +143,7 @@ fn helmert_inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) ->
 // ----- C O N S T R U C T O R ------------------------------------------------------
 
 #[rustfmt::skip]
-pub const GAMUT: [OpParameter; 19] = [
+pub const GAMUT: [OpParameter; 23] = [
     OpParameter::Flag { key: "inv" },
 
     // Translation
@@ -156,6 +170,14 @@ pub const GAMUT: [OpParameter; 19] = [
     OpParameter::Text { key: "convention", default: Some("") },
     OpParameter::Flag { key: "exact" },
 
+    // Rotation point (Molodensky-Badekas)
+    OpParameter::Real { key: "px", default: Some(0f64) },
+    OpParameter::Real { key: "py", default: Some(0f64) },
+    OpParameter::Real { key: "pz", default: Some(0f64) },
+
+    // Rotation angle (2D)
+    OpParameter::Real { key: "theta", default: Some(0f64) },
+
     // Scale and its time evoution
     OpParameter::Real { key: "s",  default: Some(0f64) },
     OpParameter::Real { key: "ds", default: Some(0f64) },  // TODO: scale by 1e-6
@@ -203,11 +225,29 @@ pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error>
         (drz / 3600.).to_radians(),
     ];
 
+    // The rotation point: Non-zero for Molodensky-Badekas
+    let P = [params.real("px")?, params.real("py")?, params.real("pz")?];
+    params.series.insert("P", Vec::from(P));
+
+    // The 2D case is selected by the rotation angle, and excludes the 3D parameters
+    let planar = params.given.contains_key("theta");
+    if planar {
+        let three_d = ["z", "dz", "rx", "ry", "rz", "drx", "dry", "drz", "pz"];
+        if three_d.iter().any(|&key| params.given.contains_key(key)) {
+            return Err(Error::Invalid(
+                "helmert: 'theta' (2D) cannot be combined with 3D parameters".to_string(),
+            ));
+        }
+        params.boolean.insert("planar");
+    }
+
     // Handling of rotations: position vector vs. coordinate frame conventions.
     let convention = params.text("convention")?;
     let rotated = !(R == [0., 0., 0.] && DR == [0., 0., 0.]);
     let mut position_vector = true;
-    if rotated {
+    if planar {
+        params.boolean.insert("rotated");
+    } else if rotated {
         if !["position_vector", "coordinate_frame"].contains(&convention.as_str()) {
             return Err(Error::BadParam("convention".to_string(), convention));
         }
@@ -256,8 +296,14 @@ pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error>
     params.real.insert("S", S);
     params.real.insert("DS", DS);
 
-    // The rotation matrix is a 3x3 symmetric matrix
-    let ROT = rotation_matrix(&R, exact, position_vector);
+    // The rotation matrix is a 3x3 symmetric matrix - or, in the 2D case, a rotation
+    // of the axes by theta around the third axis (EPSG 9621)
+    let ROT = if planar {
+        let (s, c) = (params.real("theta")? / 3600.).to_radians().sin_cos();
+        [[c, s, 0.], [-s, c, 0.], [0., 0., 1.]]
+    } else {
+        rotation_matrix(&R, exact, position_vector)
+    };
 
     // We need to turn the 3x3 into 1x9 to make it fit into the "series" store
     let mut ROTFLAT = Vec::from(ROT[0]);
@@ -435,4 +481,64 @@ mod tests {
 
         Ok(())
     }
+
+    // EPSG Guidance Note 7-2, example for method 1061: Molodensky-Badekas
+    // (coordinate frame rotation), La Canoa to REGVEN
+    #[test]
+    fn molodensky_badekas() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let definition = "
+            helmert convention = coordinate_frame
+            x = -270.933  rx = -5.266  px =  2464351.59
+            y =  115.599  ry = -1.238  py = -5783466.61
+            z = -360.226  rz =  2.381  pz =   974809.81
+            s = -5.109  exact
+        ";
+        let op = ctx.op(definition)?;
+
+        let la_canoa = Coor4D::raw(2550408.96, -5749912.26, 1054891.11, 0.);
+        let regven = Coor4D::raw(2550138.46, -5749799.87, 1054530.82, 0.);
+
+        // The published result is given to the cm
+        let mut operands = [la_canoa];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!(regven.hypot3(&operands[0]) < 0.01);
+
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(la_canoa.hypot3(&operands[0]) < 1e-8);
+        Ok(())
+    }
+
+    #[test]
+    fn planar() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // A quarter turn of the axes maps the first axis onto the negative second
+        let op = ctx.op("helmert theta=324000")?;
+        let mut operands = [Coor4D::raw(1., 0., 42., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!(operands[0][0].abs() < 1e-12);
+        assert!((operands[0][1] + 1.).abs() < 1e-12);
+        assert_eq!(operands[0][2], 42.);
+
+        // Rotation, scale and offset - the third coordinate is left alone
+        let op = ctx.op("helmert x=100 y=200 theta=3600 s=1000")?;
+        let p = Coor4D::raw(1000., 2000., 42., 0.);
+        let (s, c) = 1f64.to_radians().sin_cos();
+        let x = 1.001 * (c * 1000. + s * 2000.) + 100.;
+        let y = 1.001 * (-s * 1000. + c * 2000.) + 200.;
+        let mut operands = [p];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!((operands[0][0] - x).abs() < 1e-9);
+        assert!((operands[0][1] - y).abs() < 1e-9);
+        assert_eq!(operands[0][2], 42.);
+
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(p.hypot3(&operands[0]) < 1e-9);
+
+        // The 2D case does not mix with 3D parameters
+        assert!(ctx.op("helmert theta=3600 rx=1").is_err());
+        assert!(ctx.op("helmert theta=3600 z=1").is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,198 @@
+//! Horizontal datum shift using grid interpolation, following the PROJ operator
+//! of the same name.
+//!
+//! In contrast to `gridshift`, which selects between horizontal and vertical
+//! shifts by the number of bands of the grid, `hgridshift` always carries out a
+//! horizontal shift, using the first two bands of the grid.
+use super::gridshift::{grid_value, in_time_window, load_grids, time_window};
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let grids = &op.params.grids;
+    let use_null_grid = op.params.boolean("null_grid");
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        if !in_time_window(op, coord[3]) {
+            successes += 1;
+            continue;
+        }
+
+        if let Some(d) = grid_value(grids, &coord) {
+            coord[0] += d[0];
+            coord[1] += d[1];
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        if use_null_grid {
+            successes += 1;
+            continue;
+        }
+
+        // No grid found so we stomp on the coordinate
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let grids = &op.params.grids;
+    let use_null_grid = op.params.boolean("null_grid");
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    'points: for i in 0..n {
+        let coord = operands.get_coord(i);
+        if !in_time_window(op, coord[3]) {
+            successes += 1;
+            continue;
+        }
+
+        let Some(d) = grid_value(grids, &coord) else {
+            if use_null_grid {
+                successes += 1;
+                continue;
+            }
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        };
+
+        // The grid is given in the source system, so we need to iterate
+        let mut t = coord;
+        t[0] -= d[0];
+        t[1] -= d[1];
+        for _ in 0..10 {
+            let Some(d) = grid_value(grids, &t) else {
+                break;
+            };
+            let dx = t[0] + d[0] - coord[0];
+            let dy = t[1] + d[1] - coord[1];
+            t[0] -= dx;
+            t[1] -= dy;
+            if dx.hypot(dy) < 1e-12 {
+                operands.set_coord(i, &t);
+                successes += 1;
+                continue 'points;
+            }
+        }
+
+        // The iteration did not converge, or wandered off the grids
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 4] = [
+    OpParameter::Flag  { key: "inv" },
+    OpParameter::Texts { key: "grids",   default: None },
+    OpParameter::Real  { key: "t_epoch", default: Some(0_f64) },
+    OpParameter::Text  { key: "t_final", default: Some("0") },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    load_grids(&mut params, ctx)?;
+    time_window(&mut params, def)?;
+
+    // A horizontal shift needs both a longitude and a latitude band, while
+    // grids with more bands hold metric offsets or velocities
+    if params.grids.iter().any(|grid| grid.bands() != 2) {
+        return Err(Error::BadParam("grids".to_string(), def.clone()));
+    }
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn hgridshift() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let op = ctx.op("hgridshift grids=test.datum")?;
+        let cph = Coor4D::geo(55., 12., 0., 0.);
+        let mut data = [cph];
+
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 1);
+        let res = data[0].to_geo();
+        assert_float_eq!(res[0], 55.015278, abs <= 1e-6);
+        assert_float_eq!(res[1], 12.003333, abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut data)?, 1);
+        assert_float_eq!(data[0].0, cph.0, abs_all <= 1e-10);
+
+        // Geoid grids have only one band
+        assert!(ctx.op("hgridshift grids=test.geoid").is_err());
+        // ...and deformation grids hold metric values
+        assert!(ctx.op("hgridshift grids=test_deformation.tif").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn null_grid() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let ldn = Coor4D::geo(51.505, -0.09, 0., 0.);
+
+        let op = ctx.op("hgridshift grids=@missing.gsb, test.datum")?;
+        let mut data = [ldn];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 0);
+        assert!(data[0][0].is_nan());
+
+        let op = ctx.op("hgridshift grids=test.datum, @null")?;
+        let mut data = [ldn];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 1);
+        assert_eq!(data[0], ldn);
+        assert_eq!(ctx.apply(op, Inv, &mut data)?, 1);
+        assert_eq!(data[0], ldn);
+        Ok(())
+    }
+
+    #[test]
+    fn time_window() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let op = ctx.op("hgridshift grids=test.datum t_epoch=2000 t_final=now")?;
+
+        // Only observations before t_epoch are shifted
+        let before = Coor4D::geo(55., 12., 0., 1999.);
+        let after = Coor4D::geo(55., 12., 0., 2001.);
+        let mut data = [before, after];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 2);
+        assert!((data[0][1] - before[1]).abs() > 1e-6);
+        assert_eq!(data[1], after);
+
+        // If t_final is before t_epoch, nothing happens
+        let op = ctx.op("hgridshift grids=test.datum t_epoch=2000 t_final=1990")?;
+        let mut data = [before];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 1);
+        assert_eq!(data[0], before);
+
+        assert!(ctx
+            .op("hgridshift grids=test.datum t_epoch=2000 t_final=then")
+            .is_err());
+        Ok(())
+    }
+}
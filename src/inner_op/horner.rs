@@ -0,0 +1,410 @@
+//! Polynomial transformations in the plane, following the PROJ operator of the
+//! same name, and the Engsager/Poder tradition of legacy system conversions.
+//!
+//! In the real case, the output coordinates are given by two bivariate
+//! polynomials of degree `deg`, in the coordinates reduced to `fwd_origin`
+//! (forward), or `inv_origin` (inverse):
+//!
+//! ```txt
+//! u' = Σ fwd_u[k] · eⁱ · nʲ,    i + j ≤ deg,  i varying fastest
+//! v' = Σ fwd_v[k] · nʲ · eⁱ,    i + j ≤ deg,  j varying fastest
+//! ```
+//!
+//! i.e. the coefficient series of each polynomial holds (deg+1)(deg+2)/2
+//! elements. In the complex case, the (deg+1) complex coefficients of `fwd_c`
+//! are given as pairs of (northing, easting) components, and the output is
+//! given by the complex polynomial `N' + iE' = Σ c[k] · (n + ie)ᵏ`.
+//!
+//! If the inverse coefficients are not given, the inverse is computed by
+//! iteration, until the forward polynomial reproduces the input to within
+//! `inv_tolerance`.
+use crate::authoring::*;
+use crate::math::taylor::horner;
+
+// Limit for the number of iterations of the iterative inverse
+const MAX_ITERATIONS: usize = 20;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let Some(polynomial) = Polynomial::new(&op.params, Fwd) else {
+        return 0;
+    };
+    let Ok(origin) = op.params.series("fwd_origin") else {
+        return 0;
+    };
+    evaluate(&polynomial, origin, op, operands)
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    if !op.params.boolean("iterative") {
+        let Some(polynomial) = Polynomial::new(&op.params, Inv) else {
+            return 0;
+        };
+        let Ok(origin) = op.params.series("inv_origin") else {
+            return 0;
+        };
+        return evaluate(&polynomial, origin, op, operands);
+    }
+
+    let Some(polynomial) = Polynomial::new(&op.params, Fwd) else {
+        return 0;
+    };
+    let Ok(origin) = op.params.series("fwd_origin") else {
+        return 0;
+    };
+    let Ok(range) = op.params.real("range") else {
+        return 0;
+    };
+    let Ok(tolerance) = op.params.real("inv_tolerance") else {
+        return 0;
+    };
+
+    // The linear part of the forward polynomial, used for the Newton steps
+    let [a, b, c, d] = polynomial.linear();
+    let det = a * d - b * c;
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    'points: for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let (u, v) = (coord[0], coord[1]);
+
+        // Start at the origin of the forward polynomial
+        let (mut e, mut n) = (0., 0.);
+        for _ in 0..MAX_ITERATIONS {
+            let (uu, vv) = polynomial.evaluate(e, n);
+            let (du, dv) = (u - uu, v - vv);
+            if du.hypot(dv) < tolerance {
+                if e.abs() > range || n.abs() > range {
+                    break;
+                }
+                coord[0] = e + origin[0];
+                coord[1] = n + origin[1];
+                operands.set_coord(i, &coord);
+                successes += 1;
+                continue 'points;
+            }
+            e += (d * du - b * dv) / det;
+            n += (a * dv - c * du) / det;
+        }
+
+        // Outside of the range, or no convergence
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// The forward and the coefficient based inverse cases differ only in the
+// polynomial and origin used
+fn evaluate(
+    polynomial: &Polynomial,
+    origin: &[f64],
+    op: &Op,
+    operands: &mut dyn CoordinateSet,
+) -> usize {
+    let Ok(range) = op.params.real("range") else {
+        return 0;
+    };
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let (e, n) = (coord[0] - origin[0], coord[1] - origin[1]);
+        if e.abs() > range || n.abs() > range {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        (coord[0], coord[1]) = polynomial.evaluate(e, n);
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 12] = [
+    OpParameter::Flag    { key: "inv" },
+    OpParameter::Natural { key: "deg",           default: None },
+    OpParameter::Real    { key: "range",         default: Some(500_000_f64) },
+    OpParameter::Series  { key: "fwd_origin",    default: Some("0,0") },
+    OpParameter::Series  { key: "inv_origin",    default: Some("0,0") },
+
+    // Real polynomials
+    OpParameter::Series  { key: "fwd_u",         default: Some("") },
+    OpParameter::Series  { key: "fwd_v",         default: Some("") },
+    OpParameter::Series  { key: "inv_u",         default: Some("") },
+    OpParameter::Series  { key: "inv_v",         default: Some("") },
+
+    // Complex polynomials
+    OpParameter::Series  { key: "fwd_c",         default: Some("") },
+    OpParameter::Series  { key: "inv_c",         default: Some("") },
+
+    // Iterative inverse
+    OpParameter::Real    { key: "inv_tolerance", default: Some(0.001) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    let deg = params.natural("deg")?;
+
+    for key in ["fwd_origin", "inv_origin"] {
+        if params.series(key)?.len() != 2 {
+            return Err(Error::BadParam(key.to_string(), def.clone()));
+        }
+    }
+
+    // Complex mode is selected by the presence of the complex coefficients
+    let complex = params.series("fwd_c").is_ok();
+    let real_keys = ["fwd_u", "fwd_v", "inv_u", "inv_v"];
+    if complex && real_keys.iter().any(|&key| params.series(key).is_ok()) {
+        return Err(Error::Invalid(
+            "horner: Real and complex coefficients cannot be combined".to_string(),
+        ));
+    }
+    if complex {
+        params.boolean.insert("complex");
+    }
+
+    // Check the number of coefficients
+    let (keys, count) = if complex {
+        (&["fwd_c", "inv_c"][..], 2 * (deg + 1))
+    } else {
+        (&real_keys[..], (deg + 1) * (deg + 2) / 2)
+    };
+    for key in keys {
+        match params.series(key) {
+            Ok(coefficients) if coefficients.len() != count => {
+                return Err(Error::Unexpected {
+                    message: format!("horner: Wrong number of coefficients in '{key}'"),
+                    expected: count.to_string(),
+                    found: coefficients.len().to_string(),
+                });
+            }
+            Err(_) if key.starts_with("fwd") => return Err(Error::MissingParam(key.to_string())),
+            _ => (),
+        }
+    }
+
+    // Without inverse coefficients, we invert by iteration, which requires
+    // a non-singular linear part of the forward polynomial
+    let inverse = keys.iter().filter(|key| key.starts_with("inv"));
+    if inverse.clone().all(|key| params.series(key).is_err()) {
+        let singular = deg == 0 || {
+            let Some(polynomial) = Polynomial::new(&params, Fwd) else {
+                return Err(Error::Invalid(def.clone()));
+            };
+            let [a, b, c, d] = polynomial.linear();
+            a * d - b * c == 0.
+        };
+        if singular {
+            return Err(Error::Invalid(
+                "horner: Cannot invert - singular linear part of the polynomial".to_string(),
+            ));
+        }
+        params.boolean.insert("iterative");
+    } else if let Some(key) = inverse.clone().find(|key| params.series(key).is_err()) {
+        return Err(Error::MissingParam(key.to_string()));
+    }
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// The polynomial of one direction. In the complex case, `u` holds the
+// coefficients, and `v` is empty
+struct Polynomial<'a> {
+    degree: usize,
+    complex: bool,
+    u: &'a [f64],
+    v: &'a [f64],
+}
+
+impl<'a> Polynomial<'a> {
+    fn new(params: &'a ParsedParameters, direction: Direction) -> Option<Self> {
+        let degree = params.natural("deg").ok()?;
+        let complex = params.boolean("complex");
+        let prefix = if direction == Fwd { "fwd" } else { "inv" };
+        if complex {
+            let u = params.series(&format!("{prefix}_c")).ok()?;
+            return Some(Polynomial {
+                degree,
+                complex,
+                u,
+                v: &[],
+            });
+        }
+        let u = params.series(&format!("{prefix}_u")).ok()?;
+        let v = params.series(&format!("{prefix}_v")).ok()?;
+        Some(Polynomial {
+            degree,
+            complex,
+            u,
+            v,
+        })
+    }
+
+    // Evaluate the polynomial at the reduced coordinates (e, n)
+    fn evaluate(&self, e: f64, n: f64) -> (f64, f64) {
+        if self.complex {
+            // Horner's scheme for N + iE = Σ c[k] · (n + ie)ᵏ, summing the
+            // tiny high order elements first
+            let mut coefficients = self.u.chunks_exact(2).rev();
+            let Some(&[mut nn, mut ee]) = coefficients.next() else {
+                return (f64::NAN, f64::NAN);
+            };
+            for c in coefficients {
+                (nn, ee) = (n * nn - e * ee + c[0], n * ee + e * nn + c[1]);
+            }
+            return (ee, nn);
+        }
+        (
+            self.double_horner(self.u, n, e),
+            self.double_horner(self.v, e, n),
+        )
+    }
+
+    // Evaluate Σ c · slowⁱ · fastʲ, with the coefficients of each power of
+    // `slow` given in rows of decreasing length, as ascending powers of `fast`
+    fn double_horner(&self, coefficients: &[f64], slow: f64, fast: f64) -> f64 {
+        let deg = self.degree;
+        let mut value = 0_f64;
+        for i in (0..=deg).rev() {
+            let start = i * (deg + 1) - i * i.saturating_sub(1) / 2;
+            let row = &coefficients[start..start + deg + 1 - i];
+            value = value.mul_add(slow, horner(fast, row));
+        }
+        value
+    }
+
+    // The linear part, [du/de, du/dn, dv/de, dv/dn], of the polynomial
+    fn linear(&self) -> [f64; 4] {
+        if self.complex {
+            let (re, im) = (self.u[2], self.u[3]);
+            return [re, im, -im, re];
+        }
+        let next_row = self.degree + 1;
+        [self.u[1], self.u[next_row], self.v[next_row], self.v[1]]
+    }
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let definition = "
+            horner deg=2 fwd_origin=1000,2000
+            fwd_u=500,1,0.001,0.002,0.0001,0.00001
+            fwd_v=600,1,0.002,-0.003,0.0002,0.0003
+        ";
+        let op = ctx.op(definition)?;
+
+        // e = 10, n = 20:
+        // u = 500 + 10 + 0.001·100 + 0.002·20 + 0.0001·200 + 0.00001·400
+        // v = 600 + 20 + 0.002·400 - 0.003·10 + 0.0002·200 + 0.0003·100
+        let p = Coor4D::raw(1010., 2020., 3., 4.);
+        let mut operands = [p];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert!((operands[0][0] - 510.164).abs() < 1e-9);
+        assert!((operands[0][1] - 620.84).abs() < 1e-9);
+        assert_eq!(operands[0][2], 3.);
+        assert_eq!(operands[0][3], 4.);
+
+        // No inverse coefficients given, so we iterate
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert!(p.hypot2(&operands[0]) < 1e-3);
+
+        // ... unless we do not want to
+        let op = ctx.op("horner deg=1 fwd_origin=1000,2000 inv_origin=100,200
+             fwd_u=100,2,0  fwd_v=200,2,0
+             inv_u=1000,0.5,0  inv_v=2000,0.5,0")?;
+        let mut operands = [p];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_eq!(operands[0][0], 120.);
+        assert_eq!(operands[0][1], 240.);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert_eq!(operands[0], p);
+
+        // Outside of the range
+        let op = ctx.op(&format!("{definition} range=15"))?;
+        let mut operands = [p];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn complex() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // N + iE = 600 + 500i + i·(n + ie), i.e. a quarter turn and an offset
+        let op = ctx.op("horner deg=1 fwd_c=600,500,0,1")?;
+        let mut operands = [Coor4D::raw(10., 20., 0., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_eq!(operands[0][0], 520.);
+        assert_eq!(operands[0][1], 590.);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(Coor4D::raw(10., 20., 0., 0.).hypot2(&operands[0]) < 1e-3);
+
+        // Conversion between two Danish legacy systems, with forward and
+        // inverse coefficients from PROJ's documentation
+        let definition = "
+            horner range=500000 deg=3
+            fwd_origin=4.94690026817276e+05,6.13342113183056e+06
+            inv_origin=6.19480258923588e+05,6.13258568148837e+06
+            fwd_c=6.13258562111350e+06,6.19480105709997e+05,9.99378966275206e-01,-2.82153291753490e-02,-2.27089979140026e-10,-1.77019590701470e-09,1.08522286274070e-14,2.11430298751604e-15
+            inv_c=6.13342118787027e+06,4.94690181709311e+05,9.99824464710368e-01,2.82279070814774e-02,7.66123542220864e-11,1.78425334628927e-09,-1.05584823306400e-14,-3.32554258683744e-15
+        ";
+        let op = ctx.op(definition)?;
+        let p = Coor4D::raw(500_000., 6_100_000., 0., 0.);
+        let mut operands = [p];
+        ctx.apply(op, Fwd, &mut operands)?;
+        let q = operands[0];
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(p.hypot2(&operands[0]) < 1e-3);
+
+        // The iterative inverse agrees with the coefficient based
+        let iterative = definition.split("inv_c").next().unwrap();
+        let op = ctx.op(&format!("{iterative} inv_tolerance=1e-6"))?;
+        let mut operands = [q];
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(p.hypot2(&operands[0]) < 1e-3);
+        Ok(())
+    }
+
+    #[test]
+    fn bad_parameters() {
+        let mut ctx = Minimal::default();
+        assert!(ctx.op("horner fwd_u=1,2,3 fwd_v=1,2,3").is_err());
+        assert!(ctx.op("horner deg=1 fwd_u=1,2,3").is_err());
+        assert!(ctx.op("horner deg=1 fwd_u=1,2 fwd_v=1,2,3").is_err());
+        assert!(ctx
+            .op("horner deg=1 fwd_u=1,2,3 fwd_v=1,2,3 inv_u=1,2,3")
+            .is_err());
+        assert!(ctx
+            .op("horner deg=1 fwd_u=1,2,3 fwd_v=1,2,3 fwd_c=1,2,3,4")
+            .is_err());
+        assert!(ctx.op("horner deg=1 fwd_u=1,0,0 fwd_v=1,0,0").is_err());
+        assert!(ctx.op("horner deg=1 fwd_origin=1 fwd_c=1,2,3,4").is_err());
+    }
+}
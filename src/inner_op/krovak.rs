@@ -0,0 +1,361 @@
+//! Krovak oblique conformal conic, as used in the Czech and Slovak national grids.
+//!
+//! Follows [IOGP, 2019](crate::Bibliography::Iogp19), pp. 29-35, i.e. EPSG
+//! methods 9819 (Krovak), 1041 (Krovak North Orientated), 1042 (Krovak
+//! Modified) and 1043 (Krovak Modified North Orientated).
+//!
+//! For compatibility with PROJ, the default output is *north orientated*,
+//! i.e. (easting, northing) = (-westing, -southing). The `czech` flag selects
+//! the classical (westing, southing) output. The false easting and northing
+//! are applied to the westing and southing before a possible change of sign,
+//! as specified by IOGP (this is only significant for the modified variants,
+//! since the classical system uses a false origin of (0, 0)).
+use crate::authoring::*;
+use std::f64::consts::FRAC_PI_4;
+
+const MAX_ITERATIONS: usize = 15;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(b) = op.params.real("b") else { return 0 };
+    let Ok(n) = op.params.real("n") else { return 0 };
+    let Ok(t_0) = op.params.real("t_0") else {
+        return 0;
+    };
+    let Ok(r_0_tan) = op.params.real("r_0_tan") else {
+        return 0;
+    };
+    let Ok(alpha) = op.params.real("alpha") else {
+        return 0;
+    };
+    let (sin_alpha, cos_alpha) = alpha.to_radians().sin_cos();
+    let modified = op.params.boolean("modified");
+    let sign = if op.params.boolean("czech") { 1. } else { -1. };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0];
+        let phi = coord[1];
+        let esinphi = e * phi.sin();
+
+        // Conformal latitude, U, and longitude, V, on the Gaussian sphere
+        let u = 2.
+            * ((t_0 * (phi / 2. + FRAC_PI_4).tan().powf(b)
+                / ((1. + esinphi) / (1. - esinphi)).powf(e * b / 2.))
+            .atan()
+                - FRAC_PI_4);
+        let v = b * (lon_0 - lam);
+        let (sin_u, cos_u) = u.sin_cos();
+
+        // ...then rotate to the oblique aspect, and project onto the cone
+        let t = (cos_alpha * sin_u + sin_alpha * cos_u * v.cos()).asin();
+        let d = (cos_u * v.sin() / t.cos()).asin();
+        let theta = n * d;
+        let r = r_0_tan / (t / 2. + FRAC_PI_4).tan().powf(n);
+        let mut southing = r * theta.cos();
+        let mut westing = r * theta.sin();
+
+        if modified {
+            let (dx, dy) = modified_krovak_correction(southing, westing);
+            southing -= dx;
+            westing -= dy;
+        }
+
+        coord[0] = sign * (westing + x_0);
+        coord[1] = sign * (southing + y_0);
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let Ok(b) = op.params.real("b") else { return 0 };
+    let Ok(n) = op.params.real("n") else { return 0 };
+    let Ok(t_0) = op.params.real("t_0") else {
+        return 0;
+    };
+    let Ok(r_0_tan) = op.params.real("r_0_tan") else {
+        return 0;
+    };
+    let Ok(alpha) = op.params.real("alpha") else {
+        return 0;
+    };
+    let (sin_alpha, cos_alpha) = alpha.to_radians().sin_cos();
+    let modified = op.params.boolean("modified");
+    let sign = if op.params.boolean("czech") { 1. } else { -1. };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    'points: for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let mut westing = sign * coord[0] - x_0;
+        let mut southing = sign * coord[1] - y_0;
+
+        // The correction polynomial is a function of the uncorrected
+        // coordinates, so we must solve for those by fixed point iteration
+        if modified {
+            let (observed_southing, observed_westing) = (southing, westing);
+            for _ in 0..MAX_ITERATIONS {
+                let (dx, dy) = modified_krovak_correction(southing, westing);
+                let (next_southing, next_westing) = (observed_southing + dx, observed_westing + dy);
+                let change = (next_southing - southing).hypot(next_westing - westing);
+                (southing, westing) = (next_southing, next_westing);
+                if change < 1e-6 {
+                    break;
+                }
+            }
+        }
+
+        // From the cone to the oblique sphere...
+        let r = southing.hypot(westing);
+        let theta = westing.atan2(southing);
+        let d = theta / n;
+        let t = 2. * ((r_0_tan / r).powf(1. / n).atan() - FRAC_PI_4);
+
+        // ...and back to the normal aspect
+        let (sin_t, cos_t) = t.sin_cos();
+        let u = (cos_alpha * sin_t - sin_alpha * cos_t * d.cos()).asin();
+        let v = (cos_t * d.sin() / u.cos()).asin();
+
+        // Finally, from the Gaussian sphere to the ellipsoid
+        let k = (u / 2. + FRAC_PI_4).tan().powf(1. / b) / t_0.powf(1. / b);
+        let mut phi = u;
+        for _ in 0..MAX_ITERATIONS {
+            let esinphi = e * phi.sin();
+            let next =
+                2. * ((k * ((1. + esinphi) / (1. - esinphi)).powf(e / 2.)).atan() - FRAC_PI_4);
+            let change = (next - phi).abs();
+            phi = next;
+            if change < 1e-14 {
+                coord[0] = angular::normalize_symmetric(lon_0 - v / b);
+                coord[1] = phi;
+                operands.set_coord(i, &coord);
+                successes += 1;
+                continue 'points;
+            }
+        }
+
+        // No convergence
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+// The defaults are those of the S-JTSK system, i.e. the Bessel ellipsoid,
+// and the longitude of origin (42°30' E of Ferro) referred to Greenwich.
+// Note that the context global ellipsoid takes precedence over the default
+// given here, so `new` checks for that case explicitly
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 11] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Flag { key: "czech" },
+    OpParameter::Flag { key: "modified" },
+    OpParameter::Text { key: "ellps",  default: Some("bessel") },
+
+    OpParameter::Real { key: "lat_0",  default: Some(49.5) },
+    OpParameter::Real { key: "lon_0",  default: Some(24.833333333333333) },
+    OpParameter::Real { key: "alpha",  default: Some(30.288139752777778) },
+    OpParameter::Real { key: "lat_ts", default: Some(78.5) },
+
+    OpParameter::Real { key: "k_0",    default: Some(0.9999) },
+    OpParameter::Real { key: "x_0",    default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",    default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    // Unless given explicitly (or by a macro), the ellipsoid is Bessel's,
+    // rather than the context global
+    let global = ctx.globals().get("ellps").cloned();
+    if !params.given.contains_key("ellps") && parameters.globals.get("ellps") == global.as_ref() {
+        params.text.insert("ellps", "bessel".to_string());
+    }
+
+    let ellps = params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let e = ellps.eccentricity();
+    let es = ellps.eccentricity_squared();
+    let k_0 = params.k(0);
+
+    // Latitude of the projection center, and of the pseudo standard parallel
+    let phi_c = params.lat(0).to_radians();
+    let phi_p = params.real("lat_ts")?.to_radians();
+    if phi_p.sin().abs() < 1e-10 {
+        return Err(Error::BadParam("lat_ts".to_string(), def.clone()));
+    }
+    let (sin_phi_c, cos_phi_c) = phi_c.sin_cos();
+
+    // Radius and exponent of the conformal mapping to the Gaussian sphere
+    let aa = a * (1. - es).sqrt() / (1. - es * sin_phi_c * sin_phi_c);
+    let b = (1. + es * cos_phi_c.powi(4) / (1. - es)).sqrt();
+    let gamma_0 = (sin_phi_c / b).asin();
+    let esinphi_c = e * sin_phi_c;
+    let t_0 = (FRAC_PI_4 + gamma_0 / 2.).tan()
+        * ((1. + esinphi_c) / (1. - esinphi_c)).powf(e * b / 2.)
+        / (FRAC_PI_4 + phi_c / 2.).tan().powf(b);
+
+    // The cone constant, and the radius of the pseudo standard parallel
+    let n = phi_p.sin();
+    let r_0 = k_0 * aa / phi_p.tan();
+
+    params.real.insert("b", b);
+    params.real.insert("n", n);
+    params.real.insert("t_0", t_0);
+    params
+        .real
+        .insert("r_0_tan", r_0 * (FRAC_PI_4 + phi_p / 2.).tan().powf(n));
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- C O N S T R U C T O R,   P R O J   C O M P A T I B I L I T Y ----------------
+
+/// PROJ's `mod_krovak`, i.e. `krovak modified`
+pub fn modified(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let mut op = new(parameters, ctx)?;
+    op.params.boolean.insert("modified");
+    Ok(op)
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// Evaluation point and coefficients of the correction polynomial for the
+// modified Krovak projection, as given by IOGP (EPSG dataset, S-JTSK/05)
+const MODIFIED_ORIGIN: (f64, f64) = (1_089_000., 654_000.);
+#[rustfmt::skip]
+const MODIFIED_COEFFICIENTS: [f64; 10] = [
+    2.946529277e-02,  2.515965696e-02,  1.193845912e-07, -4.668270147e-07,
+    9.233980362e-12,  1.523735715e-12,  1.696780024e-18,  4.408314235e-18,
+   -8.331083518e-24, -3.689471323e-24,
+];
+
+/// The (southing, westing) corrections, (dX, dY), for the modified Krovak
+/// projection, computed from the uncorrected (southing, westing)
+fn modified_krovak_correction(southing: f64, westing: f64) -> (f64, f64) {
+    let [c1, c2, c3, c4, c5, c6, c7, c8, c9, c10] = MODIFIED_COEFFICIENTS;
+    let xr = southing - MODIFIED_ORIGIN.0;
+    let yr = westing - MODIFIED_ORIGIN.1;
+    let (xr2, yr2) = (xr * xr, yr * yr);
+
+    let dx =
+        c1 + c3 * xr - c4 * yr - 2. * c6 * xr * yr + c5 * (xr2 - yr2) + c7 * xr * (xr2 - 3. * yr2)
+            - c8 * yr * (3. * xr2 - yr2)
+            + 4. * c9 * xr * yr * (xr2 - yr2)
+            + c10 * (xr2 * xr2 + yr2 * yr2 - 6. * xr2 * yr2);
+
+    let dy = c2
+        + c3 * yr
+        + c4 * xr
+        + 2. * c5 * xr * yr
+        + c6 * (xr2 - yr2)
+        + c8 * xr * (xr2 - 3. * yr2)
+        + c7 * yr * (3. * xr2 - yr2)
+        - 4. * c10 * xr * yr * (xr2 - yr2)
+        + c9 * (xr2 * xr2 + yr2 * yr2 - 6. * xr2 * yr2);
+
+    (dx, dy)
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    // The IOGP test point, given in the Greenwich longitude convention
+    fn iogp_test_point() -> [Coor4D; 1] {
+        let lat = angular::dms_to_dd(50, 12, 32.442);
+        let lon = angular::dms_to_dd(16, 50, 59.179);
+        [Coor4D::geo(lat, lon, 0., 0.)]
+    }
+
+    #[test]
+    fn krovak() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let geo = iogp_test_point();
+
+        // EPSG:5513 S-JTSK / Krovak. The IOGP example for method 9819,
+        // which is fully described by the defaults
+        let op = ctx.op("krovak czech")?;
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_eq!(ctx.params(op, 0)?.text("ellps")?, "bessel");
+        let grs80 = ctx.op("krovak czech ellps=GRS80")?;
+        assert_eq!(ctx.params(grs80, 0)?.text("ellps")?, "GRS80");
+        assert_float_eq!(operands[0][0], 568_991.00, abs <= 0.01);
+        assert_float_eq!(operands[0][1], 1_050_538.63, abs <= 0.01);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+
+        // EPSG:5514 S-JTSK / Krovak East North. The IOGP example for method 1041,
+        // written out explicitly, in the PROJ syntax used by EPSG
+        let definition = parse_proj(
+            "+proj=krovak +lat_0=49.5 +lon_0=24.8333333333333 +alpha=30.2881397527778 +k=0.9999 +x_0=0 +y_0=0 +ellps=bessel",
+        )?;
+        let op = ctx.op(&definition)?;
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0][0], -568_991.00, abs <= 0.01);
+        assert_float_eq!(operands[0][1], -1_050_538.63, abs <= 0.01);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn modified_krovak() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let geo = iogp_test_point();
+
+        // EPSG:5515 S-JTSK/05 / Modified Krovak. The IOGP example for method 1042
+        let op = ctx.op("krovak czech modified x_0=5000000 y_0=5000000 ellps=bessel")?;
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0][0], 5_568_990.91, abs <= 0.01);
+        assert_float_eq!(operands[0][1], 6_050_538.71, abs <= 0.01);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+
+        // EPSG:5516 S-JTSK/05 / Modified Krovak East North (method 1043),
+        // using the PROJ name for the modified variant
+        let op = ctx.op("mod_krovak x_0=5000000 y_0=5000000 ellps=bessel")?;
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0][0], -5_568_990.91, abs <= 0.01);
+        assert_float_eq!(operands[0][1], -6_050_538.71, abs <= 0.01);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+        Ok(())
+    }
+}
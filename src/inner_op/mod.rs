@@ -7,54 +7,118 @@ use crate::authoring::*;
 
 mod adapt;
 mod addone;
+mod aea;
+mod aeqd;
+mod affine;
+mod axisswap;
 mod btmerc;
 mod cart;
+mod cass;
 mod curvature;
+#[cfg(feature = "json")]
+mod defmodel;
 mod deformation;
+mod eck4;
+mod eqc;
 mod geodesic;
+mod geogoffset;
+mod geos;
+mod gnom;
+mod gravity;
 mod gridshift;
 mod helmert;
+mod hgridshift;
+mod horner;
 mod iso6709;
+mod krovak;
 mod laea;
 mod latitude;
 mod lcc;
 mod merc;
+mod moll;
 mod molodensky;
+mod natearth;
 mod noop;
+mod ob_tran;
 mod omerc;
+mod ortho;
 pub(crate) mod pipeline; // Needed by Op for instantiation
+mod platemotion;
+mod poly;
+mod robin;
+mod sinu;
 mod somerc;
+mod stere;
+mod sterea;
+#[cfg(feature = "json")]
+mod tinshift;
 mod tmerc;
+mod topocentric;
 mod unitconvert;
 mod units;
+mod vertoffset;
+mod vgridshift;
 mod webmerc;
 
 #[rustfmt::skip]
-const BUILTIN_OPERATORS: [(&str, OpConstructor); 31] = [
+const BUILTIN_OPERATORS: &[(&str, OpConstructor)] = &[
     ("adapt",        OpConstructor(adapt::new)),
     ("addone",       OpConstructor(addone::new)),
+    ("affine",       OpConstructor(affine::new)),
+    ("aea",          OpConstructor(aea::new)),
+    ("aeqd",         OpConstructor(aeqd::new)),
+    ("axisswap",     OpConstructor(axisswap::new)),
     ("btmerc",       OpConstructor(btmerc::new)),
     ("butm",         OpConstructor(btmerc::utm)),
     ("cart",         OpConstructor(cart::new)),
+    ("cass",         OpConstructor(cass::new)),
     ("curvature",    OpConstructor(curvature::new)),
+    #[cfg(feature = "json")]
+    ("defmodel",     OpConstructor(defmodel::new)),
     ("deformation",  OpConstructor(deformation::new)),
     ("dm",           OpConstructor(iso6709::dm)),
     ("dms",          OpConstructor(iso6709::dms)),
+    ("eck4",         OpConstructor(eck4::new)),
+    ("eqc",          OpConstructor(eqc::new)),
     ("geodesic",     OpConstructor(geodesic::new)),
+    ("geogoffset",   OpConstructor(geogoffset::new)),
+    ("geos",         OpConstructor(geos::new)),
+    ("gnom",         OpConstructor(gnom::new)),
+    ("gravity",      OpConstructor(gravity::new)),
     ("gridshift",    OpConstructor(gridshift::new)),
     ("helmert",      OpConstructor(helmert::new)),
+    ("hgridshift",   OpConstructor(hgridshift::new)),
+    ("horner",       OpConstructor(horner::new)),
+    ("krovak",       OpConstructor(krovak::new)),
+    ("mod_krovak",   OpConstructor(krovak::modified)),
     ("laea",         OpConstructor(laea::new)),
     ("latitude",     OpConstructor(latitude::new)),
     ("lcc",          OpConstructor(lcc::new)),
     ("merc",         OpConstructor(merc::new)),
     ("webmerc",      OpConstructor(webmerc::new)),
+    ("moll",         OpConstructor(moll::new)),
     ("molodensky",   OpConstructor(molodensky::new)),
+    ("natearth",     OpConstructor(natearth::new)),
     ("noop",         OpConstructor(noop::new)),
+    ("ob_tran",      OpConstructor(ob_tran::new)),
     ("omerc",        OpConstructor(omerc::new)),
+    ("ortho",        OpConstructor(ortho::new)),
+    ("platemotion",  OpConstructor(platemotion::new)),
+    ("poly",         OpConstructor(poly::new)),
+    ("robin",        OpConstructor(robin::new)),
+    ("sinu",         OpConstructor(sinu::new)),
     ("somerc",       OpConstructor(somerc::new)),
+    ("stere",        OpConstructor(stere::new)),
+    ("ups",          OpConstructor(stere::ups)),
+    ("sterea",       OpConstructor(sterea::new)),
+    #[cfg(feature = "json")]
+    ("tinshift",     OpConstructor(tinshift::new)),
     ("tmerc",        OpConstructor(tmerc::new)),
     ("utm",          OpConstructor(tmerc::utm)),
+    ("topocentric",  OpConstructor(topocentric::new)),
     ("unitconvert", OpConstructor(unitconvert::new)),
+    ("vertoffset",   OpConstructor(vertoffset::new)),
+    ("vgridshift",   OpConstructor(vgridshift::new)),
     ("pipeline",     OpConstructor(pipeline::new)),
     ("pop",          OpConstructor(pipeline::pop)),
     ("push",         OpConstructor(pipeline::push)),
@@ -86,6 +150,7 @@ pub(crate) fn builtin(name: &str) -> Result<OpConstructor, Error> {
 /// OpConstructor needs to be a newtype, rather than a type alias,
 /// since we must implement the Debug-trait for OpConstructor (to
 /// make auto derive of the Debug-trait work for any derived type).
+#[derive(Clone, Copy)]
 pub struct OpConstructor(pub fn(args: &RawParameters, ctx: &dyn Context) -> Result<Op, Error>);
 
 // Cannot autoderive the Debug trait
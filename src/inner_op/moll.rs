@@ -0,0 +1,167 @@
+//! Mollweide, spherical form
+use crate::authoring::*;
+use std::f64::consts::{FRAC_PI_2, PI, SQRT_2};
+
+// Scaling constants for x and y
+const C_X: f64 = 2. * SQRT_2 / PI;
+const C_Y: f64 = SQRT_2;
+
+const MAX_ITERATIONS: usize = 30;
+const EPS: f64 = 1e-14;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = angular::normalize_symmetric(coord[0] - lon_0);
+        let theta = auxiliary_angle(coord[1]);
+        coord[0] = x_0 + r * C_X * lam * theta.cos();
+        coord[1] = y_0 + r * C_Y * theta.sin();
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let sin_theta = (coord[1] - y_0) / (r * C_Y);
+        if sin_theta.abs() > 1. + 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let theta = sin_theta.clamp(-1., 1.).asin();
+        let cos_theta = theta.cos();
+
+        // At the poles, the longitude is arbitrary, so we select the central meridian
+        let lam = if cos_theta < 1e-12 {
+            0.
+        } else {
+            (coord[0] - x_0) / (r * C_X * cos_theta)
+        };
+        if lam.abs() > PI + 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        let two_theta = 2. * theta;
+        let sin_phi = (two_theta + two_theta.sin()) / PI;
+        coord[0] = angular::normalize_symmetric(lam + lon_0);
+        coord[1] = sin_phi.clamp(-1., 1.).asin();
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    Op::plain(parameters, InnerOp(fwd), Some(InnerOp(inv)), &GAMUT, ctx)
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+/// The auxiliary angle, θ, given by 2θ + sin 2θ = π sin φ, solved by
+/// Newton-Raphson iteration (Snyder, 1987, eq. 31-4)
+fn auxiliary_angle(phi: f64) -> f64 {
+    // The iteration degenerates at the poles, where θ = φ
+    if (phi.abs() - FRAC_PI_2).abs() < 1e-12 {
+        return phi;
+    }
+    let k = PI * phi.sin();
+    let mut two_theta = phi;
+    for _ in 0..MAX_ITERATIONS {
+        let delta = (two_theta + two_theta.sin() - k) / (1. + two_theta.cos());
+        two_theta -= delta;
+        if delta.abs() < EPS {
+            break;
+        }
+    }
+    two_theta / 2.
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn moll() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("moll ellps=sphere")?;
+        let r = 6_370_997.;
+
+        // The map is an ellipse with semi-axes 2√2 R and √2 R
+        let geo = [
+            Coor4D::geo(0., -180., 0., 0.),
+            Coor4D::geo(90., 0., 0., 0.),
+            Coor4D::geo(-35., -120., 0., 0.),
+            Coor4D::geo(89.99, 17., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 4);
+        assert_float_eq!(operands[0][0], -r * 2. * SQRT_2, abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 0., abs <= 1e-6);
+        assert_float_eq!(operands[1][0], 0., abs <= 1e-6);
+        assert_float_eq!(operands[1][1], r * SQRT_2, abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 4);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn factors() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("moll ellps=sphere")?;
+        let ellps = Ellipsoid::named("sphere")?;
+
+        // Equal area, but not conformal
+        let at = Coor2D::geo(50., 60.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert_float_eq!(factors.areal_scale, 1., abs <= 1e-8);
+        assert!(factors.angular_distortion > 0.1);
+
+        // True scale along the parallels of ±40°44'
+        let at = Coor2D::geo(angular::dms_to_dd(40, 44, 11.8), 0.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert_float_eq!(factors.parallel_scale, 1., abs <= 1e-6);
+        assert_float_eq!(factors.meridional_scale, 1., abs <= 1e-6);
+        Ok(())
+    }
+}
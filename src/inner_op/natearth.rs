@@ -0,0 +1,187 @@
+//! Natural Earth, spherical form
+//!
+//! A pseudocylindrical compromise projection, defined by polynomials
+//! approximating a Robinson-like graticule (Šavrič et al., 2011).
+use crate::authoring::*;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+// Coefficients for the easting polynomial
+const A0: f64 = 0.8707;
+const A1: f64 = -0.131979;
+const A2: f64 = -0.013791;
+const A3: f64 = 0.003971;
+const A4: f64 = -0.001529;
+
+// Coefficients for the northing polynomial
+const B0: f64 = 1.007226;
+const B1: f64 = 0.015085;
+const B2: f64 = -0.044475;
+const B3: f64 = 0.028874;
+const B4: f64 = -0.005916;
+
+// Coefficients for the derivative of the northing polynomial
+const C0: f64 = B0;
+const C1: f64 = 3. * B1;
+const C2: f64 = 7. * B2;
+const C3: f64 = 9. * B3;
+const C4: f64 = 11. * B4;
+
+// The northing of the poles, on the unit sphere
+const MAX_Y: f64 = 0.8707 * 0.52 * PI;
+
+const MAX_ITERATIONS: usize = 100;
+const EPS: f64 = 1e-12;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = angular::normalize_symmetric(coord[0] - lon_0);
+        let phi = coord[1];
+        let phi2 = phi * phi;
+        let phi4 = phi2 * phi2;
+        coord[0] =
+            x_0 + r * lam * (A0 + phi2 * (A1 + phi2 * (A2 + phi4 * phi2 * (A3 + phi2 * A4))));
+        coord[1] = y_0 + r * phi * (B0 + phi2 * (B1 + phi4 * (B2 + B3 * phi2 + B4 * phi4)));
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    'points: for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let x = (coord[0] - x_0) / r;
+        let y = (coord[1] - y_0) / r;
+
+        // Outside of the map area
+        if y.is_nan() || y.abs() > MAX_Y + 1e-9 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let y = y.clamp(-MAX_Y, MAX_Y);
+
+        // Newton-Raphson for the latitude, using y as the first guess
+        let mut phi = y;
+        for _ in 0..MAX_ITERATIONS {
+            let phi2 = phi * phi;
+            let phi4 = phi2 * phi2;
+            let f = phi * (B0 + phi2 * (B1 + phi4 * (B2 + B3 * phi2 + B4 * phi4))) - y;
+            let fder = C0 + phi2 * (C1 + phi4 * (C2 + C3 * phi2 + C4 * phi4));
+            let dphi = f / fder;
+            phi -= dphi;
+            if dphi.abs() < EPS {
+                let phi2 = phi * phi;
+                let phi4 = phi2 * phi2;
+                let lam = x / (A0 + phi2 * (A1 + phi2 * (A2 + phi4 * phi2 * (A3 + phi2 * A4))));
+                if lam.abs() > PI + 1e-12 {
+                    break;
+                }
+                coord[0] = angular::normalize_symmetric(lon_0 + lam);
+                coord[1] = phi.clamp(-FRAC_PI_2, FRAC_PI_2);
+                operands.set_coord(i, &coord);
+                successes += 1;
+                continue 'points;
+            }
+        }
+
+        // No convergence, or outside of the map area
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    Op::plain(parameters, InnerOp(fwd), Some(InnerOp(inv)), &GAMUT, ctx)
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn natearth() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("natearth ellps=sphere lon_0=-20")?;
+        let r = 6_370_997.;
+
+        let geo = [
+            Coor4D::geo(90., -20., 0., 0.),
+            Coor4D::geo(0., 160., 0., 0.),
+            Coor4D::geo(55., 12., 0., 0.),
+            Coor4D::geo(-75., -150., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 4);
+
+        // The poles end up within 100 m of y = 0.52 π A0, the limit used by the inverse
+        assert_float_eq!(operands[0][1], MAX_Y * r, abs <= 100.);
+        assert_float_eq!(operands[1][0], -A0 * PI * r, abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 4);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+
+        // Outside of the map area
+        let mut operands = [Coor4D::raw(0., 1.1 * MAX_Y * r, 0., 0.)];
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 0);
+        assert!(operands[0][1].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn factors() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("natearth ellps=sphere")?;
+        let ellps = Ellipsoid::named("sphere")?;
+
+        // At the origin, the scale factors are the leading coefficients
+        let at = Coor2D::geo(0., 0.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert_float_eq!(factors.parallel_scale, A0, abs <= 1e-8);
+        assert_float_eq!(factors.meridional_scale, B0, abs <= 1e-8);
+
+        // Neither conformal nor equal area
+        let at = Coor2D::geo(50., 60.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert!((factors.areal_scale - 1.).abs() > 0.01);
+        assert!(factors.angular_distortion > 0.01);
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+//! General oblique transformation
+//!
+//! Rotate the sphere, such that the pole of the rotated system ends up at
+//! (`o_lon_p`, `o_lat_p`) in the original system, then hand the rotated
+//! coordinates over to the operator given by `o_proj`. All parameters not
+//! consumed by `ob_tran` itself are handed over to the nested operator.
+//!
+//! The operator is mostly used for describing rotated-pole grids, as used by
+//! climate models. In that case, `o_proj=longlat` (or any other no-op) is
+//! used, so the result is the rotated geographical coordinates.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let lon_0 = op.params.lon(0).to_radians();
+    let Ok(o_lat_p) = op.params.real("o_lat_p") else {
+        return 0;
+    };
+    let Ok(o_lon_p) = op.params.real("o_lon_p") else {
+        return 0;
+    };
+    let (sin_lat_p, cos_lat_p) = o_lat_p.to_radians().sin_cos();
+    let o_lon_p = o_lon_p.to_radians();
+
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let (sin_lam, cos_lam) = (coord[0] - lon_0).sin_cos();
+        let (sin_phi, cos_phi) = coord[1].sin_cos();
+
+        // Rotate the unit vector. Using atan2 rather than asin for the
+        // latitude avoids loss of accuracy near the poles
+        let x = sin_lat_p * cos_phi * cos_lam + cos_lat_p * sin_phi;
+        let y = cos_phi * sin_lam;
+        let z = sin_lat_p * sin_phi - cos_lat_p * cos_phi * cos_lam;
+        coord[0] = angular::normalize_symmetric(y.atan2(x) + o_lon_p);
+        coord[1] = z.atan2(x.hypot(y));
+        operands.set_coord(i, &coord);
+    }
+
+    // The nested operator determines the number of successes
+    op.steps[0].apply(ctx, operands, Fwd)
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let lon_0 = op.params.lon(0).to_radians();
+    let Ok(o_lat_p) = op.params.real("o_lat_p") else {
+        return 0;
+    };
+    let Ok(o_lon_p) = op.params.real("o_lon_p") else {
+        return 0;
+    };
+    let (sin_lat_p, cos_lat_p) = o_lat_p.to_radians().sin_cos();
+    let o_lon_p = o_lon_p.to_radians();
+
+    let successes = op.steps[0].apply(ctx, operands, Inv);
+
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let (sin_lam, cos_lam) = (coord[0] - o_lon_p).sin_cos();
+        let (sin_phi, cos_phi) = coord[1].sin_cos();
+
+        let x = sin_lat_p * cos_phi * cos_lam - cos_lat_p * sin_phi;
+        let y = cos_phi * sin_lam;
+        let z = sin_lat_p * sin_phi + cos_lat_p * cos_phi * cos_lam;
+        coord[0] = angular::normalize_symmetric(y.atan2(x) + lon_0);
+        coord[1] = z.atan2(x.hypot(y));
+        operands.set_coord(i, &coord);
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "o_proj",  default: None },
+    OpParameter::Real { key: "o_lat_p", default: None },
+    OpParameter::Real { key: "o_lon_p", default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    if params.real("o_lat_p")?.abs() > 90. {
+        return Err(Error::BadParam("o_lat_p".to_string(), def.clone()));
+    }
+
+    // The nested operator gets all the parameters not consumed by ob_tran
+    let o_proj = params.text("o_proj")?;
+    let mut nested = o_proj.clone();
+    for (key, value) in def.split_into_parameters() {
+        if ["name", "inv", "o_proj", "o_lat_p", "o_lon_p", "lon_0"].contains(&key.as_str()) {
+            continue;
+        }
+        nested += &format!(" {key}={value}");
+    }
+    let steps = vec![Op::op(parameters.next(&nested), ctx)?];
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn rotated_pole() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // The rotated-pole grid of the EURO-CORDEX climate model domain
+        let op = ctx.op("ob_tran o_proj=latlon o_lat_p=39.25 o_lon_p=0 lon_0=18")?;
+
+        let geo = [
+            // The rotated pole
+            Coor4D::geo(39.25, -162., 0., 0.),
+            // The origin of the rotated system
+            Coor4D::geo(50.75, 18., 0., 0.),
+            Coor4D::geo(55., 12., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][1], 90f64.to_radians(), abs <= 1e-12);
+        assert_float_eq!(operands[1][0], 0., abs <= 1e-12);
+        assert_float_eq!(operands[1][1], 0., abs <= 1e-12);
+
+        operands[0][0] = 0.;
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        assert_float_eq!(operands[0][1], geo[0][1], abs <= 1e-12);
+        for i in 1..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn nested() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // Parameters not consumed by ob_tran are handed over to the nested operator
+        let op = ctx.op("ob_tran o_proj=moll o_lat_p=45 o_lon_p=-90 lon_0=10 ellps=sphere")?;
+        let moll = ctx.op("moll ellps=sphere")?;
+        let rotated = ctx.op("ob_tran o_proj=latlon o_lat_p=45 o_lon_p=-90 lon_0=10")?;
+
+        let geo = [
+            Coor4D::geo(55., 12., 0., 0.),
+            Coor4D::geo(-30., 150., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 2);
+
+        let mut expected = geo;
+        ctx.apply(rotated, Fwd, &mut expected)?;
+        ctx.apply(moll, Fwd, &mut expected)?;
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, expected[i].0, abs_all <= 1e-9);
+        }
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 2);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+
+        // Failures in the nested operator are reported
+        let op = ctx.op("ob_tran o_proj=gnom o_lat_p=0 ellps=sphere")?;
+        let mut operands = [Coor4D::geo(0., 0., 0., 0.), Coor4D::geo(90., 0., 0., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert!(operands[0][0].is_nan());
+
+        assert!(ctx.op("ob_tran o_lat_p=45").is_err());
+        assert!(ctx.op("ob_tran o_proj=moll").is_err());
+        assert!(ctx.op("ob_tran o_proj=no_such_op o_lat_p=45").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Orthographic
+//!
+//! The ellipsoidal form, following IOGP (2019), method 9840. The projected
+//! coordinates are the easting and northing components, in the tangent
+//! plane at the projection center, of the cartesian vector from the center
+//! to the point. Hence the inverse is found as the intersection between the
+//! ellipsoid and the normal to the tangent plane through the point.
+use crate::authoring::*;
+
+const EPS10: f64 = 1e-10;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let es = ellps.eccentricity_squared();
+    let lat_0 = op.params.lat(0).to_radians();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let (sin_lat_0, cos_lat_0) = lat_0.sin_cos();
+    let nu_0 = ellps.prime_vertical_radius_of_curvature(lat_0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0] - lon_0;
+        let phi = coord[1];
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let (sin_lam, cos_lam) = lam.sin_cos();
+
+        // Points on the far side of the globe are invisible
+        if sin_lat_0 * sin_phi + cos_lat_0 * cos_phi * cos_lam < -EPS10 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        let nu = ellps.prime_vertical_radius_of_curvature(phi);
+        coord[0] = x_0 + nu * cos_phi * sin_lam;
+        coord[1] = y_0
+            + nu * (sin_phi * cos_lat_0 - cos_phi * sin_lat_0 * cos_lam)
+            + es * (nu_0 * sin_lat_0 - nu * sin_phi) * cos_lat_0;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let b = ellps.semiminor_axis();
+    let lat_0 = op.params.lat(0).to_radians();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let (sin_lat_0, cos_lat_0) = lat_0.sin_cos();
+    let (sin_lon_0, cos_lon_0) = lon_0.sin_cos();
+
+    // The projection center, and the east, north and up unit vectors there
+    let center = ellps.cartesian(&Coor4D::raw(lon_0, lat_0, 0., 0.));
+    let east = [-sin_lon_0, cos_lon_0, 0.];
+    let north = [-sin_lat_0 * cos_lon_0, -sin_lat_0 * sin_lon_0, cos_lat_0];
+    let up = [cos_lat_0 * cos_lon_0, cos_lat_0 * sin_lon_0, sin_lat_0];
+
+    // Weights for the quadratic form of the ellipsoid
+    let w = [1. / (a * a), 1. / (a * a), 1. / (b * b)];
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let x = coord[0] - x_0;
+        let y = coord[1] - y_0;
+
+        // The point in the tangent plane
+        let mut q = [0.; 3];
+        for j in 0..3 {
+            q[j] = center[j] + x * east[j] + y * north[j];
+        }
+
+        // Intersect the line `q + t up` with the ellipsoid, selecting the
+        // intersection on the visible side
+        let mut aa = 0.;
+        let mut bb = 0.;
+        let mut cc = -1.;
+        for j in 0..3 {
+            aa += w[j] * up[j] * up[j];
+            bb += 2. * w[j] * q[j] * up[j];
+            cc += w[j] * q[j] * q[j];
+        }
+        let det = bb * bb - 4. * aa * cc;
+        if det < 0. {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        let t = (-bb + det.sqrt()) / (2. * aa);
+        let p = Coor4D::raw(q[0] + t * up[0], q[1] + t * up[1], q[2] + t * up[2], 0.);
+
+        let geo = ellps.geographic(&p);
+        coord[0] = geo[0];
+        coord[1] = geo[1];
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 6] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_0", default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    Op::plain(parameters, InnerOp(fwd), Some(InnerOp(inv)), &GAMUT, ctx)
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn spherical() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("ortho ellps=sphere lat_0=0 lon_0=0")?;
+        let r = 6_370_997.;
+
+        let geo = [
+            Coor4D::geo(1., 2., 0., 0.),
+            Coor4D::geo(-1., -2., 0., 0.),
+            Coor4D::geo(60., 80., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        let (lat, lon) = (1f64.to_radians(), 2f64.to_radians());
+        assert_float_eq!(operands[0][0], r * lat.cos() * lon.sin(), abs <= 1e-6);
+        assert_float_eq!(operands[0][1], r * lat.sin(), abs <= 1e-6);
+        assert_float_eq!(operands[1][0], -r * lat.cos() * lon.sin(), abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ellipsoidal() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // The IOGP example for method 9840 (Satellite map, WGS 84)
+        let op = ctx.op("ortho ellps=WGS84 lat_0=55 lon_0=5")?;
+        let geo = [
+            Coor4D::geo(
+                angular::dms_to_dd(53, 48, 33.82),
+                angular::dms_to_dd(2, 7, 46.38),
+                0.,
+                0.,
+            ),
+            Coor4D::geo(70., 30., 0., 0.),
+            Coor4D::geo(-10., 60., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][0], -189_011.711, abs <= 1e-3);
+        assert_float_eq!(operands[0][1], -128_640.567, abs <= 1e-3);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invisible() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("ortho lat_0=55 lon_0=12")?;
+
+        // The far side of the globe
+        let mut operands = [
+            Coor4D::geo(-55., -168., 0., 0.),
+            Coor4D::geo(55., 12., 0., 0.),
+        ];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert!(operands[0][0].is_nan());
+        assert_float_eq!(operands[1][0], 0., abs <= 1e-9);
+
+        // Outside of the visible disk
+        let mut operands = [Coor4D::raw(7e6, 0., 0., 0.)];
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+//! Propagation of coordinates between epochs, using a plate motion model.
+//!
+//! The Euler pole of the plate is read from the resource `model:plate`, i.e.
+//! typically the entry `<plate>` of the resource register `model.register`
+//! (e.g. `geodesy/resources/itrf2014.register`). The entry holds the cartesian
+//! components of the rotation rate, in milliarcseconds per year, optionally
+//! followed by the translation rate (origin rate bias) of the model, in
+//! millimetres per year.
+//!
+//! In the forward direction, the coordinates are propagated from the epoch
+//! given by the fourth coordinate (or by `t_obs`, if given) to the target
+//! epoch `t_epoch`. The fourth coordinate itself is left unchanged, so the
+//! inverse operation propagates back from `t_epoch` to the epoch given by
+//! the fourth coordinate.
+//!
+//! The coordinates are geocentric cartesian, unless the `geographic` flag is
+//! given, in which case they are converted to and from cartesian form on the
+//! ellipsoid `ellps`.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    propagate(op, operands, Fwd)
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    propagate(op, operands, Inv)
+}
+
+// The forward and inverse cases differ only in the direction of time
+fn propagate(op: &Op, operands: &mut dyn CoordinateSet, direction: Direction) -> usize {
+    let Ok(w) = op.params.series("OMEGA") else {
+        return 0;
+    };
+    let Ok(t) = op.params.series("TRANSLATION") else {
+        return 0;
+    };
+    let Ok(epoch) = op.params.real("t_epoch") else {
+        return 0;
+    };
+    let t_obs = op.params.real("t_obs").unwrap_or(f64::NAN);
+    let geographic = op.params.boolean("geographic");
+    let ellps = op.params.ellps(0);
+
+    // The plate velocity at the cartesian position `x`
+    let velocity = |x: &Coor4D| {
+        Coor4D::raw(
+            w[1] * x[2] - w[2] * x[1] + t[0],
+            w[2] * x[0] - w[0] * x[2] + t[1],
+            w[0] * x[1] - w[1] * x[0] + t[2],
+            0.,
+        )
+    };
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let coord = operands.get_coord(i);
+        let mut x = if geographic {
+            ellps.cartesian(&coord)
+        } else {
+            coord
+        };
+
+        let t = if t_obs.is_nan() { coord[3] } else { t_obs };
+        let dt = epoch - t;
+
+        if direction == Fwd {
+            x = x + velocity(&x).scale(dt);
+        } else {
+            // The velocity is evaluated at the propagated position, so we
+            // iterate. The rotation over a few decades is well below 1e-6,
+            // so two iterations bring us to the floating point resolution
+            let target = x;
+            for _ in 0..2 {
+                x = target - velocity(&x).scale(dt);
+            }
+        }
+
+        x[3] = coord[3];
+        let result = if geographic { ellps.geographic(&x) } else { x };
+        if !result.0.iter().any(|c| c.is_nan()) {
+            successes += 1;
+        }
+        operands.set_coord(i, &result);
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 7] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Flag { key: "geographic" },
+    OpParameter::Text { key: "ellps",   default: Some("GRS80") },
+    OpParameter::Text { key: "model",   default: None },
+    OpParameter::Text { key: "plate",   default: None },
+
+    // Target epoch
+    OpParameter::Real { key: "t_epoch", default: None },
+
+    // Fixed observation time - ignore the fourth coordinate.
+    OpParameter::Real { key: "t_obs",   default: Some(f64::NAN) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    // Read the Euler pole, ignoring comments
+    let name = format!("{}:{}", params.text("model")?, params.text("plate")?);
+    let resource = ctx.get_resource(&name)?;
+    let mut pole = Vec::new();
+    for line in resource.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        for element in line.split([' ', '\t', ',']).filter(|e| !e.is_empty()) {
+            let Ok(value) = element.parse::<f64>() else {
+                return Err(Error::BadParam(name, element.to_string()));
+            };
+            pole.push(value);
+        }
+    }
+    if pole.len() != 3 && pole.len() != 6 {
+        return Err(Error::Unexpected {
+            message: format!("platemotion: Bad Euler pole for '{name}'"),
+            expected: "3 or 6 elements".to_string(),
+            found: pole.len().to_string(),
+        });
+    }
+    pole.resize(6, 0.);
+
+    // Convert to radians per year and metres per year
+    let omega = pole[..3].iter().map(|w| (w / 3_600_000.).to_radians());
+    let translation = pole[3..].iter().map(|t| t / 1000.);
+    params.series.insert("OMEGA", omega.collect());
+    params.series.insert("TRANSLATION", translation.collect());
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Onsala, propagated 10 years forward on the ITRF2014 Eurasian plate
+    const ONSA: Coor4D = Coor4D([3370658.0, 711877.0, 5349787.0, 2010.0]);
+    const ONSA_2020: Coor4D = Coor4D([
+        3370657.8394023553,
+        711877.1513748901,
+        5349787.091239308,
+        2010.0,
+    ]);
+
+    #[test]
+    fn platemotion() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let op = ctx.op("platemotion model=itrf2014 plate=EURA t_epoch=2020")?;
+
+        let mut operands = [ONSA];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert!(ONSA_2020.hypot3(&operands[0]) < 1e-6);
+        assert_eq!(operands[0][3], 2010.);
+
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(ONSA.hypot3(&operands[0]) < 1e-9);
+
+        // With a fixed observation time, the fourth coordinate is ignored
+        let op = ctx.op("platemotion model=itrf2014 plate=EURA t_epoch=2020 t_obs=2010")?;
+        let mut operands = [ONSA];
+        operands[0][3] = 1984.;
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!(ONSA_2020.hypot2(&operands[0]) < 1e-6);
+
+        // Geographic coordinates move the same way
+        let op = ctx.op("platemotion model=itrf2014 plate=EURA t_epoch=2020 geographic")?;
+        let ellps = Ellipsoid::named("GRS80")?;
+        let mut operands = [ellps.geographic(&ONSA)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!(ONSA_2020.hypot3(&ellps.cartesian(&operands[0])) < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn registered_pole() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // No translation rate, and a full turn in a thousand years
+        ctx.register_resource("test:plate", "# A fast plate\n0, 0, 1296000");
+        let op = ctx.op("platemotion model=test plate=plate t_epoch=0")?;
+        let mut operands = [Coor4D::raw(1e6, 0., 0., -1.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        let angle = std::f64::consts::TAU / 1e3;
+        assert!((operands[0][1] - 1e6 * angle).abs() < 1e-9);
+
+        // Missing poles, and bad poles
+        assert!(ctx
+            .op("platemotion model=test plate=none t_epoch=0")
+            .is_err());
+        ctx.register_resource("test:bad", "1 2");
+        assert!(ctx
+            .op("platemotion model=test plate=bad t_epoch=0")
+            .is_err());
+        assert!(ctx.op("platemotion model=test plate=plate").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+//! American Polyconic
+use crate::authoring::*;
+
+const EPS10: f64 = 1e-10;
+const MAX_ITERATIONS: usize = 20;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+// Following IOGP, 2019, method 9818, and Snyder (1987), pp. 124-130
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let a = ellps.semimajor_axis();
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let lam = coord[0] - lon_0;
+        let phi = coord[1];
+
+        // On the equator, the projection degenerates to a straight line
+        if phi.abs() < EPS10 {
+            coord[0] = x_0 + a * lam;
+            coord[1] = y_0 - m_0;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let nu_cot_phi = ellps.prime_vertical_radius_of_curvature(phi) * cos_phi / sin_phi;
+        let (sin_l, cos_l) = (lam * sin_phi).sin_cos();
+
+        coord[0] = x_0 + nu_cot_phi * sin_l;
+        coord[1] = y_0 + ellps.meridian_latitude_to_distance(phi) - m_0 + nu_cot_phi * (1. - cos_l);
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+    let a = ellps.semimajor_axis();
+    let es = ellps.eccentricity_squared();
+    let Ok(m_0) = op.params.real("m_0") else {
+        return 0;
+    };
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    'points: for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let x = (coord[0] - x_0) / a;
+        let aa = (m_0 + coord[1] - y_0) / a;
+
+        // On the equator
+        if aa.abs() < EPS10 {
+            coord[0] = angular::normalize_symmetric(lon_0 + x);
+            coord[1] = 0.;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // Newton-Raphson iteration, Snyder (1987) eq. 18-21
+        let bb = x * x + aa * aa;
+        let mut phi = aa;
+        for _ in 0..MAX_ITERATIONS {
+            let (sin_phi, cos_phi) = phi.sin_cos();
+            let sin_2phi = 2. * sin_phi * cos_phi;
+            let w = 1. - es * sin_phi * sin_phi;
+            let c = w.sqrt() * sin_phi / cos_phi;
+            let m = ellps.meridian_latitude_to_distance(phi) / a;
+            let m_prime = (1. - es) / (w * w.sqrt());
+
+            let numerator = aa * (c * m + 1.) - m - 0.5 * (m * m + bb) * c;
+            let denominator = es * sin_2phi * (m * m + bb - 2. * aa * m) / (4. * c)
+                + (aa - m) * (c * m_prime - 2. / sin_2phi)
+                - m_prime;
+            let dphi = numerator / denominator;
+            phi -= dphi;
+
+            if dphi.abs() < EPS10 {
+                let (sin_phi, cos_phi) = phi.sin_cos();
+                let c = (1. - es * sin_phi * sin_phi).sqrt() * sin_phi / cos_phi;
+                let lam = (x * c).asin() / sin_phi;
+                coord[0] = angular::normalize_symmetric(lon_0 + lam);
+                coord[1] = phi;
+                operands.set_coord(i, &coord);
+                successes += 1;
+                continue 'points;
+            }
+        }
+
+        // No convergence
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 6] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_0", default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    let ellps = params.ellps(0);
+
+    // The meridian distance from the equator to the latitude of origin
+    let m_0 = ellps.meridian_latitude_to_distance(params.lat(0).to_radians());
+    params.real.insert("m_0", m_0);
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn poly() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // The worked example for the ellipsoidal case from Snyder (1987), p. 295
+        let op = ctx.op("poly lat_0=30 lon_0=-96 ellps=clrk66")?;
+        let geo = [Coor4D::geo(40., -75., 0., 0.)];
+        let projected = [Coor4D::raw(1_776_774.5, 1_319_657.8, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.1);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-9);
+
+        // Roundtrip, including the equator and the southern hemisphere
+        let op = ctx.op("poly lat_0=0 lon_0=-54 x_0=5000000 y_0=10000000")?;
+        let geo = [
+            Coor4D::geo(0., -50., 0., 0.),
+            Coor4D::geo(-20., -40., 0., 0.),
+            Coor4D::geo(5., -70., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][1], 10_000_000., abs <= 1e-9);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-9);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+//! Robinson, spherical form
+//!
+//! The Robinson projection is defined by a table of parallel lengths and
+//! distances from the equator, given at 5° intervals of latitude. Following
+//! PROJ, the table is interpolated by piecewise cubic polynomials.
+use crate::authoring::*;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+// Scaling of the tabulated parallel lengths and distances from the equator
+const FXC: f64 = 0.8487;
+const FYC: f64 = 1.3523;
+
+// The reciprocal of the node interval, 5°, in radians, and the interval itself
+const C1: f64 = 11.459_155_902_616_464;
+const RC1: f64 = 0.087_266_462_599_716_48;
+const NODES: usize = 18;
+
+const MAX_ITERATIONS: usize = 100;
+const EPS: f64 = 1e-12;
+
+// Cubic interpolation polynomials for the parallel lengths, X,
+// and the distances from the equator, Y. One entry for each 5° of latitude.
+#[rustfmt::skip]
+const X: [[f64; 4]; NODES + 1] = [
+    [1.0,    2.2199e-17,  -7.15515e-05,  3.1103e-06],
+    [0.9986, -0.000482243, -2.4897e-05, -1.3309e-06],
+    [0.9954, -0.00083103, -4.48605e-05, -9.86701e-07],
+    [0.99,   -0.00135364,  -5.9661e-05,  3.6777e-06],
+    [0.9822, -0.00167442, -4.49547e-06, -5.72411e-06],
+    [0.973,  -0.00214868, -9.03571e-05,  1.8736e-08],
+    [0.96,   -0.00305085, -9.00761e-05,  1.64917e-06],
+    [0.9427, -0.00382792, -6.53386e-05, -2.6154e-06],
+    [0.9216, -0.00467746, -0.00010457,   4.81243e-06],
+    [0.8962, -0.00536223, -3.23831e-05, -5.43432e-06],
+    [0.8679, -0.00609363, -0.000113898,  3.32484e-06],
+    [0.835,  -0.00698325, -6.40253e-05,  9.34959e-07],
+    [0.7986, -0.00755338, -5.00009e-05,  9.35324e-07],
+    [0.7597, -0.00798324, -3.5971e-05,  -2.27626e-06],
+    [0.7186, -0.00851367, -7.01149e-05, -8.6303e-06],
+    [0.6732, -0.00986209, -0.000199569,  1.91974e-05],
+    [0.6213, -0.010418,    8.83923e-05,  6.24051e-06],
+    [0.5722, -0.00906601,  0.000182,     6.24051e-06],
+    [0.5322, -0.00677797,  0.000275608,  6.24051e-06],
+];
+
+#[rustfmt::skip]
+const Y: [[f64; 4]; NODES + 1] = [
+    [-5.20417e-18, 0.0124,     1.21431e-18, -8.45284e-11],
+    [0.062,        0.0124,    -1.26793e-09,  4.22642e-10],
+    [0.124,        0.0124,     5.07171e-09, -1.60604e-09],
+    [0.186,        0.0123999, -1.90189e-08,  6.00152e-09],
+    [0.248,        0.0124002,  7.10039e-08, -2.24e-08],
+    [0.31,         0.0123992, -2.64997e-07,  8.35986e-08],
+    [0.372,        0.0124029,  9.88983e-07, -3.11994e-07],
+    [0.434,        0.0123893, -3.69093e-06, -4.35621e-07],
+    [0.4958,       0.0123198, -1.02252e-05, -3.45523e-07],
+    [0.5571,       0.0121916, -1.54081e-05, -5.82288e-07],
+    [0.6176,       0.0119938, -2.41424e-05, -5.25327e-07],
+    [0.6769,       0.011713,  -3.20223e-05, -5.16405e-07],
+    [0.7346,       0.0113541, -3.97684e-05, -6.09052e-07],
+    [0.7903,       0.0109107, -4.89042e-05, -1.04739e-06],
+    [0.8435,       0.0103431, -6.4615e-05,  -1.40374e-09],
+    [0.8936,       0.00969686, -6.4636e-05, -8.547e-06],
+    [0.9394,       0.00840947, -0.000192841, -4.2106e-06],
+    [0.9761,       0.00616527, -0.000256,    -4.2106e-06],
+    [1.0,          0.00328947, -0.000319159, -4.2106e-06],
+];
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = angular::normalize_symmetric(coord[0] - lon_0);
+        let phi = coord[1];
+        if phi.is_nan() || phi.abs() > FRAC_PI_2 + 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        // The table interval, and the (degree) offset into it. At the poles,
+        // we use the last table node directly
+        let dphi = phi.abs();
+        let node = ((dphi * C1 + 1e-15).floor() as usize).min(NODES);
+        let dphi = (dphi - RC1 * node as f64).to_degrees();
+
+        let y = r * FYC * v(&Y[node], dphi);
+        coord[0] = x_0 + r * FXC * lam * v(&X[node], dphi);
+        coord[1] = y_0 + if phi < 0. { -y } else { y };
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    'points: for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let x = (coord[0] - x_0) / r;
+        let y = (coord[1] - y_0) / r;
+        let lam = x / FXC;
+        let yy = (y / FYC).abs();
+
+        // Outside of the map area
+        if yy.is_nan() || yy > 1. + 1e-6 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        // At (or numerically very close to) the poles
+        if yy >= 1. {
+            coord[0] = angular::normalize_symmetric(lon_0 + lam / X[NODES][0]);
+            coord[1] = FRAC_PI_2.copysign(y);
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // Find the table interval
+        let mut node = (yy * NODES as f64).floor() as usize;
+        if node >= NODES {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+        loop {
+            if Y[node][0] > yy {
+                node -= 1;
+            } else if Y[node + 1][0] <= yy {
+                node += 1;
+            } else {
+                break;
+            }
+        }
+
+        // Linear interpolation for a first guess, then Newton-Raphson
+        let mut t = 5. * (yy - Y[node][0]) / (Y[node + 1][0] - Y[node][0]);
+        let mut coefs = Y[node];
+        coefs[0] -= yy;
+        for _ in 0..MAX_ITERATIONS {
+            let dt = v(&coefs, t) / dv(&coefs, t);
+            t -= dt;
+            if dt.abs() < EPS {
+                let phi = (5. * node as f64 + t).to_radians();
+                let lam = lam / v(&X[node], t);
+                if lam.abs() > PI + 1e-12 {
+                    break;
+                }
+                coord[0] = angular::normalize_symmetric(lon_0 + lam);
+                coord[1] = phi.copysign(y);
+                operands.set_coord(i, &coord);
+                successes += 1;
+                continue 'points;
+            }
+        }
+
+        // No convergence, or outside of the map area
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    Op::plain(parameters, InnerOp(fwd), Some(InnerOp(inv)), &GAMUT, ctx)
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+/// Evaluate the interpolation polynomial, `c`, at `z`
+fn v(c: &[f64; 4], z: f64) -> f64 {
+    c[0] + z * (c[1] + z * (c[2] + z * c[3]))
+}
+
+/// Evaluate the derivative of the interpolation polynomial, `c`, at `z`
+fn dv(c: &[f64; 4], z: f64) -> f64 {
+    c[1] + z * (2. * c[2] + z * 3. * c[3])
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn robin() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("robin ellps=sphere")?;
+        let r = 6_370_997.;
+
+        // The table nodes are reproduced exactly
+        let geo = [
+            Coor4D::geo(0., -180., 0., 0.),
+            Coor4D::geo(90., 60., 0., 0.),
+            Coor4D::geo(45., 0., 0., 0.),
+            Coor4D::geo(-45., 90., 0., 0.),
+            Coor4D::geo(62.5, -123., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 5);
+        assert_float_eq!(operands[0][0], -FXC * PI * r, abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 0., abs <= 1e-6);
+        assert_float_eq!(operands[1][0], FXC * 0.5322 * PI / 3. * r, abs <= 1e-6);
+        assert_float_eq!(operands[1][1], FYC * r, abs <= 1e-6);
+        assert_float_eq!(operands[2][1], FYC * 0.5571 * r, abs <= 1e-6);
+        assert_float_eq!(operands[3][0], FXC * 0.8962 * FRAC_PI_2 * r, abs <= 1e-6);
+        assert_float_eq!(operands[3][1], -FYC * 0.5571 * r, abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 5);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-10);
+        }
+
+        // Outside of the map area
+        let mut operands = [Coor4D::raw(0., 1.1 * FYC * r, 0., 0.)];
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 0);
+        assert!(operands[0][1].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn factors() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("robin ellps=sphere")?;
+        let ellps = Ellipsoid::named("sphere")?;
+
+        // At the origin, the scale factors follow directly from the table
+        let at = Coor2D::geo(0., 0.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert_float_eq!(factors.parallel_scale, FXC, abs <= 1e-8);
+        assert_float_eq!(
+            factors.meridional_scale,
+            FYC * 0.0124 * 180. / PI,
+            abs <= 1e-6
+        );
+
+        // Neither conformal nor equal area
+        let at = Coor2D::geo(50., 60.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert!((factors.areal_scale - 1.).abs() > 0.01);
+        assert!(factors.angular_distortion > 0.01);
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+//! Sinusoidal (Sanson-Flamsteed), spherical form
+use crate::authoring::*;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let lam = angular::normalize_symmetric(coord[0] - lon_0);
+        let phi = coord[1];
+        coord[0] = x_0 + r * lam * phi.cos();
+        coord[1] = y_0 + r * phi;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let r = op.params.ellps(0).semimajor_axis();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let phi = (coord[1] - y_0) / r;
+        let cos_phi = phi.cos();
+
+        // Outside of the map area?
+        if phi.abs() > FRAC_PI_2 + 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        // At the poles, the longitude is arbitrary, so we select the central meridian
+        let lam = if cos_phi.abs() < 1e-12 {
+            0.
+        } else {
+            (coord[0] - x_0) / (r * cos_phi)
+        };
+        if lam.abs() > PI + 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        coord[0] = angular::normalize_symmetric(lam + lon_0);
+        coord[1] = phi.clamp(-FRAC_PI_2, FRAC_PI_2);
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    OpParameter::Real { key: "lon_0", default: Some(0_f64) },
+    OpParameter::Real { key: "x_0",   default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    Op::plain(parameters, InnerOp(fwd), Some(InnerOp(inv)), &GAMUT, ctx)
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn sinu() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("sinu ellps=sphere lon_0=10")?;
+        let r = 6_370_997.;
+
+        let geo = [
+            Coor4D::geo(0., 100., 0., 0.),
+            Coor4D::geo(60., 70., 0., 0.),
+            Coor4D::geo(-45., -120., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][0], r * FRAC_PI_2, abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 0., abs <= 1e-6);
+        assert_float_eq!(operands[1][0], r * PI / 3. * 0.5, abs <= 1e-6);
+        assert_float_eq!(operands[1][1], r * PI / 3., abs <= 1e-6);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+
+        // Outside of the map area
+        let mut operands = [Coor4D::raw(r * 3., r * 1.5, 0., 0.)];
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn factors() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("sinu ellps=sphere")?;
+        let ellps = Ellipsoid::named("sphere")?;
+
+        // Equal area, and true scale along all parallels
+        let at = Coor2D::geo(50., 60.);
+        let factors =
+            Jacobian::new(&ctx, op, [1f64.to_degrees(), 1.], [false, false], ellps, at)?.factors();
+        assert_float_eq!(factors.areal_scale, 1., abs <= 1e-8);
+        assert_float_eq!(factors.parallel_scale, 1., abs <= 1e-8);
+        assert!(factors.angular_distortion > 0.1);
+        Ok(())
+    }
+}
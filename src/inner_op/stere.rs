@@ -0,0 +1,472 @@
+//! Stereographic and Universal Polar Stereographic.
+//!
+//! The polar aspects implement EPSG coordinate operation methods 9810, 9829
+//! and 9830 (Polar Stereographic variants A, B and C), following
+//! [IOGP, 2019](crate::Bibliography::Iogp19), pp. 85-91. The oblique and
+//! equatorial aspects follow the PROJ implementation, i.e. a conformal
+//! mapping to the sphere, followed by a spherical stereographic projection.
+use crate::authoring::*;
+use std::f64::consts::FRAC_PI_2;
+
+const EPS10: f64 = 1e-10;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let Ok(y_0) = op.params.real("y_pole") else {
+        return 0;
+    };
+    let Ok(akm1) = op.params.real("akm1") else {
+        return 0;
+    };
+    let Ok(conformal) = op.params.fourier_coefficients("conformal") else {
+        return 0;
+    };
+
+    let north_polar = op.params.boolean("north_polar");
+    let south_polar = op.params.boolean("south_polar");
+    let equatorial = op.params.boolean("equatorial");
+    let (sin_chi_0, cos_chi_0) = op.params.real("chi_0").unwrap_or(0.).sin_cos();
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let (sin_lon, cos_lon) = (coord[0] - lon_0).sin_cos();
+        let lat = coord[1];
+
+        // The polar aspects: Eqs. for variants A and B are identical, except
+        // for the determination of akm1 (which includes the scale factor)
+        if north_polar || south_polar {
+            // Map the south polar case onto the north polar
+            let (lat, cos_lon) = if south_polar {
+                (-lat, -cos_lon)
+            } else {
+                (lat, cos_lon)
+            };
+
+            // The antipodal pole is at infinity
+            if (lat + FRAC_PI_2).abs() < EPS10 {
+                operands.set_coord(i, &Coor4D::nan());
+                continue;
+            }
+
+            let rho = akm1 * ancillary::ts(lat.sin_cos(), e);
+            coord[0] = x_0 + a * rho * sin_lon;
+            coord[1] = y_0 - a * rho * cos_lon;
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // The oblique and equatorial aspects
+        let chi = ellps.latitude_geographic_to_conformal(lat, &conformal);
+        let (sin_chi, cos_chi) = chi.sin_cos();
+        let denom = if equatorial {
+            1. + cos_chi * cos_lon
+        } else {
+            cos_chi_0 * (1. + sin_chi_0 * sin_chi + cos_chi_0 * cos_chi * cos_lon)
+        };
+
+        // The antipode of the origin is at infinity
+        if denom.abs() < EPS10 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        let aa = akm1 / denom;
+        let y = if equatorial {
+            aa * sin_chi
+        } else {
+            aa * (cos_chi_0 * sin_chi - sin_chi_0 * cos_chi * cos_lon)
+        };
+
+        coord[0] = x_0 + a * aa * cos_chi * sin_lon;
+        coord[1] = y_0 + a * y;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let a = ellps.semimajor_axis();
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let Ok(y_0) = op.params.real("y_pole") else {
+        return 0;
+    };
+    let Ok(akm1) = op.params.real("akm1") else {
+        return 0;
+    };
+    let Ok(conformal) = op.params.fourier_coefficients("conformal") else {
+        return 0;
+    };
+
+    let north_polar = op.params.boolean("north_polar");
+    let south_polar = op.params.boolean("south_polar");
+    let (sin_chi_0, cos_chi_0) = op.params.real("chi_0").unwrap_or(0.).sin_cos();
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let x = (coord[0] - x_0) / a;
+        let y = (coord[1] - y_0) / a;
+        let rho = x.hypot(y);
+
+        if north_polar || south_polar {
+            let lat = if rho == 0. {
+                FRAC_PI_2
+            } else {
+                ancillary::pj_phi2(rho / akm1, e)
+            };
+            if lat.is_nan() {
+                operands.set_coord(i, &Coor4D::nan());
+                continue;
+            }
+
+            // At the pole, the longitude is arbitrary, so we select the central meridian
+            let lon = if rho == 0. {
+                0.
+            } else if south_polar {
+                x.atan2(y)
+            } else {
+                x.atan2(-y)
+            };
+
+            coord[0] = angular::normalize_symmetric(lon + lon_0);
+            coord[1] = if south_polar { -lat } else { lat };
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        // The oblique and equatorial aspects: First the conformal latitude...
+        let c = 2. * (rho * cos_chi_0).atan2(akm1);
+        let (sin_c, cos_c) = c.sin_cos();
+        let chi = if rho == 0. {
+            (cos_c * sin_chi_0).asin()
+        } else {
+            (cos_c * sin_chi_0 + y * sin_c * cos_chi_0 / rho).asin()
+        };
+
+        // ...then the longitude
+        let lon = if rho == 0. {
+            0.
+        } else {
+            (x * sin_c).atan2(rho * cos_chi_0 * cos_c - y * sin_chi_0 * sin_c)
+        };
+
+        coord[0] = angular::normalize_symmetric(lon + lon_0);
+        coord[1] = ellps.latitude_conformal_to_geographic(chi, &conformal);
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 9] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Flag { key: "variant_c" },
+    OpParameter::Text { key: "ellps",  default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_0",  default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0",  default: Some(0_f64) },
+    OpParameter::Real { key: "lat_ts", default: Some(f64::NAN) },
+
+    OpParameter::Real { key: "x_0",    default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",    default: Some(0_f64) },
+
+    OpParameter::Real { key: "k_0",    default: Some(1_f64) },
+];
+
+#[rustfmt::skip]
+pub const UPS_GAMUT: [OpParameter; 3] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Flag { key: "south" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let params = ParsedParameters::new(parameters, &GAMUT)?;
+    let mut op = Op {
+        descriptor: OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv))),
+        params,
+        steps: Vec::<Op>::new(),
+        id: OpHandle::new(),
+    };
+    precompute(&mut op)?;
+    Ok(op)
+}
+
+// ----- C O N S T R U C T O R,   U P S ------------------------------------------------
+
+pub fn ups(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &UPS_GAMUT)?;
+
+    // The scaling factor is 0.994 by definition of UPS
+    params.real.insert("k_0", 0.994);
+
+    // The projection is centered at the pole, with the Greenwich meridian "down"
+    // (i.e. towards the false origin) for the northern aspect, and "up" for the
+    // southern
+    let lat_0 = if params.boolean("south") { -90. } else { 90. };
+    params.real.insert("lat_0", lat_0);
+    params.real.insert("lon_0", 0.);
+    params.real.insert("lat_ts", f64::NAN);
+
+    // The false easting and northing are both 2000000 m by definition of UPS
+    params.real.insert("x_0", 2_000_000.);
+    params.real.insert("y_0", 2_000_000.);
+
+    let mut op = Op {
+        descriptor: OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv))),
+        params,
+        steps: Vec::<Op>::new(),
+        id: OpHandle::new(),
+    };
+    precompute(&mut op)?;
+    Ok(op)
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// Common setup workhorse between ups and the plain stere
+fn precompute(op: &mut Op) -> Result<(), Error> {
+    let def = &op.descriptor.definition;
+    let params = &mut op.params;
+    let ellps = params.ellps(0);
+    let e = ellps.eccentricity();
+    let es = ellps.eccentricity_squared();
+    let a = ellps.semimajor_axis();
+    let k_0 = params.k(0);
+    let lat_0 = params.lat(0).to_radians();
+    let y_0 = params.y(0);
+
+    if lat_0.abs() > FRAC_PI_2 + EPS10 {
+        warn!("Stere: Bad central latitude!");
+        return Err(Error::BadParam("lat_0".to_string(), def.clone()));
+    }
+
+    let polar = (lat_0.abs() - FRAC_PI_2).abs() < EPS10;
+    let conformal = ellps.coefficients_for_conformal_latitude_computations();
+    params.fourier_coefficients.insert("conformal", conformal);
+
+    // The oblique and equatorial aspects
+    if !polar {
+        if params.boolean("variant_c") {
+            warn!("Stere: variant_c requires a polar aspect");
+            return Err(Error::BadParam("variant_c".to_string(), def.clone()));
+        }
+        if lat_0.abs() < EPS10 {
+            params.boolean.insert("equatorial");
+        }
+
+        // Conformal latitude of the origin, and the scaled radius of the
+        // conformal sphere (times 2)
+        let chi_0 = ellps.latitude_geographic_to_conformal(lat_0, &conformal);
+        let akm1 = 2. * k_0 * ancillary::pj_msfn(lat_0.sin_cos(), es);
+        params.real.insert("chi_0", chi_0);
+        params.real.insert("akm1", akm1);
+        params.real.insert("y_pole", y_0);
+        return Ok(());
+    }
+
+    let south = lat_0 < 0.;
+    if south {
+        params.boolean.insert("south_polar");
+    } else {
+        params.boolean.insert("north_polar");
+    }
+
+    // Latitude of the standard parallel - mirrored onto the northern hemisphere
+    // for the south polar case. Variant A, if not given
+    let mut lat_ts = params.real("lat_ts")?.to_radians();
+    if lat_ts.is_nan() {
+        lat_ts = FRAC_PI_2;
+    }
+    let lat_ts = lat_ts.abs();
+    if !(EPS10..=FRAC_PI_2 + EPS10).contains(&lat_ts) {
+        warn!("Stere: Bad latitude of true scale!");
+        return Err(Error::BadParam("lat_ts".to_string(), def.clone()));
+    }
+
+    // Variant A: Scale factor given at the pole
+    let akm1 = if (lat_ts - FRAC_PI_2).abs() < EPS10 {
+        2. * k_0 / ((1. + e).powf(1. + e) * (1. - e).powf(1. - e)).sqrt()
+    }
+    // Variants B and C: Scale is unity along the standard parallel, i.e. mF / tF
+    else {
+        let sc = lat_ts.sin_cos();
+        ancillary::pj_msfn(sc, es) / ancillary::ts(sc, e)
+    };
+    params.real.insert("akm1", akm1);
+
+    // Variant C: The false northing is given at the standard parallel, rather
+    // than at the pole. Hence, we must offset by the radius of the parallel, 𝜌F
+    let mut y_pole = y_0;
+    if params.boolean("variant_c") {
+        let rho_f = a * ancillary::pj_msfn(lat_ts.sin_cos(), es);
+        y_pole += if south { -rho_f } else { rho_f };
+    }
+    params.real.insert("y_pole", y_pole);
+    Ok(())
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn variant_a() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:5041 WGS 84 / UPS North (E,N): The IOGP example for variant A
+        let op = ctx.op("stere lat_0=90 lon_0=0 k_0=0.994 x_0=2000000 y_0=2000000 ellps=WGS84")?;
+        let geo = [Coor4D::geo(73., 44., 0., 0.)];
+        let projected = [Coor4D::raw(3_320_416.75, 632_668.43, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.01);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+
+        // The pole itself is at the false origin
+        let mut operands = [Coor4D::geo(90., 44., 0., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][0], 2e6, abs <= 1e-9);
+        assert_float_eq!(operands[0][1], 2e6, abs <= 1e-9);
+
+        // ...while the South Pole is at infinity
+        let mut operands = [Coor4D::geo(-90., 44., 0., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn variant_b() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:3032 WGS 84 / Australian Antarctic Polar Stereographic
+        let op =
+            ctx.op("stere lat_0=-90 lat_ts=-71 lon_0=70 x_0=6000000 y_0=6000000 ellps=WGS84")?;
+        let geo = [Coor4D::geo(-75., 120., 0., 0.)];
+        let projected = [Coor4D::raw(7_255_380.79, 7_053_389.56, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.01);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn variant_c() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:2985 Petrels 1972 / Terre Adelie Polar Stereographic
+        let op = ctx.op(
+            "stere variant_c lat_0=-90 lat_ts=-67 lon_0=140 x_0=300000 y_0=200000 ellps=intl",
+        )?;
+        let lat = -angular::dms_to_dd(66, 36, 18.820);
+        let lon = angular::dms_to_dd(140, 4, 17.040);
+        let geo = [Coor4D::geo(lat, lon, 0., 0.)];
+        let projected = [Coor4D::raw(303_169.52, 244_055.72, 0., 0.)];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, projected[0].0, abs_all <= 0.01);
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 1);
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+
+        // variant_c is meaningless for the non-polar aspects
+        assert!(ctx.op("stere variant_c lat_0=55").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn oblique_and_equatorial() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // On the sphere, the equatorial aspect maps the point 90° from the
+        // origin to a distance of 2R from the center of projection
+        let op = ctx.op("stere lat_0=0 lon_0=0 ellps=sphere")?;
+        let mut operands = [Coor4D::geo(0., 90., 0., 0.), Coor4D::geo(90., 0., 0., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][0], 2. * 6_370_997., abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 0., abs <= 1e-6);
+        assert_float_eq!(operands[1][0], 0., abs <= 1e-6);
+        assert_float_eq!(operands[1][1], 2. * 6_370_997., abs <= 1e-6);
+
+        // Roundtrip in the oblique ellipsoidal case
+        let op = ctx.op("stere lat_0=55 lon_0=12 k_0=0.9999 x_0=500000 y_0=100000")?;
+        let geo = [
+            Coor4D::geo(55., 12., 0., 0.),
+            Coor4D::geo(59., 18., 0., 0.),
+            Coor4D::geo(50., -5., 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][0], 500_000., abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 100_000., abs <= 1e-6);
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ups() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // UPS is variant A with a fixed set of parameters
+        let op = ctx.op("ups ellps=WGS84")?;
+        let geo = [Coor4D::geo(73., 44., 0., 0.)];
+        let mut operands = geo;
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][0], 3_320_416.75, abs <= 0.01);
+        assert_float_eq!(operands[0][1], 632_668.43, abs <= 0.01);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+
+        // The southern aspect is the mirror image of the northern
+        let op = ctx.op("ups south ellps=WGS84")?;
+        let geo = [Coor4D::geo(-73., 44., 0., 0.)];
+        let mut operands = geo;
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_float_eq!(operands[0][0], 3_320_416.75, abs <= 0.01);
+        assert_float_eq!(operands[0][1], 4e6 - 632_668.43, abs <= 0.01);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert_float_eq!(operands[0].0, geo[0].0, abs_all <= 1e-12);
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+//! Oblique stereographic, a.k.a. double stereographic (EPSG method 9809).
+//!
+//! The ellipsoid is conformally mapped to the Gaussian sphere, followed by
+//! a spherical stereographic projection. Follows
+//! [IOGP, 2019](crate::Bibliography::Iogp19), pp. 91-94, but uses the
+//! isometric latitude helpers for the mappings to and from the sphere,
+//! to avoid iteration in the inverse case.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    // Grab pre-computed values
+    let Ok(n) = op.params.real("n") else {
+        return 0;
+    };
+    let Ok(c) = op.params.real("c") else {
+        return 0;
+    };
+    let Ok(chi_0) = op.params.real("chi_0") else {
+        return 0;
+    };
+    let Ok(two_r_k0) = op.params.real("two_r_k0") else {
+        return 0;
+    };
+    let (sin_chi_0, cos_chi_0) = chi_0.sin_cos();
+    let half_ln_c = c.ln() / 2.;
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+
+        // Geographic to conformal spherical coordinates
+        let psi = -ancillary::ts(coord[1].sin_cos(), e).ln();
+        let chi = ancillary::gudermannian::fwd(n * psi + half_ln_c);
+        let (sin_dlon, cos_dlon) = (n * (coord[0] - lon_0)).sin_cos();
+        let (sin_chi, cos_chi) = chi.sin_cos();
+
+        // Spherical stereographic. The antipode of the origin is at infinity
+        let b = 1. + sin_chi * sin_chi_0 + cos_chi * cos_chi_0 * cos_dlon;
+        if b.abs() < 1e-12 {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        }
+
+        coord[0] = x_0 + two_r_k0 * cos_chi * sin_dlon / b;
+        coord[1] = y_0 + two_r_k0 * (sin_chi * cos_chi_0 - cos_chi * sin_chi_0 * cos_dlon) / b;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+
+    successes
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let ellps = op.params.ellps(0);
+    let e = ellps.eccentricity();
+    let lon_0 = op.params.lon(0).to_radians();
+    let x_0 = op.params.x(0);
+    let y_0 = op.params.y(0);
+
+    // Grab pre-computed values
+    let Ok(n) = op.params.real("n") else {
+        return 0;
+    };
+    let Ok(c) = op.params.real("c") else {
+        return 0;
+    };
+    let Ok(chi_0) = op.params.real("chi_0") else {
+        return 0;
+    };
+    let Ok(two_r_k0) = op.params.real("two_r_k0") else {
+        return 0;
+    };
+    let (sin_chi_0, cos_chi_0) = chi_0.sin_cos();
+    let half_ln_c = c.ln() / 2.;
+
+    let mut successes = 0_usize;
+    let length = operands.len();
+
+    for i in 0..length {
+        let mut coord = operands.get_coord(i);
+        let x = (coord[0] - x_0) / two_r_k0;
+        let y = (coord[1] - y_0) / two_r_k0;
+        let rho = x.hypot(y);
+
+        // Inverse spherical stereographic
+        let (chi, dlon) = if rho == 0. {
+            (chi_0, 0.)
+        } else {
+            let (sin_c, cos_c) = (2. * rho.atan()).sin_cos();
+            let chi = (cos_c * sin_chi_0 + y * sin_c * cos_chi_0 / rho).asin();
+            let dlon = (x * sin_c).atan2(rho * cos_chi_0 * cos_c - y * sin_chi_0 * sin_c);
+            (chi, dlon)
+        };
+
+        // Conformal spherical to geographic coordinates
+        let psi = (ancillary::gudermannian::inv(chi) - half_ln_c) / n;
+        let lat = ancillary::sinhpsi_to_tanphi(psi.sinh(), e).atan();
+
+        coord[0] = angular::normalize_symmetric(dlon / n + lon_0);
+        coord[1] = lat;
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 7] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps",  default: Some("GRS80") },
+
+    OpParameter::Real { key: "lat_0",  default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0",  default: Some(0_f64) },
+    OpParameter::Real { key: "x_0",    default: Some(0_f64) },
+    OpParameter::Real { key: "y_0",    default: Some(0_f64) },
+
+    OpParameter::Real { key: "k_0",    default: Some(1_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    let ellps = params.ellps(0);
+    let e = ellps.eccentricity();
+    let es = ellps.eccentricity_squared();
+    let a = ellps.semimajor_axis();
+    let k_0 = params.k(0);
+    let lat_0 = params.lat(0).to_radians();
+    if lat_0.abs() > std::f64::consts::FRAC_PI_2 {
+        return Err(Error::BadParam("lat_0".to_string(), def.clone()));
+    }
+    let (sin_lat_0, cos_lat_0) = lat_0.sin_cos();
+
+    // Radius of the conformal sphere: The geometric mean of the principal
+    // radii of curvature at the origin
+    let radius = a * (1. - es).sqrt() / (1. - es * sin_lat_0 * sin_lat_0);
+
+    // Exponent and constant factor of the mapping to the sphere
+    let n = (1. + es * cos_lat_0.powi(4) / (1. - es)).sqrt();
+    let w1 = (-2. * n * ancillary::ts((sin_lat_0, cos_lat_0), e).ln()).exp();
+    let sin_chi = (w1 - 1.) / (w1 + 1.);
+    let c = (n + sin_lat_0) * (1. - sin_chi) / ((n - sin_lat_0) * (1. + sin_chi));
+
+    // Conformal latitude of the origin
+    let w2 = c * w1;
+    let chi_0 = ((w2 - 1.) / (w2 + 1.)).asin();
+
+    params.real.insert("n", n);
+    params.real.insert("c", c);
+    params.real.insert("chi_0", chi_0);
+    params.real.insert("two_r_k0", 2. * radius * k_0);
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn sterea() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:28992 Amersfoort / RD New. The IOGP example for method 9809
+        let lat_0 = angular::dms_to_dd(52, 9, 22.178);
+        let lon_0 = angular::dms_to_dd(5, 23, 15.5);
+        let definition = format!(
+            "sterea lat_0={lat_0} lon_0={lon_0} k_0=0.9999079 x_0=155000 y_0=463000 ellps=bessel"
+        );
+        let op = ctx.op(&definition)?;
+
+        let geo = [
+            Coor4D::geo(53., 6., 0., 0.),
+            Coor4D::geo(lat_0, lon_0, 0., 0.),
+        ];
+        let projected = [
+            Coor4D::raw(196_105.283, 557_057.739, 0., 0.),
+            Coor4D::raw(155_000., 463_000., 0., 0.),
+        ];
+
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 2);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, projected[i].0, abs_all <= 1e-3);
+        }
+
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 2);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn proj_syntax() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+
+        // EPSG:2953 NAD83(CSRS) / New Brunswick Stereographic, in PROJ syntax
+        let definition = parse_proj(
+            "+proj=sterea +lat_0=46.5 +lon_0=-66.5 +k=0.999912 +x_0=2500000 +y_0=7500000 +ellps=GRS80",
+        )?;
+        assert_eq!(
+            definition,
+            "sterea lat_0=46.5 lon_0=-66.5 k_0=0.999912 x_0=2500000 y_0=7500000 ellps=GRS80"
+        );
+        let op = ctx.op(&definition)?;
+
+        // The origin maps to the false origin
+        let geo = [
+            Coor4D::geo(46.5, -66.5, 0., 0.),
+            Coor4D::geo(45.3, -64.1, 0., 0.),
+            Coor4D::geo(47.9, -68.7, 0., 0.),
+        ];
+        let mut operands = geo;
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 3);
+        assert_float_eq!(operands[0][0], 2_500_000., abs <= 1e-6);
+        assert_float_eq!(operands[0][1], 7_500_000., abs <= 1e-6);
+
+        // Roundtrip
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 3);
+        for i in 0..operands.len() {
+            assert_float_eq!(operands[i].0, geo[i].0, abs_all <= 1e-12);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,567 @@
+//! Triangulation based transformation, following the PROJ operator of the
+//! same name, and reading the triangulation from a PROJ style JSON file.
+//!
+//! The coordinates are shifted by barycentric interpolation of the vertex
+//! values of the triangle containing the point. In the forward direction,
+//! the triangle is located using the source coordinates of the vertices, and
+//! in the inverse direction using the target coordinates.
+//!
+//! The operator works directly on the coordinate values, in the units of the
+//! triangulation file - typically projected coordinates, in meters.
+use crate::authoring::*;
+use serde_json::Value;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    shift(op, operands, Fwd)
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    shift(op, operands, Inv)
+}
+
+// Forward and inverse differ only in which vertex coordinates are used to
+// locate the triangle, and which are interpolated
+fn shift(op: &Op, operands: &mut dyn CoordinateSet, direction: Direction) -> usize {
+    let Ok(vertices) = op.params.series("VERTICES") else {
+        return 0;
+    };
+    let Ok(triangles) = op.params.series("TRIANGLES") else {
+        return 0;
+    };
+    let (index, from, to, sign) = match direction {
+        Fwd => (op.params.series("FWD_INDEX"), SOURCE, TARGET, 1.),
+        Inv => (op.params.series("INV_INDEX"), TARGET, SOURCE, -1.),
+    };
+    let Ok(index) = index else {
+        return 0;
+    };
+    let horizontal = op.params.boolean("horizontal");
+    let vertical = op.params.boolean("vertical");
+    let fallback = op.params.natural("fallback").unwrap_or(NONE);
+    let tin = Tin {
+        vertices,
+        triangles,
+    };
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let (x, y) = (coord[0], coord[1]);
+        let Some((t, w)) = tin
+            .locate(index, from, x, y)
+            .or_else(|| tin.nearest(fallback, from, x, y))
+        else {
+            operands.set_coord(i, &Coor4D::nan());
+            continue;
+        };
+
+        if horizontal {
+            coord[0] = tin.interpolate(t, &w, to);
+            coord[1] = tin.interpolate(t, &w, to + 1);
+        }
+        if vertical {
+            coord[2] += sign * tin.interpolate(t, &w, OFFSET_Z);
+        }
+        operands.set_coord(i, &coord);
+        successes += 1;
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 2] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "file", default: None },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+
+    let buf = ctx.get_blob(&params.text("file")?)?;
+    read_triangulation(&buf, &mut params)?;
+
+    // Spatial indices for locating the triangles in each direction
+    let tin = Tin {
+        vertices: params.series("VERTICES")?,
+        triangles: params.series("TRIANGLES")?,
+    };
+    let fwd_index = tin.index(SOURCE);
+    let inv_index = tin.index(TARGET);
+    params.series.insert("FWD_INDEX", fwd_index);
+    params.series.insert("INV_INDEX", inv_index);
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// The vertices are stored with a fixed stride, irrespective of the columns given
+// in the file: source_x, source_y, target_x, target_y, offset_z
+const STRIDE: usize = 5;
+const SOURCE: usize = 0;
+const TARGET: usize = 2;
+const OFFSET_Z: usize = 4;
+
+// Fallback strategies for points outside of the triangulation
+const NONE: usize = 0;
+const NEAREST_SIDE: usize = 1;
+const NEAREST_CENTROID: usize = 2;
+
+// Read a PROJ style triangulation file, and store the vertices, the triangles,
+// and the properties of the transformation in `params`
+fn read_triangulation(buf: &[u8], params: &mut ParsedParameters) -> Result<(), Error> {
+    let invalid = |message: &str| Error::Invalid(format!("tinshift: {message}"));
+    let json: Value =
+        serde_json::from_slice(buf).map_err(|e| invalid(&format!("malformed JSON: {e}")))?;
+
+    if json["file_type"] != "triangulation_file" {
+        return Err(invalid("not a triangulation file"));
+    }
+    let version = json["format_version"].as_str().unwrap_or_default();
+    if !["1.0", "1.1"].contains(&version) {
+        return Err(invalid(&format!("unsupported format version '{version}'")));
+    }
+
+    // Which components are transformed?
+    let Some(components) = json["transformed_components"].as_array() else {
+        return Err(invalid("missing 'transformed_components'"));
+    };
+    let horizontal = components.iter().any(|c| c == "horizontal");
+    let vertical = components.iter().any(|c| c == "vertical");
+    if !horizontal && !vertical {
+        return Err(invalid("no transformed components"));
+    }
+
+    let fallback = match json["fallback_strategy"].as_str().unwrap_or("none") {
+        "none" => NONE,
+        "nearest_side" => NEAREST_SIDE,
+        "nearest_centroid" => NEAREST_CENTROID,
+        other => return Err(invalid(&format!("unknown fallback strategy '{other}'"))),
+    };
+
+    // Locate the columns we need
+    let columns = |key: &str| -> Result<Vec<&str>, Error> {
+        let Some(columns) = json[key].as_array() else {
+            return Err(invalid(&format!("missing '{key}'")));
+        };
+        Ok(columns
+            .iter()
+            .map(|c| c.as_str().unwrap_or_default())
+            .collect())
+    };
+    let vertices_columns = columns("vertices_columns")?;
+    let column = |name: &str| vertices_columns.iter().position(|&c| c == name);
+    let required =
+        |name: &str| column(name).ok_or_else(|| invalid(&format!("missing column '{name}'")));
+
+    let source = [required("source_x")?, required("source_y")?];
+    let target = if horizontal {
+        Some([required("target_x")?, required("target_y")?])
+    } else {
+        None
+    };
+    // The vertical offset is given directly, or as the difference between target and source
+    let offset = if !vertical {
+        None
+    } else if let Some(offset_z) = column("offset_z") {
+        Some((offset_z, None))
+    } else {
+        Some((required("target_z")?, Some(required("source_z")?)))
+    };
+
+    let triangles_columns = columns("triangles_columns")?;
+    let mut corners = [0_usize; 3];
+    for (i, name) in ["idx_vertex1", "idx_vertex2", "idx_vertex3"]
+        .iter()
+        .enumerate()
+    {
+        corners[i] = triangles_columns
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| invalid(&format!("missing column '{name}'")))?;
+    }
+
+    // Read the vertices into the fixed stride layout
+    let Some(rows) = json["vertices"].as_array() else {
+        return Err(invalid("missing 'vertices'"));
+    };
+    let mut vertices = Vec::with_capacity(rows.len() * STRIDE);
+    for row in rows {
+        let row: Vec<f64> = row
+            .as_array()
+            .map(|r| r.iter().map(|v| v.as_f64().unwrap_or(f64::NAN)).collect())
+            .unwrap_or_default();
+        if row.len() != vertices_columns.len() || row.iter().any(|v| v.is_nan()) {
+            return Err(invalid("malformed vertex"));
+        }
+        let (sx, sy) = (row[source[0]], row[source[1]]);
+        let (tx, ty) = target.map_or((sx, sy), |t| (row[t[0]], row[t[1]]));
+        let dz = match offset {
+            None => 0.,
+            Some((z, None)) => row[z],
+            Some((z, Some(source_z))) => row[z] - row[source_z],
+        };
+        vertices.extend([sx, sy, tx, ty, dz]);
+    }
+
+    // Read the triangles, checking that they refer to existing vertices
+    let Some(rows) = json["triangles"].as_array() else {
+        return Err(invalid("missing 'triangles'"));
+    };
+    let mut triangles = Vec::with_capacity(rows.len() * 3);
+    for row in rows {
+        let row: Vec<u64> = row
+            .as_array()
+            .map(|r| r.iter().map(|v| v.as_u64().unwrap_or(u64::MAX)).collect())
+            .unwrap_or_default();
+        if row.len() != triangles_columns.len() {
+            return Err(invalid("malformed triangle"));
+        }
+        for corner in corners {
+            let vertex = row[corner];
+            if vertex as usize >= vertices.len() / STRIDE {
+                return Err(invalid("triangle refers to non-existing vertex"));
+            }
+            triangles.push(vertex as f64);
+        }
+    }
+    if triangles.is_empty() {
+        return Err(invalid("empty triangulation"));
+    }
+
+    params.series.insert("VERTICES", vertices);
+    params.series.insert("TRIANGLES", triangles);
+    params.natural.insert("fallback", fallback);
+    if horizontal {
+        params.boolean.insert("horizontal");
+    }
+    if vertical {
+        params.boolean.insert("vertical");
+    }
+    Ok(())
+}
+
+// A view of the triangulation stored in the operator parameters
+struct Tin<'a> {
+    vertices: &'a [f64],
+    triangles: &'a [f64],
+}
+
+impl Tin<'_> {
+    fn len(&self) -> usize {
+        self.triangles.len() / 3
+    }
+
+    // The index of the k'th corner vertex of triangle t
+    fn corner(&self, t: usize, k: usize) -> usize {
+        self.triangles[3 * t + k] as usize
+    }
+
+    // The coordinate pair starting at column `col` of the k'th corner of triangle t
+    fn point(&self, t: usize, k: usize, col: usize) -> (f64, f64) {
+        let v = STRIDE * self.corner(t, k) + col;
+        (self.vertices[v], self.vertices[v + 1])
+    }
+
+    // Barycentric interpolation of column `col` in triangle t
+    fn interpolate(&self, t: usize, w: &[f64; 3], col: usize) -> f64 {
+        (0..3)
+            .map(|k| w[k] * self.vertices[STRIDE * self.corner(t, k) + col])
+            .sum()
+    }
+
+    // The barycentric coordinates of (x, y) with respect to triangle t.
+    // None for degenerate triangles
+    fn weights(&self, t: usize, col: usize, x: f64, y: f64) -> Option<[f64; 3]> {
+        let (a, b, c) = (
+            self.point(t, 0, col),
+            self.point(t, 1, col),
+            self.point(t, 2, col),
+        );
+        let det = (b.1 - c.1) * (a.0 - c.0) + (c.0 - b.0) * (a.1 - c.1);
+        if det == 0. {
+            return None;
+        }
+        let w0 = ((b.1 - c.1) * (x - c.0) + (c.0 - b.0) * (y - c.1)) / det;
+        let w1 = ((c.1 - a.1) * (x - c.0) + (a.0 - c.0) * (y - c.1)) / det;
+        Some([w0, w1, 1. - w0 - w1])
+    }
+
+    // Build a spatial index over the triangles, as given by the coordinates
+    // starting at column `col`: A uniform grid of buckets over the bounding
+    // box of the triangulation, with each bucket listing the triangles
+    // overlapping it. For storage in the operator parameters, the index is
+    // flattened into a series, consisting of the grid parameters (x0, y0,
+    // cell width, cell height, columns, rows), the start of each bucket in
+    // the item list (plus an end marker), and the item list itself
+    fn index(&self, col: usize) -> Vec<f64> {
+        let bbox = |t: usize| {
+            let (a, b, c) = (
+                self.point(t, 0, col),
+                self.point(t, 1, col),
+                self.point(t, 2, col),
+            );
+            [
+                a.0.min(b.0).min(c.0),
+                a.1.min(b.1).min(c.1),
+                a.0.max(b.0).max(c.0),
+                a.1.max(b.1).max(c.1),
+            ]
+        };
+        let mut extent = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for t in 0..self.len() {
+            let b = bbox(t);
+            extent = [
+                extent[0].min(b[0]),
+                extent[1].min(b[1]),
+                extent[2].max(b[2]),
+                extent[3].max(b[3]),
+            ];
+        }
+
+        // Aim at a handful of triangles per bucket
+        let n = ((self.len() as f64).sqrt().ceil() as usize).max(1);
+        let cell = |size: f64| if size > 0. { size / n as f64 } else { 1. };
+        let (width, height) = (cell(extent[2] - extent[0]), cell(extent[3] - extent[1]));
+        let bucket = |v: f64, origin: f64, size: f64| (((v - origin) / size) as usize).min(n - 1);
+
+        let mut buckets = vec![Vec::new(); n * n];
+        for t in 0..self.len() {
+            let b = bbox(t);
+            for row in bucket(b[1], extent[1], height)..=bucket(b[3], extent[1], height) {
+                for column in bucket(b[0], extent[0], width)..=bucket(b[2], extent[0], width) {
+                    buckets[row * n + column].push(t as f64);
+                }
+            }
+        }
+
+        let mut index = vec![extent[0], extent[1], width, height, n as f64, n as f64];
+        let mut start = 0;
+        for b in &buckets {
+            index.push(start as f64);
+            start += b.len();
+        }
+        index.push(start as f64);
+        index.extend(buckets.concat());
+        index
+    }
+
+    // Locate the triangle containing (x, y), using the index built by `index(col)`
+    fn locate(&self, index: &[f64], col: usize, x: f64, y: f64) -> Option<(usize, [f64; 3])> {
+        let (columns, rows) = (index[4] as usize, index[5] as usize);
+        let column = ((x - index[0]) / index[2]).floor();
+        let row = ((y - index[1]) / index[3]).floor();
+
+        // Points on the upper and right hand boundaries belong to the last bucket
+        let clamp = |i: f64, n: usize| {
+            if i == n as f64 {
+                Some(n - 1)
+            } else if (0. ..n as f64).contains(&i) {
+                Some(i as usize)
+            } else {
+                None
+            }
+        };
+        let bucket = clamp(row, rows)? * columns + clamp(column, columns)?;
+
+        let starts = &index[6..];
+        let items = &index[7 + rows * columns..];
+        let candidates = &items[starts[bucket] as usize..starts[bucket + 1] as usize];
+        for &t in candidates {
+            let t = t as usize;
+            let Some(w) = self.weights(t, col, x, y) else {
+                continue;
+            };
+            if w.iter().all(|&w| w >= -1e-12) {
+                return Some((t, w));
+            }
+        }
+        None
+    }
+
+    // Fallback for points outside of the triangulation: Extrapolate from the
+    // triangle with the nearest side, or with the nearest centroid
+    fn nearest(&self, strategy: usize, col: usize, x: f64, y: f64) -> Option<(usize, [f64; 3])> {
+        if strategy == NONE {
+            return None;
+        }
+        let distance = |t: usize| {
+            let p = [
+                self.point(t, 0, col),
+                self.point(t, 1, col),
+                self.point(t, 2, col),
+            ];
+            if strategy == NEAREST_CENTROID {
+                let cx = (p[0].0 + p[1].0 + p[2].0) / 3.;
+                let cy = (p[0].1 + p[1].1 + p[2].1) / 3.;
+                return (x - cx).hypot(y - cy);
+            }
+            (0..3)
+                .map(|k| segment_distance((x, y), p[k], p[(k + 1) % 3]))
+                .fold(f64::INFINITY, f64::min)
+        };
+
+        let mut best = None;
+        let mut shortest = f64::INFINITY;
+        for t in 0..self.len() {
+            let d = distance(t);
+            if d < shortest {
+                if let Some(w) = self.weights(t, col, x, y) {
+                    shortest = d;
+                    best = Some((t, w));
+                }
+            }
+        }
+        best
+    }
+}
+
+// The distance from the point p to the line segment from a to b
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length2 = dx * dx + dy * dy;
+    let s = if length2 == 0. {
+        0.
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length2).clamp(0., 1.)
+    };
+    (p.0 - a.0 - s * dx).hypot(p.1 - a.1 - s * dy)
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn tinshift() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let op = ctx.op("tinshift file=test_tinshift.json")?;
+
+        // The vertices are reproduced exactly, and the interior interpolated
+        let mut operands = [
+            Coor4D::raw(500., 500., 100., 0.),
+            Coor4D::raw(1000., 1000., 100., 0.),
+            Coor4D::raw(750., 250., 100., 0.),
+            Coor4D::raw(0., 500., 100., 0.),
+        ];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 4);
+        assert_float_eq!(operands[0].0, [511., 522., 102., 0.], abs_all <= 1e-9);
+        assert_float_eq!(operands[1].0, [1012., 1024., 103., 0.], abs_all <= 1e-9);
+        assert_float_eq!(operands[2].0, [761.5, 271., 102., 0.], abs_all <= 1e-9);
+        assert_float_eq!(operands[3].0, [10., 522., 101.5, 0.], abs_all <= 1e-9);
+
+        // The inverse locates the triangle in the target coordinates
+        assert_eq!(ctx.apply(op, Inv, &mut operands)?, 4);
+        assert_float_eq!(operands[0].0, [500., 500., 100., 0.], abs_all <= 1e-9);
+        assert_float_eq!(operands[1].0, [1000., 1000., 100., 0.], abs_all <= 1e-9);
+        assert_float_eq!(operands[2].0, [750., 250., 100., 0.], abs_all <= 1e-9);
+        assert_float_eq!(operands[3].0, [0., 500., 100., 0.], abs_all <= 1e-9);
+
+        // Points outside of the triangulation are stomped on
+        let mut operands = [Coor4D::raw(-1., 500., 100., 0.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 0);
+        assert!(operands[0][0].is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn index() -> Result<(), Error> {
+        // A 10x10 grid of unit squares, each split into two triangles
+        let mut vertices = Vec::new();
+        for row in 0..11 {
+            for col in 0..11 {
+                let (x, y) = (col as f64, row as f64);
+                vertices.extend([x, y, x + 1., y + 2., 0.]);
+            }
+        }
+        let mut triangles = Vec::new();
+        for row in 0..10 {
+            for col in 0..10 {
+                let v = (row * 11 + col) as f64;
+                triangles.extend([v, v + 1., v + 12., v, v + 12., v + 11.]);
+            }
+        }
+        let tin = Tin {
+            vertices: &vertices,
+            triangles: &triangles,
+        };
+
+        let index = tin.index(SOURCE);
+        for (x, y) in [(0., 0.), (3.25, 7.5), (9.9, 0.1), (10., 10.), (5., 5.)] {
+            let (t, w) = tin.locate(&index, SOURCE, x, y).unwrap();
+            assert_float_eq!(tin.interpolate(t, &w, SOURCE), x, abs <= 1e-12);
+            assert_float_eq!(tin.interpolate(t, &w, TARGET), x + 1., abs <= 1e-12);
+            assert_float_eq!(tin.interpolate(t, &w, TARGET + 1), y + 2., abs <= 1e-12);
+        }
+        assert!(tin.locate(&index, SOURCE, -0.1, 5.).is_none());
+        assert!(tin.locate(&index, SOURCE, 5., 10.1).is_none());
+
+        // The fallback strategies extrapolate from the nearest triangle
+        for strategy in [NEAREST_SIDE, NEAREST_CENTROID] {
+            let (t, w) = tin.nearest(strategy, SOURCE, 12., 5.5).unwrap();
+            assert_float_eq!(tin.interpolate(t, &w, TARGET), 13., abs <= 1e-12);
+        }
+        assert!(tin.nearest(NONE, SOURCE, 12., 5.5).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        assert!(ctx.op("tinshift").is_err());
+        assert!(ctx.op("tinshift file=no_such_file.json").is_err());
+
+        // Break a valid file in various ways
+        let raw = RawParameters::new("tinshift file=test_tinshift.json", &Default::default());
+        let valid = include_str!("../../geodesy/json/test_tinshift.json");
+        let mut params = ParsedParameters::new(&raw, &GAMUT)?;
+        assert!(read_triangulation(valid.as_bytes(), &mut params).is_ok());
+        for (from, to) in [
+            ("triangulation_file", "deformation_model_file"),
+            ("\"1.1\"", "\"2.0\""),
+            ("\"none\"", "\"nearest_triangle\""),
+            ("\"offset_z\"", "\"offset_h\""),
+            ("[ 3, 0, 4 ]", "[ 3, 0, 5 ]"),
+            ("[ 3, 0, 4 ]", "[ 3, 0 ]"),
+            (
+                "[  500,  500,  511,  522, 2 ]",
+                "[  500,  500,  511,  522 ]",
+            ),
+            ("\"horizontal\", \"vertical\"", ""),
+            ("}", ""),
+        ] {
+            let mut params = ParsedParameters::new(&raw, &GAMUT)?;
+            let invalid = valid.replacen(from, to, 1);
+            assert!(invalid != valid, "{from}");
+            assert!(
+                read_triangulation(invalid.as_bytes(), &mut params).is_err(),
+                "{from}"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+//! Geocentric cartesian to topocentric (east, north, up) conversion, following
+//! the PROJ operator of the same name, and EPSG methods 9836 and 9837.
+//!
+//! The topocentric origin is given either as geocentric cartesian coordinates,
+//! `X_0, Y_0, Z_0`, or as geographic coordinates, `lat_0, lon_0, h_0`, on
+//! the ellipsoid `ellps`. The input is always geocentric cartesian, so for
+//! EPSG method 9837, a `cart` step must precede the `topocentric` step.
+use super::deformation::rotate_and_integrate_velocity;
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let Some([origin, e, n, u]) = basis(op) else {
+        return 0;
+    };
+
+    let n_operands = operands.len();
+    for i in 0..n_operands {
+        let mut coord = operands.get_coord(i);
+        let d = [
+            coord[0] - origin[0],
+            coord[1] - origin[1],
+            coord[2] - origin[2],
+        ];
+        coord[0] = e[0] * d[0] + e[1] * d[1] + e[2] * d[2];
+        coord[1] = n[0] * d[0] + n[1] * d[1] + n[2] * d[2];
+        coord[2] = u[0] * d[0] + u[1] * d[1] + u[2] * d[2];
+        operands.set_coord(i, &coord);
+    }
+    n_operands
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    let Some([origin, e, n, u]) = basis(op) else {
+        return 0;
+    };
+
+    let n_operands = operands.len();
+    for i in 0..n_operands {
+        let mut coord = operands.get_coord(i);
+        let d = [coord[0], coord[1], coord[2]];
+        for j in 0..3 {
+            coord[j] = origin[j] + e[j] * d[0] + n[j] * d[1] + u[j] * d[2];
+        }
+        operands.set_coord(i, &coord);
+    }
+    n_operands
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 8] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps", default: Some("GRS80") },
+
+    // Cartesian origin
+    OpParameter::Real { key: "X_0",   default: Some(f64::NAN) },
+    OpParameter::Real { key: "Y_0",   default: Some(f64::NAN) },
+    OpParameter::Real { key: "Z_0",   default: Some(f64::NAN) },
+
+    // Geographic origin
+    OpParameter::Real { key: "lat_0", default: Some(f64::NAN) },
+    OpParameter::Real { key: "lon_0", default: Some(f64::NAN) },
+    OpParameter::Real { key: "h_0",   default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    let ellps = params.ellps(0);
+
+    let cartesian = ["X_0", "Y_0", "Z_0"];
+    let geographic = ["lat_0", "lon_0", "h_0"];
+    let is_given = |key: &&str| params.given.contains_key(*key);
+
+    // The origin, in both cartesian and geographic form
+    let (origin, geo) = match (
+        cartesian.iter().any(is_given),
+        geographic.iter().any(is_given),
+    ) {
+        (true, true) => {
+            return Err(Error::Invalid(
+                "topocentric: Cartesian and geographic origins cannot be combined".to_string(),
+            ));
+        }
+        (true, false) => {
+            let origin = Coor4D::raw(
+                params.real("X_0")?,
+                params.real("Y_0")?,
+                params.real("Z_0")?,
+                0.,
+            );
+            (origin, ellps.geographic(&origin))
+        }
+        (false, true) => {
+            let geo = Coor4D::geo(
+                params.real("lat_0")?,
+                params.real("lon_0")?,
+                params.real("h_0")?,
+                0.,
+            );
+            (ellps.cartesian(&geo), geo)
+        }
+        (false, false) => {
+            return Err(Error::MissingParam(
+                "topocentric: X_0, Y_0, Z_0 or lat_0, lon_0".to_string(),
+            ));
+        }
+    };
+    if origin.0.iter().any(|c| c.is_nan()) {
+        return Err(Error::Invalid(format!(
+            "topocentric: Incomplete origin in '{def}'"
+        )));
+    }
+
+    // The topocentric unit vectors, expressed in the geocentric system
+    let (lon, lat) = (geo[0], geo[1]);
+    let unit = |enu: [f64; 3]| {
+        let v =
+            rotate_and_integrate_velocity(Coor4D::raw(enu[0], enu[1], enu[2], 0.), lon, lat, 1.);
+        Vec::from(&v.0[..3])
+    };
+    params.series.insert("ORIGIN", Vec::from(&origin.0[..3]));
+    params.series.insert("EAST", unit([1., 0., 0.]));
+    params.series.insert("NORTH", unit([0., 1., 0.]));
+    params.series.insert("UP", unit([0., 0., 1.]));
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- A N C I L L A R Y   F U N C T I O N S -----------------------------------------
+
+// The origin and the topocentric unit vectors, as precomputed by the constructor
+fn basis(op: &Op) -> Option<[&[f64]; 4]> {
+    Some([
+        op.params.series("ORIGIN").ok()?,
+        op.params.series("EAST").ok()?,
+        op.params.series("NORTH").ok()?,
+        op.params.series("UP").ok()?,
+    ])
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // EPSG Guidance Note 7-2, examples for methods 9836 and 9837
+    #[test]
+    fn topocentric() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let cartesian = "topocentric ellps=WGS84 X_0=3652755.3058 Y_0=319574.6799 Z_0=5201547.3536";
+        let geographic = "cart ellps=WGS84 | topocentric ellps=WGS84 lat_0=55 lon_0=5 h_0=200";
+
+        let enu = Coor4D::raw(-189013.869, -128642.040, -4220.171, 0.);
+        let cart = Coor4D::raw(3771793.968, 140253.342, 5124304.349, 0.);
+        let geo = Coor4D::geo(53.809394444, 2.129550000, 73., 0.);
+
+        let op = ctx.op(cartesian)?;
+        let mut operands = [cart];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!(enu.hypot3(&operands[0]) < 1e-3);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(cart.hypot3(&operands[0]) < 1e-8);
+
+        let op = ctx.op(geographic)?;
+        let mut operands = [geo];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!(enu.hypot3(&operands[0]) < 2e-3);
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!(geo.hypot2(&operands[0]) < 1e-12);
+
+        // The time coordinate is left untouched
+        let op = ctx.op(cartesian)?;
+        let mut operands = [Coor4D::raw(cart[0], cart[1], cart[2], 2020.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_eq!(operands[0][3], 2020.);
+        Ok(())
+    }
+
+    #[test]
+    fn bad_parameters() {
+        let mut ctx = Minimal::default();
+        assert!(ctx.op("topocentric").is_err());
+        assert!(ctx.op("topocentric X_0=1 Y_0=2").is_err());
+        assert!(ctx.op("topocentric lat_0=55 h_0=200").is_err());
+        assert!(ctx.op("topocentric X_0=1 Y_0=2 Z_0=3 lat_0=55").is_err());
+    }
+}
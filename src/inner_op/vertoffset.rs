@@ -0,0 +1,133 @@
+//! Vertical offset and slope, following the PROJ operator of the same name,
+//! and EPSG method 1046.
+//!
+//! The height offset is given by an inclined plane through the origin
+//! (`lat_0`, `lon_0`):
+//!
+//! ```txt
+//! h' = h + dh + slope_lat · ρ₀ · (φ - φ₀) + slope_lon · ν₀ · (λ - λ₀) · cos φ
+//! ```
+//!
+//! where ρ₀ and ν₀ are the meridian and prime vertical radii of curvature of
+//! `ellps` at the origin, and the slopes are given in arcseconds. The
+//! horizontal coordinates are passed through unchanged.
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    offset(op, operands, 1.)
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    offset(op, operands, -1.)
+}
+
+// The offset depends on the horizontal coordinates only, so the forward and
+// inverse cases differ only by its sign
+fn offset(op: &Op, operands: &mut dyn CoordinateSet, sign: f64) -> usize {
+    let Ok(&[lam0, phi0, dh, slope_lat, slope_lon]) = op.params.series("PLANE") else {
+        return 0;
+    };
+
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        let (lam, phi) = (coord[0], coord[1]);
+        let offset = dh + slope_lat * (phi - phi0) + slope_lon * (lam - lam0) * phi.cos();
+        coord[2] += sign * offset;
+        operands.set_coord(i, &coord);
+    }
+    n
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 7] = [
+    OpParameter::Flag { key: "inv" },
+    OpParameter::Text { key: "ellps",     default: Some("GRS80") },
+    OpParameter::Real { key: "lat_0",     default: Some(0_f64) },
+    OpParameter::Real { key: "lon_0",     default: Some(0_f64) },
+    OpParameter::Real { key: "dh",        default: Some(0_f64) },
+    OpParameter::Real { key: "slope_lat", default: Some(0_f64) },
+    OpParameter::Real { key: "slope_lon", default: Some(0_f64) },
+];
+
+pub fn new(parameters: &RawParameters, _ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    let ellps = params.ellps(0);
+
+    let lat_0 = params.real("lat_0")?.to_radians();
+    let lon_0 = params.real("lon_0")?.to_radians();
+    let dh = params.real("dh")?;
+
+    // The slopes, converted from arcseconds to metres per radian
+    let rho_0 = ellps.meridian_radius_of_curvature(lat_0);
+    let nu_0 = ellps.prime_vertical_radius_of_curvature(lat_0);
+    let slope_lat = (params.real("slope_lat")? / 3600.).to_radians() * rho_0;
+    let slope_lon = (params.real("slope_lon")? / 3600.).to_radians() * nu_0;
+
+    params
+        .series
+        .insert("PLANE", Vec::from([lon_0, lat_0, dh, slope_lat, slope_lon]));
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The parameters of EPSG:5453 (LN02 height to LHN95 height, Switzerland)
+    #[test]
+    fn vertoffset() -> Result<(), Error> {
+        let mut ctx = Minimal::default();
+        let definition = "
+            vertoffset ellps=bessel lat_0=46.9166666666666666 lon_0=8.183333333333334
+            dh=-0.245 slope_lat=-0.210 slope_lon=-0.032
+        ";
+        let op = ctx.op(definition)?;
+
+        // The expected value is computed directly from the formula for EPSG
+        // method 1046 in IOGP 373-7-2, with the meridian and prime vertical
+        // radii of curvature of Bessel 1841 at the evaluation point, 46°55'N
+        let (rho_0, nu_0) = (6_368_813.994, 6_388_780.256);
+        let (lat_0, lon_0) = (46. + 55. / 60., 8. + 11. / 60.);
+        let (lat, lon, h) = (47.5_f64, 9.5_f64, 1000.);
+        let slope_lat = (-0.210_f64 / 3600.).to_radians();
+        let slope_lon = (-0.032_f64 / 3600.).to_radians();
+        let expected = h - 0.245
+            + slope_lat * rho_0 * (lat - lat_0).to_radians()
+            + slope_lon * nu_0 * (lon - lon_0).to_radians() * lat.to_radians().cos();
+
+        let p = Coor4D::geo(lat, lon, h, 0.);
+        let mut operands = [p];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!((operands[0][2] - expected).abs() < 1e-6);
+        assert_eq!(operands[0][0], p[0]);
+        assert_eq!(operands[0][1], p[1]);
+
+        ctx.apply(op, Inv, &mut operands)?;
+        assert!((operands[0][2] - 1000.).abs() < 1e-9);
+
+        // At the origin, only the constant offset remains
+        let mut operands = [Coor4D::geo(46. + 55. / 60., 8. + 11. / 60., 0., 0.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert!((operands[0][2] + 0.245).abs() < 1e-9);
+        Ok(())
+    }
+}
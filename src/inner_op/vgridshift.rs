@@ -0,0 +1,149 @@
+//! Vertical datum shift using grid interpolation, following the PROJ operator
+//! of the same name.
+//!
+//! The grid value, scaled by `multiplier`, is added to the height in the
+//! forward direction. The default `multiplier` of -1 corresponds to the
+//! conversion of ellipsoidal heights to orthometric heights, using a geoid
+//! model. For multiband grids, the first band is used.
+use super::gridshift::{grid_value, in_time_window, load_grids, time_window};
+use crate::authoring::*;
+
+// ----- F O R W A R D -----------------------------------------------------------------
+
+fn fwd(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    shift(op, operands, Fwd)
+}
+
+// ----- I N V E R S E -----------------------------------------------------------------
+
+fn inv(op: &Op, _ctx: &dyn Context, operands: &mut dyn CoordinateSet) -> usize {
+    shift(op, operands, Inv)
+}
+
+// The vertical shift does not depend on the height, so the forward and
+// inverse cases differ only by the sign of the correction
+fn shift(op: &Op, operands: &mut dyn CoordinateSet, direction: Direction) -> usize {
+    let grids = &op.params.grids;
+    let use_null_grid = op.params.boolean("null_grid");
+    let Ok(mut multiplier) = op.params.real("multiplier") else {
+        return 0;
+    };
+    if direction == Inv {
+        multiplier = -multiplier;
+    }
+
+    let mut successes = 0_usize;
+    let n = operands.len();
+    for i in 0..n {
+        let mut coord = operands.get_coord(i);
+        if !in_time_window(op, coord[3]) {
+            successes += 1;
+            continue;
+        }
+
+        if let Some(d) = grid_value(grids, &coord) {
+            coord[2] += multiplier * d[0];
+            operands.set_coord(i, &coord);
+            successes += 1;
+            continue;
+        }
+
+        if use_null_grid {
+            successes += 1;
+            continue;
+        }
+
+        // No grid found so we stomp on the coordinate
+        operands.set_coord(i, &Coor4D::nan());
+    }
+    successes
+}
+
+// ----- C O N S T R U C T O R ---------------------------------------------------------
+
+#[rustfmt::skip]
+pub const GAMUT: [OpParameter; 5] = [
+    OpParameter::Flag  { key: "inv" },
+    OpParameter::Texts { key: "grids",      default: None },
+    OpParameter::Real  { key: "multiplier", default: Some(-1_f64) },
+    OpParameter::Real  { key: "t_epoch",    default: Some(0_f64) },
+    OpParameter::Text  { key: "t_final",    default: Some("0") },
+];
+
+pub fn new(parameters: &RawParameters, ctx: &dyn Context) -> Result<Op, Error> {
+    let def = &parameters.definition;
+    let mut params = ParsedParameters::new(parameters, &GAMUT)?;
+    load_grids(&mut params, ctx)?;
+    time_window(&mut params, def)?;
+
+    let descriptor = OpDescriptor::new(def, InnerOp(fwd), Some(InnerOp(inv)));
+    let steps = Vec::<Op>::new();
+    let id = OpHandle::new();
+    Ok(Op {
+        descriptor,
+        params,
+        steps,
+        id,
+    })
+}
+
+// ----- T E S T S ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn vgridshift() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let op = ctx.op("vgridshift grids=test.geoid")?;
+        let cph = Coor4D::geo(55., 12., 100., 0.);
+        let mut data = [cph];
+
+        // The geoid height is subtracted, by default
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 1);
+        assert_float_eq!(data[0][2], 100. - 55.12, abs <= 1e-5);
+        assert_eq!(data[0][0], cph[0]);
+        assert_eq!(data[0][1], cph[1]);
+        assert_eq!(ctx.apply(op, Inv, &mut data)?, 1);
+        assert_float_eq!(data[0][2], 100., abs <= 1e-9);
+
+        // ... but the multiplier may change that
+        let op = ctx.op("vgridshift grids=test.geoid multiplier=0.5")?;
+        let mut data = [cph];
+        ctx.apply(op, Fwd, &mut data)?;
+        assert_float_eq!(data[0][2], 100. + 55.12 / 2., abs <= 1e-5);
+
+        // Multiband grids use the first band
+        let op = ctx.op("vgridshift grids=test.datum multiplier=1")?;
+        let mut data = [cph];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 1);
+        assert_eq!(data[0][0], cph[0]);
+        assert!(data[0][2] > 100.);
+        Ok(())
+    }
+
+    #[test]
+    fn null_grid_and_time_window() -> Result<(), Error> {
+        let mut ctx = Plain::default();
+        let ldn = Coor4D::geo(51.505, -0.09, 100., 1999.);
+
+        let op = ctx.op("vgridshift grids=test.geoid")?;
+        let mut data = [ldn];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 0);
+        assert!(data[0][2].is_nan());
+
+        let op = ctx.op("vgridshift grids=@missing.gtx, test.geoid, @null")?;
+        let mut data = [ldn];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 1);
+        assert_eq!(data[0], ldn);
+
+        // Observations after t_epoch are not shifted
+        let op = ctx.op("vgridshift grids=test.geoid t_epoch=1990 t_final=now")?;
+        let mut data = [Coor4D::geo(55., 12., 100., 2000.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut data)?, 1);
+        assert_eq!(data[0][2], 100.);
+        Ok(())
+    }
+}
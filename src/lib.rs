@@ -10,6 +10,8 @@ pub mod prelude {
     pub use crate::Minimal;
     pub use crate::OpHandle;
     #[cfg(feature = "with_plain")]
+    pub use crate::Parallel;
+    #[cfg(feature = "with_plain")]
     pub use crate::Plain;
 
     // Coordinate related
@@ -154,11 +156,15 @@ mod token;
 
 // ---- Context providers ----
 
-// The Context trait and the two implementing built-in types
+// The Context trait and the implementing built-in types
 pub use crate::context::Context;
 
 pub use crate::context::minimal::Minimal;
 #[cfg(feature = "with_plain")]
+pub use crate::context::parallel::CoordinateSubSet;
+#[cfg(feature = "with_plain")]
+pub use crate::context::parallel::Parallel;
+#[cfg(feature = "with_plain")]
 pub use crate::context::plain::Plain;
 
 // Specify which operator to apply in `Context::apply(...)`
@@ -179,6 +185,12 @@ pub use crate::coordinate::AngularUnits;
 pub use crate::coordinate::CoordinateMetadata;
 pub use crate::coordinate::CoordinateSet;
 
+// Grid reference and geocode codecs
+pub use crate::coordinate::geocode::geohash;
+pub use crate::coordinate::geocode::maidenhead;
+pub use crate::coordinate::geocode::pluscode;
+pub use crate::coordinate::mgrs;
+
 // ---- Et cetera ----
 
 // Tokenizing Rust Geodesy operations
@@ -197,6 +209,7 @@ pub use crate::op::OpParameter;
 pub use crate::op::ParsedParameters;
 pub use crate::op::RawParameters;
 
+pub use crate::grid::geotiff::GeoTiffGrid;
 pub use crate::grid::ntv2::Ntv2Grid;
 
 #[cfg(doc)]
@@ -184,6 +184,12 @@ where
 /// have the scaling defined as `k` instead of `k_0`.
 /// - *parse_proj* will replace `k` with `k_0` whenever it is encountered.
 ///
+/// ## Axis order via `axis` parameter
+/// - PROJ supports the `axis` parameter for all operators, while Rust Geodesy
+///   handles axis order and orientation by the separate `axisswap` operator.
+/// - *parse_proj* will replace `axis=...` with an `axisswap axis=...` step,
+///   following (or, for inverted steps, preceding) the step it was given for.
+///
 pub fn parse_proj(definition: &str) -> Result<String, Error> {
     // If it doesn't look like a PROJ string, we return it unchanged
     if definition.contains('|') | !definition.contains("proj") {
@@ -290,7 +296,7 @@ pub fn parse_proj(definition: &str) -> Result<String, Error> {
             }
         }
 
-        tidy_proj(&mut elements)?;
+        let axisswap = tidy_proj(&mut elements)?;
 
         // Skip empty steps, insert pipeline globals, handle step and pipeline
         // inversions, and handle directional omissions (omit_fwd, omit_inv)
@@ -317,6 +323,23 @@ pub fn parse_proj(definition: &str) -> Result<String, Error> {
             }
 
             geodesy_step = elements.join(" ").trim().to_string();
+
+            // A non-default `axis` is handled by an `axisswap` step, which must
+            // come after the step in the forward direction, and before in the inverse.
+            // It must also be omitted whenever the step itself is
+            if let Some(mut axisswap) = axisswap {
+                for omit in ["omit_fwd", "omit_inv"] {
+                    if elements.iter().any(|x| x == omit) {
+                        axisswap += &format!(" {omit}");
+                    }
+                }
+                if elements.contains(&"inv".to_string()) {
+                    geodesy_step = format!("{axisswap} inv | {geodesy_step}");
+                } else {
+                    geodesy_step = format!("{geodesy_step} | {axisswap}");
+                }
+            }
+
             if pipeline_is_inverted {
                 geodesy_steps.insert(0, geodesy_step);
             } else {
@@ -330,7 +353,9 @@ pub fn parse_proj(definition: &str) -> Result<String, Error> {
 // Address some known incompatibilities between PROJ and Rust Geodesy
 // - Ellipsoid definitions
 // - Scaling via the deprecated `k` parameter
-fn tidy_proj(elements: &mut Vec<String>) -> Result<(), Error> {
+// - Axis order and orientation via the `axis` parameter. This is returned
+//   as a separate `axisswap` step, to be inserted next to the current
+fn tidy_proj(elements: &mut Vec<String>) -> Result<Option<String>, Error> {
     // Geodesy only supports ellipsoid definitions as named builtins or ellps=a,rf
     // PROJ has richer support which we try navigate here
     // First we find the indices of ellps, a and rf elements
@@ -379,7 +404,20 @@ fn tidy_proj(elements: &mut Vec<String>) -> Result<(), Error> {
         }
     }
 
-    Ok(())
+    // In PROJ, any operator may take an `axis` parameter, whereas in Rust Geodesy
+    // it is handled by the separate `axisswap` operator. Hence, we remove it from
+    // all other operators, and hand it back to the caller, unless it is the default
+    if elements.first().map(|x| x.as_str()) == Some("axisswap") {
+        return Ok(None);
+    }
+    let Some(i) = elements.iter().position(|x| x.starts_with("axis=")) else {
+        return Ok(None);
+    };
+    let axis = elements.remove(i);
+    if axis == "axis=enu" {
+        return Ok(None);
+    }
+    Ok(Some(format!("axisswap {axis}")))
 }
 
 // ----- T E S T S ------------------------------------------------------------------
@@ -521,6 +559,89 @@ mod tests {
         // Replace occurrences of `k=` with `k_0=`
         assert_eq!(parse_proj("+proj=tmerc +k=1.5")?, "tmerc k_0=1.5");
 
+        // PROJ axisswap steps are passed on unchanged
+        assert_eq!(
+            parse_proj("+proj=pipeline +step +proj=axisswap +order=2,1 +step +proj=utm +zone=32")?,
+            "axisswap order=2,1 | utm zone=32"
+        );
+
+        // The `axis` parameter is handled by an extra axisswap step
+        assert_eq!(
+            parse_proj("+proj=tmerc +axis=wsu +ellps=GRS80")?,
+            "tmerc ellps=GRS80 | axisswap axis=wsu"
+        );
+        assert_eq!(
+            parse_proj("+proj=pipeline +step +inv +proj=tmerc +axis=neu +step +proj=cart")?,
+            "axisswap axis=neu inv | tmerc inv | cart"
+        );
+        assert_eq!(
+            parse_proj("+proj=pipeline +inv +step +proj=tmerc +axis=neu +step +proj=cart")?,
+            "cart inv | axisswap axis=neu inv | tmerc inv"
+        );
+        assert_eq!(parse_proj("+proj=tmerc +axis=enu")?, "tmerc");
+
+        // ...and is omitted along with the step it came from
+        assert_eq!(
+            parse_proj("+proj=pipeline +step +omit_inv +proj=tmerc +axis=neu +step +proj=cart")?,
+            "tmerc omit_fwd | axisswap axis=neu omit_fwd | cart"
+        );
+        assert_eq!(
+            parse_proj("+proj=pipeline +inv +step +omit_fwd +proj=tmerc +axis=neu")?,
+            "axisswap axis=neu omit_inv inv | tmerc inv omit_inv"
+        );
+        let mut ctx = Minimal::default();
+        let op = ctx.op(&parse_proj(
+            "+proj=pipeline +step +omit_inv +proj=tmerc +axis=neu +step +proj=noop",
+        )?)?;
+        let mut operands = [Coor4D::raw(1., 2., 3., 4.)];
+        ctx.apply(op, Fwd, &mut operands)?;
+        assert_eq!(operands[0], Coor4D::raw(1., 2., 3., 4.));
+
+        // PROJ grid shift steps, including optional grids and time windows, are passed on unchanged
+        let definition = parse_proj(
+            "+proj=pipeline +step +proj=hgridshift +grids=@missing.gsb,test.datum +t_epoch=2000 +t_final=now
+             +step +proj=vgridshift +grids=test.geoid +multiplier=1",
+        )?;
+        assert_eq!(
+            definition,
+            "hgridshift grids=@missing.gsb,test.datum t_epoch=2000 t_final=now | vgridshift grids=test.geoid multiplier=1"
+        );
+        let mut ctx = Plain::default();
+        let op = ctx.op(&definition)?;
+        let mut operands = [Coor4D::geo(55., 12., 0., 1999.)];
+        assert_eq!(ctx.apply(op, Fwd, &mut operands)?, 1);
+        assert!(operands[0][2] > 55.);
+
+        // ...and so are triangulation based shifts and deformation models
+        assert_eq!(
+            parse_proj("+proj=tinshift +file=test_tinshift.json")?,
+            "tinshift file=test_tinshift.json"
+        );
+        assert_eq!(
+            parse_proj("+proj=defmodel +model=test_defmodel.json")?,
+            "defmodel model=test_defmodel.json"
+        );
+
+        // ...as are polynomial transformations
+        assert_eq!(
+            parse_proj("+proj=horner +deg=1 +fwd_c=600,500,0,1 +range=1000")?,
+            "horner deg=1 fwd_c=600,500,0,1 range=1000"
+        );
+        assert_eq!(
+            parse_proj("+proj=topocentric +lat_0=55 +lon_0=5 +h_0=200")?,
+            "topocentric lat_0=55 lon_0=5 h_0=200"
+        );
+
+        // ...and offsets
+        assert_eq!(
+            parse_proj("+proj=geogoffset +dlat=-36 +dlon=72 +dh=100")?,
+            "geogoffset dlat=-36 dlon=72 dh=100"
+        );
+        assert_eq!(
+            parse_proj("+proj=vertoffset +lat_0=47 +lon_0=8 +dh=-0.245 +slope_lat=-0.21")?,
+            "vertoffset lat_0=47 lon_0=8 dh=-0.245 slope_lat=-0.21"
+        );
+
         Ok(())
     }
 }